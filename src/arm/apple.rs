@@ -487,14 +487,18 @@ impl TCpuDisplay for Cpu {
         Self: std::fmt::Debug,
     {
         println!(
-            "Main ID Register (MIDR): 0x{:X}",
-            self.raw_midr().iter().next().unwrap_or(&0)
+            "Main ID Register (MIDR): 0x{}",
+            fmt_hex(*self.raw_midr().iter().next().unwrap_or(&0) as u32, 0)
         );
         if let Some(midr) = self.midr() {
-            println!("Implementer: 0x{:X} ({})", midr.implementer, self.vendor());
-            println!("Variant: 0x{:X}", midr.variant);
-            println!("Part Number: 0x{:X}", midr.part);
-            println!("Revision: 0x{:X}", midr.revision);
+            println!(
+                "Implementer: 0x{} ({})",
+                fmt_hex(midr.implementer as u32, 0),
+                self.vendor()
+            );
+            println!("Variant: 0x{}", fmt_hex(midr.variant as u32, 0));
+            println!("Part Number: 0x{}", fmt_hex(midr.part as u32, 0));
+            println!("Revision: 0x{}", fmt_hex(midr.revision as u32, 0));
         }
         println!("{:#?}", self);
     }
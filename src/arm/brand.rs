@@ -13,13 +13,17 @@ pub enum Vendor {
     Broadcom,
     Cavium,
     DEC,
+    Faraday,
     Freescale,
     Fujitsu,
+    HiSilicon,
     Infineon,
     Intel,
     Marvell,
     Mediatek,
+    Microsoft,
     Nvidia,
+    Phytium,
     Rockchip,
     Samsung,
     Qualcomm,
@@ -46,13 +50,17 @@ impl From<Vendor> for &'static str {
             Broadcom => "Broadcom",
             Cavium => "Cavium",
             DEC => "DEC",
-            Freescale => "Motorolla or Freescale Semiconductor",
+            Faraday => "Faraday Technology",
+            Freescale => "Motorola or Freescale Semiconductor",
             Fujitsu => "Fujitsu",
+            HiSilicon => "HiSilicon",
             Infineon => "Infineon",
             Intel => "Intel",
             Marvell => "Marvell",
             Mediatek => "Mediatek",
+            Microsoft => "Microsoft",
             Nvidia => "Nvidia",
+            Phytium => "Phytium",
             Rockchip => "Rockchip",
             Samsung => "Samsung",
             Qualcomm => "Qualcomm",
@@ -61,6 +69,12 @@ impl From<Vendor> for &'static str {
     }
 }
 
+impl core::fmt::Display for Vendor {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str((*self).into())
+    }
+}
+
 impl From<usize> for Vendor {
     fn from(v: usize) -> Self {
         // Several mappings taken from https://github.com/bp0/armids/blob/master/arm.ids
@@ -71,6 +85,7 @@ impl From<usize> for Vendor {
             0x43 => Self::Cavium,
             0x44 => Self::DEC,
             0x46 => Self::Fujitsu,
+            0x48 => Self::HiSilicon,
             0x49 => Self::Infineon,
             0x4d => Self::Freescale,
             0x4e => Self::Nvidia,
@@ -79,9 +94,32 @@ impl From<usize> for Vendor {
             0x53 => Self::Samsung,
             0x56 => Self::Marvell,
             0x61 => Self::Apple,
+            0x66 => Self::Faraday,
             0x69 => Self::Intel,
+            0x6d => Self::Microsoft,
+            0x70 => Self::Phytium,
             0xC0 => Self::Ampere,
             _ => Self::Unknown,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vendor_from_implementer() {
+        assert_eq!(Vendor::from(IMPL_ARM), Vendor::Arm);
+        assert_eq!(Vendor::from(IMPL_QUALCOMM), Vendor::Qualcomm);
+        assert_eq!(Vendor::from(IMPL_APPLE), Vendor::Apple);
+        assert_eq!(Vendor::from(0x48), Vendor::HiSilicon);
+        assert_eq!(Vendor::from(0xFF), Vendor::Unknown);
+    }
+
+    #[test]
+    fn test_vendor_display() {
+        assert_eq!(Vendor::Nvidia.to_string(), "Nvidia");
+        assert_eq!(Vendor::Unknown.to_string(), "Unknown");
+    }
+}
@@ -139,14 +139,18 @@ impl TCpuDisplay for Cpu {
         Self: std::fmt::Debug,
     {
         println!(
-            "Main ID Register (MIDR): 0x{:X}",
-            self.raw_midr().iter().next().unwrap_or(&0)
+            "Main ID Register (MIDR): 0x{}",
+            fmt_hex(*self.raw_midr().iter().next().unwrap_or(&0) as u32, 0)
         );
         if let Some(midr) = self.midr() {
-            println!("Implementer: 0x{:X} ({})", midr.implementer, self.vendor());
-            println!("Variant: 0x{:X}", midr.variant);
-            println!("Part Number: 0x{:X}", midr.part);
-            println!("Revision: 0x{:X}", midr.revision);
+            println!(
+                "Implementer: 0x{} ({})",
+                fmt_hex(midr.implementer as u32, 0),
+                self.vendor()
+            );
+            println!("Variant: 0x{}", fmt_hex(midr.variant as u32, 0));
+            println!("Part Number: 0x{}", fmt_hex(midr.part as u32, 0));
+            println!("Revision: 0x{}", fmt_hex(midr.revision as u32, 0));
         }
         println!("{:#?}", self);
     }
@@ -170,7 +174,47 @@ impl TArmCpu for Cpu {
     }
 }
 
+/// A group of identical cores, as in a big.LITTLE or DynamIQ design
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreCluster {
+    pub kind: CoreType,
+    pub name: String,
+    pub count: u32,
+}
+
 impl Cpu {
+    /// Lists each distinct core type, fastest first, e.g. Cortex-A78 and
+    /// Cortex-A55.
+    ///
+    /// Cores with different revisions of the same part are counted
+    /// together. If the MIDR could only be read for the current core,
+    /// this has just that core.
+    pub fn cluster_info(&self) -> Vec<CoreCluster> {
+        let mut clusters: Vec<CoreCluster> = Vec::new();
+
+        for ((kind, name, _), core) in &self.cores {
+            let name = name.as_deref().unwrap_or(UNK);
+            match clusters
+                .iter_mut()
+                .find(|c| c.kind == *kind && c.name == name)
+            {
+                Some(cluster) => cluster.count += core.count,
+                None => clusters.push(CoreCluster {
+                    kind: *kind,
+                    name: name.to_string(),
+                    count: core.count,
+                }),
+            }
+        }
+
+        clusters
+    }
+
+    /// Whether there is more than one kind of core
+    pub fn is_heterogeneous(&self) -> bool {
+        self.cluster_info().len() > 1
+    }
+
     #[cfg(target_os = "linux")]
     fn detect_linux_midrs() -> Vec<usize> {
         let mut midrs = Vec::new();
@@ -319,3 +363,37 @@ impl Cpu {
         cores
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster_info() {
+        let mut cpu = Cpu::default();
+        let cores = [
+            (CoreType::Performance, "Cortex-A78", 0x411F_D410, 4),
+            (CoreType::Efficiency, "Cortex-A55", 0x412F_D050, 2),
+            (CoreType::Efficiency, "Cortex-A55", 0x413F_D050, 2),
+        ];
+        for (kind, name, midr, count) in cores {
+            cpu.cores.insert(
+                (kind, Some(name.to_string()), Midr::new(midr)),
+                CpuCore {
+                    kind,
+                    name: Some(name.to_string()),
+                    cache: None,
+                    count,
+                },
+            );
+        }
+
+        let clusters = cpu.cluster_info();
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].name, "Cortex-A78");
+        assert_eq!(clusters[0].count, 4);
+        assert_eq!(clusters[1].kind, CoreType::Efficiency);
+        assert_eq!(clusters[1].count, 4);
+        assert!(cpu.is_heterogeneous());
+    }
+}
@@ -0,0 +1,335 @@
+//! AArch64 ID register reads.
+//!
+//! The `ID_AA64ISAR0_EL1`, `ID_AA64ISAR1_EL1` and `ID_AA64PFR0_EL1`
+//! registers encode the instruction set extensions as 4-bit fields.
+//! They are EL1 registers, so a read from user space only works when the
+//! kernel traps and emulates it. Linux does this, and says so with the
+//! `cpuid` hwcap.
+//!
+//! The cache ID registers are never emulated, so they aren't read here. On
+//! Linux, [`cache_info`] packs the kernel's sysfs copy of their fields back
+//! into the register layouts, so they go through the same decoding as values
+//! read at EL1.
+
+use crate::common::cache::CacheType;
+use std::collections::BTreeMap;
+
+/// Reads `ID_AA64ISAR0_EL1`, the first instruction set attribute register.
+///
+/// # Safety
+///
+/// The OS must trap and emulate ID register reads from user space, which
+/// Linux reports with the `cpuid` hwcap. Otherwise the read is an illegal
+/// instruction.
+#[must_use]
+pub unsafe fn read_isar0() -> u64 {
+    let isar0: u64;
+    unsafe {
+        core::arch::asm!("mrs {isar0}, id_aa64isar0_el1", isar0 = out(reg) isar0, options(nomem, nostack));
+    }
+    isar0
+}
+
+/// Reads `ID_AA64ISAR1_EL1`, the second instruction set attribute register.
+///
+/// # Safety
+///
+/// Same requirements as [`read_isar0`].
+#[must_use]
+pub unsafe fn read_isar1() -> u64 {
+    let isar1: u64;
+    unsafe {
+        core::arch::asm!("mrs {isar1}, id_aa64isar1_el1", isar1 = out(reg) isar1, options(nomem, nostack));
+    }
+    isar1
+}
+
+/// Reads `ID_AA64PFR0_EL1`, the first processor feature register.
+///
+/// # Safety
+///
+/// Same requirements as [`read_isar0`].
+#[must_use]
+pub unsafe fn read_pfr0() -> u64 {
+    let pfr0: u64;
+    unsafe {
+        core::arch::asm!("mrs {pfr0}, id_aa64pfr0_el1", pfr0 = out(reg) pfr0, options(nomem, nostack));
+    }
+    pfr0
+}
+
+/// Gets the 4-bit field starting at `shift`
+const fn field(reg: u64, shift: u32) -> u64 {
+    (reg >> shift) & 0xF
+}
+
+/// Reads and decodes the ID registers.
+///
+/// # Safety
+///
+/// Same requirements as [`read_isar0`].
+pub unsafe fn get_features_from_id_regs() -> BTreeMap<String, bool> {
+    unsafe { decode_id_regs(read_isar0(), read_isar1(), read_pfr0()) }
+}
+
+/// Decodes the ID register fields into features, using the same names
+/// as the Linux hwcaps.
+pub fn decode_id_regs(isar0: u64, isar1: u64, pfr0: u64) -> BTreeMap<String, bool> {
+    let mut features: BTreeMap<String, bool> = BTreeMap::new();
+    let mut set = |name: &str, present: bool| {
+        features.insert(name.to_string(), present);
+    };
+
+    // ID_AA64PFR0_EL1. FP and AdvSIMD are signed, with 0xF meaning absent
+    let fp = field(pfr0, 16);
+    let simd = field(pfr0, 20);
+    set("fp", fp != 0xF);
+    set("fphp", fp == 1);
+    set("asimd", simd != 0xF);
+    set("asimdhp", simd == 1);
+    set("sve", field(pfr0, 32) >= 1);
+    set("dit", field(pfr0, 48) >= 1);
+    set("csv2", field(pfr0, 56) >= 1);
+    set("csv3", field(pfr0, 60) >= 1);
+
+    // ID_AA64ISAR0_EL1
+    set("aes", field(isar0, 4) >= 1);
+    set("pmull", field(isar0, 4) >= 2);
+    set("sha1", field(isar0, 8) >= 1);
+    set("sha2", field(isar0, 12) >= 1);
+    set("sha512", field(isar0, 12) >= 2);
+    set("crc32", field(isar0, 16) >= 1);
+    set("atomics", field(isar0, 20) >= 2);
+    set("asimdrdm", field(isar0, 28) >= 1);
+    set("sha3", field(isar0, 32) >= 1);
+    set("sm3", field(isar0, 36) >= 1);
+    set("sm4", field(isar0, 40) >= 1);
+    set("asimddp", field(isar0, 44) >= 1);
+    set("asimdfhm", field(isar0, 48) >= 1);
+    set("flagm", field(isar0, 52) >= 1);
+    set("flagm2", field(isar0, 52) >= 2);
+
+    // ID_AA64ISAR1_EL1
+    set("dcpop", field(isar1, 0) >= 1);
+    set(
+        "pauth",
+        field(isar1, 4) >= 1 || field(isar1, 8) >= 1 || field(isar1, 24) >= 1,
+    );
+    set("jscvt", field(isar1, 12) >= 1);
+    set("fcma", field(isar1, 16) >= 1);
+    set("lrcpc", field(isar1, 20) >= 1);
+    set("frintts", field(isar1, 32) >= 1);
+    set("sb", field(isar1, 36) >= 1);
+    set("bf16", field(isar1, 44) >= 1);
+    set("i8mm", field(isar1, 52) >= 1);
+
+    features
+}
+
+// ----------------------------------------------------------------------------
+// Cache ID registers
+// ----------------------------------------------------------------------------
+
+/// One cache, described by `CCSIDR_EL1`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheInfo {
+    pub level: u8,
+    pub kind: CacheType,
+    /// Size in bytes
+    pub size: u32,
+    /// Line length in bytes
+    pub line_size: u32,
+    pub assoc: u32,
+}
+
+impl CacheInfo {
+    /// Short name, such as "L1d", "L1i" or "L2"
+    pub fn name(&self) -> String {
+        let suffix = match self.kind {
+            CacheType::Data => "d",
+            CacheType::Instruction => "i",
+            _ => "",
+        };
+
+        format!("L{}{suffix}", self.level)
+    }
+}
+
+/// Reads the caches of the first CPU from `/sys/devices/system/cpu/cpu0/cache`.
+///
+/// Returns an empty list if sysfs doesn't describe the caches.
+#[cfg(target_os = "linux")]
+pub fn cache_info() -> Vec<CacheInfo> {
+    let root = std::path::Path::new("/sys/devices/system/cpu/cpu0/cache");
+    let mut clidr = 0u64;
+    let mut ccsidrs: BTreeMap<(u8, bool), u64> = BTreeMap::new();
+
+    for index in 0.. {
+        let dir = root.join(format!("index{index}"));
+        let read = |name: &str| {
+            std::fs::read_to_string(dir.join(name))
+                .ok()
+                .map(|s| s.trim().to_string())
+        };
+        let number = |name: &str| read(name).and_then(|s| s.parse::<u64>().ok()).unwrap_or(0);
+
+        let Some(kind) = read("type") else {
+            break;
+        };
+        let level = number("level");
+        if !(1..=7).contains(&level) {
+            continue;
+        }
+
+        // The CLIDR_EL1 Ctype field for the level, and whether it's the
+        // instruction cache, the CSSELR_EL1.InD bit
+        let (ctype, instruction) = match kind.as_str() {
+            "Data" => (0b010, false),
+            "Instruction" => (0b001, true),
+            "Unified" => (0b100, false),
+            _ => continue,
+        };
+
+        clidr |= ctype << (3 * (level - 1));
+        ccsidrs.insert(
+            (level as u8, instruction),
+            encode_ccsidr(
+                number("ways_of_associativity"),
+                number("number_of_sets"),
+                number("coherency_line_size"),
+            ),
+        );
+    }
+
+    decode_caches(clidr, true, |level, instruction| {
+        ccsidrs.get(&(level, instruction)).copied().unwrap_or(0)
+    })
+}
+
+/// Packs cache geometry into the `CCSIDR_EL1` layout with FEAT_CCIDX, which
+/// has the widest fields
+#[cfg(any(target_os = "linux", test))]
+fn encode_ccsidr(ways: u64, sets: u64, line_size: u64) -> u64 {
+    let line_bits = u64::from(line_size.max(16).ilog2()) - 4;
+
+    (sets.saturating_sub(1) & 0xFF_FFFF) << 32
+        | (ways.saturating_sub(1) & 0x1F_FFFF) << 3
+        | (line_bits & 0b111)
+}
+
+/// Walks the cache levels in `clidr`, using `ccsidr` to get the details
+/// for a (level, is-instruction-cache) pair. Levels start at 1, as in the
+/// architecture manual, not at the `CSSELR_EL1` encoding.
+pub fn decode_caches(clidr: u64, ccidx: bool, ccsidr: impl Fn(u8, bool) -> u64) -> Vec<CacheInfo> {
+    let mut caches = Vec::new();
+
+    for level in 1..=7u8 {
+        let ctype = (clidr >> (3 * u32::from(level - 1))) & 0b111;
+        let kinds: &[CacheType] = match ctype {
+            0 => break,
+            1 => &[CacheType::Instruction],
+            2 => &[CacheType::Data],
+            3 => &[CacheType::Data, CacheType::Instruction],
+            4 => &[CacheType::Unified],
+            _ => continue,
+        };
+
+        for &kind in kinds {
+            let reg = ccsidr(level, kind == CacheType::Instruction);
+            caches.push(decode_ccsidr(level, kind, reg, ccidx));
+        }
+    }
+
+    caches
+}
+
+/// Decodes the line size, ways and sets from a `CCSIDR_EL1` value
+fn decode_ccsidr(level: u8, kind: CacheType, ccsidr: u64, ccidx: bool) -> CacheInfo {
+    let line_size = 1u32 << ((ccsidr & 0b111) + 4);
+    let (assoc, sets) = if ccidx {
+        (
+            ((ccsidr >> 3) & 0x1F_FFFF) + 1,
+            ((ccsidr >> 32) & 0xFF_FFFF) + 1,
+        )
+    } else {
+        (((ccsidr >> 3) & 0x3FF) + 1, ((ccsidr >> 13) & 0x7FFF) + 1)
+    };
+
+    CacheInfo {
+        level,
+        kind,
+        size: (u64::from(line_size) * assoc * sets) as u32,
+        line_size,
+        assoc: assoc as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_id_regs() {
+        // AES+PMULL, SHA1, SHA2, CRC32, LSE, RDM, DotProd, FP16 and
+        // AdvSIMD FP16, CSV2, CSV3, DPB and LRCPC
+        let isar0 = 0x0000_1000_1021_1120;
+        let isar1 = 0x0000_0000_0010_0001;
+        let pfr0 = 0x1100_0000_0011_1111;
+        let features = decode_id_regs(isar0, isar1, pfr0);
+
+        for name in [
+            "fp", "fphp", "asimd", "asimdhp", "aes", "pmull", "sha1", "sha2", "crc32", "atomics",
+            "asimdrdm", "asimddp", "dcpop", "lrcpc", "csv2", "csv3",
+        ] {
+            assert_eq!(features.get(name), Some(&true), "{name}");
+        }
+
+        for name in ["sha512", "sha3", "sve", "pauth", "jscvt", "fcma", "bf16"] {
+            assert_eq!(features.get(name), Some(&false), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_decode_no_fp() {
+        let features = decode_id_regs(0, 0, 0x0000_0000_00FF_0000);
+        assert_eq!(features.get("fp"), Some(&false));
+        assert_eq!(features.get("asimd"), Some(&false));
+    }
+
+    #[test]
+    fn test_decode_caches() {
+        // Split L1, unified L2
+        let clidr = 0b100_011;
+        let caches = decode_caches(clidr, false, |level, instruction| {
+            match (level, instruction) {
+                // 64KB, 64-byte lines, 4-way: 256 sets
+                (1, false) => (255 << 13) | (3 << 3) | 2,
+                // 32KB, 64-byte lines, 4-way: 128 sets
+                (1, true) => (127 << 13) | (3 << 3) | 2,
+                // 512KB, 64-byte lines, 8-way: 1024 sets
+                _ => (1023 << 13) | (7 << 3) | 2,
+            }
+        });
+
+        assert_eq!(caches.len(), 3);
+        assert_eq!(caches[0].kind, CacheType::Data);
+        assert_eq!(caches[0].size, 64 * 1024);
+        assert_eq!(caches[0].line_size, 64);
+        assert_eq!(caches[1].kind, CacheType::Instruction);
+        assert_eq!(caches[1].size, 32 * 1024);
+        assert_eq!(caches[2].level, 2);
+        assert_eq!(caches[2].size, 512 * 1024);
+        assert_eq!(caches[2].assoc, 8);
+        assert_eq!(caches[2].name(), "L2");
+    }
+
+    #[test]
+    fn test_encode_ccsidr() {
+        // 64KB, 64-byte lines, 4-way: 256 sets
+        let cache = decode_ccsidr(1, CacheType::Data, encode_ccsidr(4, 256, 64), true);
+        assert_eq!(cache.size, 64 * 1024);
+        assert_eq!(cache.line_size, 64);
+        assert_eq!(cache.assoc, 4);
+        assert_eq!(cache.name(), "L1d");
+    }
+}
@@ -274,7 +274,17 @@ pub fn get_all_features() -> BTreeMap<&'static str, String> {
 
     // Combine cpuinfo and hwcap sources
     let cpuinfo = get_features_from_cpuinfo();
-    let hwcap = get_features_from_hwcap();
+    #[allow(unused_mut)]
+    let mut hwcap = get_features_from_hwcap();
+
+    #[cfg(target_arch = "aarch64")]
+    if hwcap.get("cpuid").copied().unwrap_or(false) {
+        // SAFETY: the kernel emulates ID register reads when it sets HWCAP_CPUID
+        let id_regs = unsafe { super::fns::get_features_from_id_regs() };
+        for (name, present) in id_regs {
+            *hwcap.entry(name).or_default() |= present;
+        }
+    }
 
     // Base features
     detected.insert(
@@ -4,8 +4,10 @@
 mod brand;
 pub mod cpu;
 pub mod features;
+#[cfg(target_arch = "aarch64")]
+pub mod fns;
 pub mod micro_arch;
-use crate::common::{CliFlags, CoreType, CpuDisplay};
+use crate::common::{CliFlags, ConsoleWriter, CoreType, CpuDisplay};
 pub use micro_arch::{CpuCore, Midr};
 use std::collections::{BTreeMap, HashSet};
 
@@ -51,14 +53,15 @@ trait TArmCpu {
     fn vendor(&self) -> &str;
 }
 
-impl CpuDisplay {
+impl CpuDisplay<'_> {
     pub fn display(
         cpu_arch: &micro_arch::CpuArch,
         cores: &BTreeMap<(CoreType, Option<String>, Midr), CpuCore>,
         features: &BTreeMap<&'static str, String>,
         flags: CliFlags,
     ) {
-        let cpu = CpuDisplay { flags };
+        let mut out = ConsoleWriter;
+        let cpu = CpuDisplay::new(flags, &mut out);
 
         println!();
 
@@ -111,6 +114,17 @@ impl CpuDisplay {
             cpu.display_cache(core.cache, &cc, 0);
         }
 
+        // The cache rows above don't show the line size
+        #[cfg(all(target_arch = "aarch64", target_os = "linux"))]
+        for (i, cache) in fns::cache_info().iter().enumerate() {
+            let label = if i == 0 {
+                cpu.inline_sublabel("Cache Line", &cache.name())
+            } else {
+                cpu.sublabel(&cache.name())
+            };
+            println!("{label}{} bytes", cache.line_size);
+        }
+
         // Display features
         if !features.is_empty() {
             let keys = ["Base", "SIMD", "Security", "Atomics", "Fp", "Misc"];
@@ -3,43 +3,104 @@ use super::constants::*;
 
 use alloc::format;
 use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt::{self, Write};
 
 use crate::common::CliFlags;
 #[cfg(dos)]
-use crate::println;
+use crate::print;
 
-pub struct CpuDisplay {
+/// Default console width, matching the 80-column DOS text mode
+pub const DEFAULT_COLS: usize = 80;
+
+/// Default width labels are right-aligned to
+pub const DEFAULT_LABEL_WIDTH: usize = 14;
+
+/// Line ending used for display output
+#[cfg(dos)]
+pub const NEWLINE: &str = "\r\n";
+/// Line ending used for display output
+#[cfg(not(dos))]
+pub const NEWLINE: &str = "\n";
+
+/// Writes a formatted line to a [`CpuDisplay`]'s output, like `println!`
+macro_rules! outln {
+    ($disp:expr) => {
+        $disp.line(format_args!(""))
+    };
+    ($disp:expr, $($arg:tt)*) => {
+        $disp.line(format_args!($($arg)*))
+    };
+}
+pub(crate) use outln;
+
+/// A [`fmt::Write`] sink for the console, through `print!`
+pub struct ConsoleWriter;
+
+impl Write for ConsoleWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        print!("{s}");
+        Ok(())
+    }
+}
+
+pub struct CpuDisplay<'a> {
     pub flags: CliFlags,
+    out: RefCell<&'a mut dyn Write>,
 }
 
-impl CpuDisplay {
+impl<'a> CpuDisplay<'a> {
+    pub fn new(flags: CliFlags, out: &'a mut dyn Write) -> Self {
+        Self {
+            flags,
+            out: RefCell::new(out),
+        }
+    }
+
+    /// Write a line of formatted output
+    pub fn line(&self, args: fmt::Arguments) {
+        let mut out = self.out.borrow_mut();
+        let _ = out.write_fmt(args);
+        let _ = out.write_str(NEWLINE);
+    }
+
+    /// A label at the default width, without color
     pub fn raw_label(s: &str) -> String {
-        format!("{:>14}: ", s)
+        format!("{:>w$}: ", s, w = DEFAULT_LABEL_WIDTH)
     }
 
+    /// A sublabel at the default width, without color
     pub fn raw_sublabel(s: &str) -> String {
-        format!("{:>16}{}: ", "", s)
+        format!("{:>w$}{}: ", "", s, w = DEFAULT_LABEL_WIDTH + 2)
     }
 
+    /// A label and sublabel on one line at the default width, without color
     pub fn raw_inline_sublabel(label: &str, sub: &str) -> String {
-        format!("{:>14}: {:1}: ", label, sub)
+        format!("{:>w$}: {:1}: ", label, sub, w = DEFAULT_LABEL_WIDTH)
+    }
+
+    /// Blank space the width of a label, to line text up with the values
+    pub fn indent(&self) -> String {
+        format!("{:>w$}", "", w = self.flags.label_width + 2)
     }
 
     pub fn label(&self, s: &str) -> String {
+        let w = self.flags.label_width;
         if !self.flags.color {
-            Self::raw_label(s)
+            format!("{s:>w$}: ")
         } else {
-            format!("{}{s:>14}{}: ", Self::ansi(ANSI_GREEN), ANSI_RESET)
+            format!("{}{s:>w$}{}: ", Self::ansi(ANSI_GREEN), ANSI_RESET)
         }
     }
 
     pub fn sublabel(&self, s: &str) -> String {
         if !self.flags.color {
-            Self::raw_sublabel(s)
+            format!("{}{s}: ", self.indent())
         } else {
             format!(
-                "{:>16}{}{s}{}: ",
-                "",
+                "{}{}{s}{}: ",
+                self.indent(),
                 Self::ansi(ANSI_BRIGHT_BLUE),
                 ANSI_RESET
             )
@@ -47,11 +108,12 @@ impl CpuDisplay {
     }
 
     pub fn inline_sublabel(&self, label: &str, sub: &str) -> String {
+        let w = self.flags.label_width;
         if !self.flags.color {
-            Self::raw_inline_sublabel(label, sub)
+            format!("{label:>w$}: {sub:1}: ")
         } else {
             format!(
-                "{}{label:>14}{}: {}{sub:1}{}: ",
+                "{}{label:>w$}{}: {}{sub:1}{}: ",
                 Self::ansi(ANSI_GREEN),
                 ANSI_RESET,
                 Self::ansi(ANSI_BRIGHT_BLUE),
@@ -70,15 +132,89 @@ impl CpuDisplay {
 
     pub fn simple_line(&self, l: &str, v: &str) {
         let l = self.label(l);
-        println!("{}{}", l, v);
+        outln!(self, "{}{}", l, v);
+        self.newline();
+    }
 
-        #[cfg(not(dos))]
-        println!();
+    /// Print a label and value, wrapping the value at the configured width.
+    ///
+    /// Continuation lines are indented to line up with the value column.
+    /// `label` is expected to be pre-formatted, e.g. by [`CpuDisplay::label`].
+    pub fn wrapped_line(&self, label: &str, v: &str) {
+        let indent = Self::visible_len(label);
+        let avail = if self.flags.width == 0 {
+            usize::MAX
+        } else {
+            self.flags.width.saturating_sub(indent)
+        };
+
+        for (i, line) in Self::wrap(v, avail).iter().enumerate() {
+            if i == 0 {
+                outln!(self, "{}{}", label, line);
+            } else {
+                outln!(self, "{:indent$}{}", "", line);
+            }
+        }
+    }
+
+    /// Split a space-separated value into lines of at most `avail` characters.
+    ///
+    /// Words are never broken, so a single word longer than `avail`
+    /// gets a line of its own.
+    pub fn wrap(v: &str, avail: usize) -> Vec<&str> {
+        if avail == 0 || v.len() <= avail {
+            return alloc::vec![v];
+        }
+
+        let mut lines = Vec::new();
+        let mut line: Option<(usize, usize)> = None;
+        let mut pos = 0;
+
+        for word in v.split(' ') {
+            let (word_start, word_end) = (pos, pos + word.len());
+            pos = word_end + 1;
+
+            if word.is_empty() {
+                continue;
+            }
+
+            line = match line {
+                Some((start, end)) if word_end - start > avail => {
+                    lines.push(&v[start..end]);
+                    Some((word_start, word_end))
+                }
+                Some((start, _)) => Some((start, word_end)),
+                None => Some((word_start, word_end)),
+            };
+        }
+
+        if let Some((start, end)) = line {
+            lines.push(&v[start..end]);
+        }
+
+        lines
+    }
+
+    /// The number of columns a string takes up on screen, ignoring ANSI color codes
+    pub fn visible_len(s: &str) -> usize {
+        let mut len = 0;
+        let mut in_escape = false;
+
+        for c in s.chars() {
+            match c {
+                '\x1b' => in_escape = true,
+                'm' if in_escape => in_escape = false,
+                _ if in_escape => (),
+                _ => len += 1,
+            }
+        }
+
+        len
     }
 
-    pub fn newline() {
+    pub fn newline(&self) {
         #[cfg(not(dos))]
-        println!();
+        outln!(self);
     }
 
     pub fn format_frequency(mhz: impl Into<u64>) -> String {
@@ -109,14 +245,15 @@ impl CpuDisplay {
         if let Some(cache) = cache {
             match cache.l1 {
                 Level1Cache::Unified(l1) => {
-                    println!("{}L1: Unified {:>4} KB", self.label("Cache"), l1.size);
+                    outln!(self, "{}L1: Unified {:>4} KB", self.label("Cache"), l1.size);
                 }
                 Level1Cache::Split { data, instruction } => {
                     let data_count: String = cache_count(data.share_count);
                     let instruction_count: String = cache_count(instruction.share_count);
 
                     if data.assoc > 0 {
-                        println!(
+                        outln!(
+                            self,
                             "{}{}{} KB, {}-way",
                             self.inline_sublabel("Cache", "L1d"),
                             &data_count,
@@ -124,7 +261,8 @@ impl CpuDisplay {
                             data.assoc
                         );
                     } else {
-                        println!(
+                        outln!(
+                            self,
                             "{}{}{} KB",
                             self.inline_sublabel("Cache", "L1d"),
                             &data_count,
@@ -133,7 +271,8 @@ impl CpuDisplay {
                     }
 
                     if instruction.assoc > 0 {
-                        println!(
+                        outln!(
+                            self,
                             "{}{}{} KB, {}-way",
                             self.sublabel("L1i"),
                             &instruction_count,
@@ -141,7 +280,8 @@ impl CpuDisplay {
                             instruction.assoc
                         );
                     } else {
-                        println!(
+                        outln!(
+                            self,
                             "{}{}{} KB",
                             self.sublabel("L1i"),
                             &instruction_count,
@@ -156,7 +296,8 @@ impl CpuDisplay {
                 let (num, unit) = Self::cache_size(l2.size);
 
                 if l2.assoc > 0 {
-                    println!(
+                    outln!(
+                        self,
                         "{} {}{} {}, {}-way",
                         self.sublabel("L2"),
                         &count,
@@ -165,7 +306,7 @@ impl CpuDisplay {
                         l2.assoc
                     );
                 } else {
-                    println!("{} {}{} {}", self.sublabel("L2"), &count, num, unit);
+                    outln!(self, "{} {}{} {}", self.sublabel("L2"), &count, num, unit);
                 }
             }
 
@@ -178,7 +319,8 @@ impl CpuDisplay {
                 };
 
                 if l3.assoc > 0 {
-                    println!(
+                    outln!(
+                        self,
                         "{} {}{} {}, {}-way",
                         self.sublabel("L3"),
                         &count,
@@ -187,10 +329,10 @@ impl CpuDisplay {
                         l3.assoc
                     );
                 } else {
-                    println!("{} {}{} {}", self.sublabel("L3"), &count, num, unit);
+                    outln!(self, "{} {}{} {}", self.sublabel("L3"), &count, num, unit);
                 }
             }
-            Self::newline();
+            self.newline();
         }
     }
 
@@ -206,3 +348,160 @@ impl CpuDisplay {
         (num, unit)
     }
 }
+
+/// A number from [`fmt_hex`] or [`fmt_dec`], with its digits in a buffer on
+/// the stack, so formatting it doesn't allocate.
+///
+/// The digits are built directly, so they are the same on DOS and std. The
+/// width and alignment of the format string, as in `{:<4}`, still apply.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FmtNum {
+    buf: [u8; FmtNum::MAX_DIGITS],
+    len: usize,
+}
+
+impl FmtNum {
+    /// Zero-padding past this many digits is cut off
+    pub const MAX_DIGITS: usize = 16;
+
+    /// Formats `n` in `radix`, with upper-case digits, zero-padded to at
+    /// least `width` digits.
+    fn new(mut n: u32, radix: u32, width: usize) -> Self {
+        const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+        // Filled from the end, since the lowest digit comes first
+        let mut buf = [b'0'; Self::MAX_DIGITS];
+        let mut start = Self::MAX_DIGITS;
+        loop {
+            start -= 1;
+            buf[start] = DIGITS[(n % radix) as usize];
+            n /= radix;
+            if n == 0 {
+                break;
+            }
+        }
+
+        let len = (Self::MAX_DIGITS - start).max(width.min(Self::MAX_DIGITS));
+        buf.copy_within(Self::MAX_DIGITS - len.., 0);
+
+        Self { buf, len }
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        // Only ASCII digits are written
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or_default()
+    }
+}
+
+impl core::fmt::Display for FmtNum {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+/// Formats `n` as upper-case hex, zero-padded to at least `width` digits.
+#[must_use]
+pub fn fmt_hex(n: u32, width: usize) -> FmtNum {
+    FmtNum::new(n, 16, width)
+}
+
+/// Formats `n` in decimal, zero-padded to at least `width` digits.
+#[must_use]
+pub fn fmt_dec(n: u32, width: usize) -> FmtNum {
+    FmtNum::new(n, 10, width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fmt_hex() {
+        assert_eq!(fmt_hex(0, 0).as_str(), "0");
+        assert_eq!(fmt_hex(0, 2).as_str(), "00");
+        assert_eq!(fmt_hex(0xA, 0).as_str(), "A");
+        assert_eq!(fmt_hex(0x6, 8).as_str(), "00000006");
+        assert_eq!(fmt_hex(0x1234_5678, 2).as_str(), "12345678");
+        assert_eq!(fmt_hex(u32::MAX, 8).as_str(), "FFFFFFFF");
+        assert_eq!(format!("[{:<4}]", fmt_hex(0xA, 0)), "[A   ]");
+        assert_eq!(format!("[{:>4}]", fmt_hex(0x1F, 0)), "[  1F]");
+    }
+
+    #[test]
+    fn test_fmt_dec() {
+        assert_eq!(fmt_dec(0, 0).as_str(), "0");
+        assert_eq!(fmt_dec(7, 3).as_str(), "007");
+        assert_eq!(fmt_dec(1234, 2).as_str(), "1234");
+        assert_eq!(fmt_dec(u32::MAX, 0).as_str(), "4294967295");
+        assert_eq!(fmt_dec(u32::MAX, 12).as_str(), "004294967295");
+    }
+
+    #[test]
+    fn test_wrap_short_value() {
+        assert_eq!(CpuDisplay::wrap("FPU TSC CX8", 64), ["FPU TSC CX8"]);
+    }
+
+    #[test]
+    fn test_wrap_long_value() {
+        let lines = CpuDisplay::wrap("FPU TSC CX8 CMOV MMX SSE SSE2", 12);
+        assert_eq!(lines, ["FPU TSC CX8", "CMOV MMX SSE", "SSE2"]);
+        assert!(lines.iter().all(|l| l.len() <= 12));
+    }
+
+    #[test]
+    fn test_wrap_oversized_word() {
+        assert_eq!(
+            CpuDisplay::wrap("SSE 3DNow!-Prefetch MMX", 8),
+            ["SSE", "3DNow!-Prefetch", "MMX"]
+        );
+    }
+
+    #[test]
+    fn test_output_to_string() {
+        let mut out = String::new();
+        {
+            let disp = CpuDisplay::new(CliFlags::default(), &mut out);
+            disp.simple_line("Vendor", "GenuineIntel");
+            outln!(disp, "{}{}", disp.label("Model"), 486);
+        }
+
+        assert_eq!(
+            out,
+            format!("{:>14}: GenuineIntel\n\n{:>14}: 486\n", "Vendor", "Model")
+        );
+    }
+
+    #[test]
+    fn test_label_width() {
+        let flags = CliFlags {
+            label_width: 20,
+            ..Default::default()
+        };
+        let mut out = String::new();
+        let disp = CpuDisplay::new(flags, &mut out);
+
+        assert_eq!(disp.label("Hypervisor"), format!("{:>20}: ", "Hypervisor"));
+        assert_eq!(disp.sublabel("L1d"), format!("{:22}L1d: ", ""));
+        assert_eq!(
+            disp.indent().len(),
+            CpuDisplay::visible_len(&disp.label(""))
+        );
+    }
+
+    #[test]
+    fn test_visible_len_ignores_ansi() {
+        let flags = CliFlags {
+            color: true,
+            ..Default::default()
+        };
+        let mut out = String::new();
+        let disp = CpuDisplay::new(flags, &mut out);
+
+        assert_eq!(CpuDisplay::visible_len(&disp.label("Features")), 16);
+        assert_eq!(
+            CpuDisplay::visible_len(&disp.label("Features")),
+            CpuDisplay::raw_label("Features").len()
+        );
+    }
+}
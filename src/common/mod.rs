@@ -22,22 +22,56 @@ pub use os::*;
 
 use alloc::string::String;
 
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
 pub struct CliFlags {
     pub color: bool,
     pub verbose: bool,
+    /// Column to wrap long lines (like the feature list) at. 0 disables wrapping.
+    pub width: usize,
+    /// Width labels are right-aligned to. Longer labels push their value over.
+    pub label_width: usize,
+    /// Show the raw signature fields under the signature, outside verbose mode
+    pub raw_signature: bool,
+}
+
+impl Default for CliFlags {
+    fn default() -> Self {
+        Self {
+            color: false,
+            verbose: false,
+            width: DEFAULT_COLS,
+            label_width: DEFAULT_LABEL_WIDTH,
+            raw_signature: true,
+        }
+    }
 }
 
 pub trait TDetect {
     fn detect() -> Self;
 }
 
+/// Formats the CPU information table to any [`core::fmt::Write`] sink,
+/// such as a `String` or a serial port.
+pub trait Report {
+    /// Write the CPU information table to `out`
+    fn emit(&self, flags: CliFlags, out: &mut dyn core::fmt::Write);
+}
+
 pub trait TCpuDisplay: TDetect {
     /// Display the Rust debug output of the CPU object
     fn debug(&self);
 
     /// Display the CPU information in a table format
     fn display_table(&self, flags: CliFlags);
+
+    /// Display the CPU information in a table format, wrapping long lines at `cols` columns
+    fn display_table_width(&self, flags: CliFlags, cols: usize) {
+        self.display_table(CliFlags {
+            width: cols,
+            ..flags
+        });
+    }
 }
 
 #[derive(Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone)]
@@ -12,6 +12,7 @@ pub enum CpuBrand {
     AMD,
     Cyrix,
     DMP,
+    Elbrus,
     Hygon,
     IDT,
     Intel,
@@ -25,6 +26,8 @@ pub enum CpuBrand {
     Unknown,
     Via,
     Zhaoxin,
+    /// A vendor not in this list, with its vendor string, padded with NULs
+    Other([u8; 12]),
 }
 
 impl CpuBrand {
@@ -46,6 +49,8 @@ impl CpuBrand {
 
             CpuBrand::DMP => VENDOR_DMP,
 
+            CpuBrand::Elbrus => VENDOR_ELBRUS,
+
             CpuBrand::Hygon => VENDOR_HYGON,
 
             CpuBrand::IDT => VENDOR_CENTAUR,
@@ -69,6 +74,10 @@ impl CpuBrand {
             CpuBrand::Umc => VENDOR_UMC,
 
             CpuBrand::Unknown => UNK,
+
+            CpuBrand::Other(bytes) => {
+                core::str::from_utf8(bytes).map_or(UNK, |vendor| vendor.trim_end_matches('\0'))
+            }
         }
     }
 
@@ -82,6 +91,8 @@ impl CpuBrand {
 
             CpuBrand::DMP => "DM&P",
 
+            CpuBrand::Elbrus => "MCST",
+
             CpuBrand::Hygon => "Hygon",
 
             CpuBrand::IDT => "IDT",
@@ -106,7 +117,7 @@ impl CpuBrand {
 
             CpuBrand::Zhaoxin => "Zhaoxin",
 
-            CpuBrand::Unknown => UNK,
+            CpuBrand::Unknown | CpuBrand::Other(_) => UNK,
         }
     }
 }
@@ -120,6 +131,8 @@ impl From<&str> for CpuBrand {
 
             VENDOR_DMP => CpuBrand::DMP,
 
+            VENDOR_ELBRUS => CpuBrand::Elbrus,
+
             VENDOR_HYGON => CpuBrand::Hygon,
 
             VENDOR_INTEL => CpuBrand::Intel,
@@ -138,8 +151,23 @@ impl From<&str> for CpuBrand {
 
             VENDOR_UMC => CpuBrand::Umc,
 
+            VENDOR_VIA => CpuBrand::Via,
+
             VENDOR_ZHAOXIN => CpuBrand::Zhaoxin,
 
+            // Which brand depends on the signature, see `vendor::centaur`
+            VENDOR_CENTAUR => CpuBrand::Unknown,
+
+            "" | UNK => CpuBrand::Unknown,
+
+            // Keep the vendor string of anything else, if it could be one
+            _ if brand.len() <= 12 => {
+                let mut bytes = [0u8; 12];
+                bytes[..brand.len()].copy_from_slice(brand.as_bytes());
+
+                CpuBrand::Other(bytes)
+            }
+
             _ => CpuBrand::Unknown,
         }
     }
@@ -243,6 +271,15 @@ mod tests {
         assert_eq!(CpuBrand::Zhaoxin.to_vendor_str(), VENDOR_CENTAUR);
 
         assert_eq!(CpuBrand::Unknown.to_vendor_str(), UNK);
+
+        assert_eq!(CpuBrand::Elbrus.to_vendor_str(), VENDOR_ELBRUS);
+
+        assert_eq!(
+            CpuBrand::from("MiSTer AO486").to_vendor_str(),
+            "MiSTer AO486"
+        );
+
+        assert_eq!(CpuBrand::from("UNKNOWN_VEN").to_vendor_str(), "UNKNOWN_VEN");
     }
 
     #[test]
@@ -277,6 +314,10 @@ mod tests {
         assert_eq!(CpuBrand::Zhaoxin.to_brand_name(), "Zhaoxin");
 
         assert_eq!(CpuBrand::Unknown.to_brand_name(), UNK);
+
+        assert_eq!(CpuBrand::Elbrus.to_brand_name(), "MCST");
+
+        assert_eq!(CpuBrand::from("MiSTer AO486").to_brand_name(), UNK);
     }
 
     #[test]
@@ -303,6 +344,14 @@ mod tests {
 
         assert_eq!(CpuBrand::from(VENDOR_UMC), CpuBrand::Umc);
 
+        assert_eq!(CpuBrand::from(VENDOR_VIA), CpuBrand::Via);
+
+        assert_eq!(CpuBrand::from(VENDOR_ELBRUS), CpuBrand::Elbrus);
+
+        assert_eq!(CpuBrand::from(VENDOR_CENTAUR), CpuBrand::Unknown);
+
+        assert_eq!(CpuBrand::from(UNK), CpuBrand::Unknown);
+
         assert_eq!(CpuBrand::from("SomeOtherVendor"), CpuBrand::Unknown);
     }
 
@@ -311,8 +360,12 @@ mod tests {
         let amd_string = String::from(VENDOR_AMD);
         assert_eq!(CpuBrand::from(amd_string), CpuBrand::AMD);
 
+        // Unknown vendors keep their vendor string
         let unknown_string = String::from("UNKNOWN_VEN");
-        assert_eq!(CpuBrand::from(unknown_string), CpuBrand::Unknown);
+        assert_eq!(
+            CpuBrand::from(unknown_string),
+            CpuBrand::Other(*b"UNKNOWN_VEN\0")
+        );
     }
 
     #[test]
@@ -7,6 +7,8 @@ pub const VENDOR_AMD: &str = "AuthenticAMD";
 pub const VENDOR_CENTAUR: &str = "CentaurHauls";
 pub const VENDOR_CYRIX: &str = "CyrixInstead";
 pub const VENDOR_DMP: &str = "Vortex86 SoC";
+/// MCST Elbrus, in x86 binary translation mode. The last byte is a NUL.
+pub const VENDOR_ELBRUS: &str = "E2K MACHINE";
 pub const VENDOR_HYGON: &str = "HygonGenuine";
 pub const VENDOR_INTEL: &str = "GenuineIntel";
 pub const VENDOR_NEXGEN: &str = "NexGenDriven";
@@ -16,6 +18,8 @@ pub const VENDOR_RISE: &str = "RiseRiseRise";
 pub const VENDOR_SIS: &str = "SiS SiS SiS ";
 pub const VENDOR_TRANSMETA: &str = "GenuineTMx86";
 pub const VENDOR_UMC: &str = "UMC UMC UMC ";
+/// Used by some later VIA CPUs, instead of `CentaurHauls`
+pub const VENDOR_VIA: &str = "VIA VIA VIA ";
 pub const VENDOR_ZHAOXIN: &str = "  Shanghai  ";
 
 // ----------------------------------------------------------------------------
@@ -56,6 +60,9 @@ pub const LEAF_2: u32 = 0x2;
 /// Intel deterministic cache parameters
 pub const LEAF_4: u32 = 0x4;
 
+/// CPUID leaf 0x00000006 - Thermal and power management
+pub const LEAF_6: u32 = 0x6;
+
 /// CPUID leaf 0x00000007 - Extended feature flags
 pub const LEAF_7: u32 = 0x7;
 
@@ -65,6 +72,12 @@ pub const LEAF_0B: u32 = 0xB;
 /// CPUID leaf 0x0000000D - Processor Extended State
 pub const LEAF_0D: u32 = 0xD;
 
+/// CPUID leaf 0x0000000F - Resource Director Technology monitoring
+pub const LEAF_0F: u32 = 0xF;
+
+/// CPUID leaf 0x00000010 - Resource Director Technology allocation
+pub const LEAF_10: u32 = 0x10;
+
 /// CPUID leaf 0x00000014 - Intel Processor Trace
 pub const LEAF_14: u32 = 0x14;
 
@@ -86,6 +99,12 @@ pub const LEAF_1A: u32 = 0x1A;
 /// Intel extended topology v2
 pub const LEAF_1F: u32 = 0x1F;
 
+/// CPUID leaf 0x00000020 - Processor History Reset
+pub const LEAF_20: u32 = 0x20;
+
+/// CPUID leaf 0x00000024 - AVX10 Converged Vector ISA
+pub const LEAF_24: u32 = 0x24;
+
 // --------------------------------------------
 // ! Hypervisor CPUID Leaves
 // --------------------------------------------
@@ -122,6 +141,12 @@ pub const EXT_LEAF_1D: u32 = 0x8000_001D;
 /// AMD CPU topology
 pub const EXT_LEAF_1E: u32 = 0x8000_001E;
 
+/// AMD memory encryption (SME/SEV)
+pub const EXT_LEAF_1F: u32 = 0x8000_001F;
+
+/// AMD extended feature identification 2
+pub const EXT_LEAF_21: u32 = 0x8000_0021;
+
 /// AMD extended CPU topology
 pub const EXT_LEAF_26: u32 = 0x8000_0026;
 
@@ -157,3 +182,44 @@ pub const TRANSMETA_LEAF_6: u32 = 0x8086_0006;
 
 /// Transmeta live CPU information
 pub const TRANSMETA_LEAF_7: u32 = 0x8086_0007;
+
+// --------------------------------------------
+// ! Model Specific Registers
+// --------------------------------------------
+
+/// Intel platform information (non-turbo and minimum ratios)
+pub const MSR_PLATFORM_INFO: u32 = 0xCE;
+
+/// Limits for the user-mode wait instructions, UMWAIT and TPAUSE
+pub const MSR_UMWAIT_CONTROL: u32 = 0xE1;
+
+/// Counts at the base (TSC) clock while the core is running
+pub const MSR_MPERF: u32 = 0xE7;
+
+/// Counts at the actual clock while the core is running
+pub const MSR_APERF: u32 = 0xE8;
+
+/// Speculative execution issues the CPU isn't affected by
+pub const MSR_ARCH_CAPABILITIES: u32 = 0x10A;
+
+/// Intel miscellaneous feature enables
+pub const MSR_MISC_ENABLE: u32 = 0x1A0;
+
+/// Intel Total Memory Encryption capabilities
+pub const MSR_TME_CAPABILITY: u32 = 0x981;
+
+/// Intel Total Memory Encryption settings, set by the BIOS
+pub const MSR_TME_ACTIVATE: u32 = 0x982;
+
+// --------------------------------------------
+// ! XCR0 state components
+// --------------------------------------------
+
+/// SSE and AVX (YMM) state, needed for AVX
+pub const XCR0_AVX: u64 = 0b110;
+
+/// Opmask and ZMM state, needed for AVX-512, along with [`XCR0_AVX`]
+pub const XCR0_AVX512: u64 = XCR0_AVX | 0b1110_0000;
+
+/// AMX tile configuration and data
+pub const XCR0_AMX: u64 = 0b11 << 17;
@@ -1,11 +1,11 @@
 //! Let's count sockets/cores/threads
 use crate::common::{DataSource, TopologyTier};
-use crate::cpuid::{amd_threads_per_core, cpuid_data_source, has_ht};
+use crate::cpuid::{core_counts, cpuid_data_source};
 
 #[cfg(not(dos))]
 use crate::common::{OS, TOSData};
 
-use super::{amd_logical_cores, is_amd};
+use super::{is_amd, is_hygon};
 
 #[cfg(not(dos))]
 use super::{info_source, provider::CpuidInfoSource};
@@ -28,35 +28,15 @@ pub fn get_platform_socket_count() -> TopologyTier {
 }
 
 pub fn get_thread_count() -> TopologyTier {
-    if is_amd() {
-        TopologyTier::new(amd_logical_cores(), cpuid_data_source())
-    } else {
-        get_platform_thread_count()
-    }
-}
-
-fn get_platform_thread_count() -> TopologyTier {
-    TopologyTier::default()
+    TopologyTier::new(core_counts().logical, cpuid_data_source())
 }
 
 pub fn get_core_count() -> TopologyTier {
-    if is_amd() {
-        TopologyTier::new(
-            amd_logical_cores() / amd_threads_per_core(),
-            DataSource::Calculated("AMD Cpuid"),
-        )
+    let source = if is_amd() || is_hygon() {
+        DataSource::Calculated("AMD Cpuid")
     } else {
-        get_platform_core_count()
-    }
-}
-
-fn get_platform_core_count() -> TopologyTier {
-    let thread_tier = get_platform_thread_count();
-    let thread_count = thread_tier.count;
+        DataSource::Calculated("Cpuid")
+    };
 
-    if !is_amd() && has_ht() && thread_count > 1 {
-        TopologyTier::new(thread_count / 2, DataSource::Calculated("AMD Cpuid"))
-    } else {
-        thread_tier
-    }
+    TopologyTier::new(core_counts().physical, source)
 }
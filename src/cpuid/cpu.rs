@@ -3,26 +3,29 @@
 use super::brand::CpuBrand;
 use super::micro_arch::{CpuArch, MicroArch};
 use super::topology::Topology;
-use super::vendor::Cyrix;
+use super::vendor::{AMDCpuFamily, Amd, Cyrix};
 use super::*;
 use super::{EXT_LEAF_2, EXT_LEAF_4, LEAF_1, read_multi_leaf_str, x86_cpuid};
 
 #[cfg(not(dos))]
 use super::provider;
 
-use crate::common::{Cache, CoreType, DataSource, TDetect, UNK};
+use crate::common::{Cache, CoreType, DataSource, TDetect, UNK, fmt_hex};
+use alloc::boxed::Box;
 use alloc::collections::BTreeMap;
 use alloc::string::String;
 use alloc::vec::Vec;
+use core::sync::atomic::{AtomicPtr, Ordering};
 
 /// CPU feature class/level enumeration.
 ///
 /// Represents the instruction set and feature level of an x86 processor,
 /// roughly based on x86-64 microarchitecture levels.
 #[allow(non_camel_case_types)]
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
 pub enum FeatureClass {
     /// 80386-class processor
+    #[default]
     i386,
     /// 80486-class processor
     i486,
@@ -38,7 +41,7 @@ pub enum FeatureClass {
     i686_SSE3,
     /// x86-64 version 1 (baseline SSE/SSE2)
     x86_64_v1,
-    /// x86-64 version 2 (adds CMPXCHG16B, POPCNT, SSE4.2)
+    /// x86-64 version 2 (adds CMPXCHG16B, LAHF/SAHF, POPCNT, SSE4.2)
     x86_64_v2,
     /// x86-64 version 3 (adds AVX, AVX2, BMI, F16C, FMA)
     x86_64_v3,
@@ -61,7 +64,14 @@ impl FeatureClass {
             return FeatureClass::x86_64_v3;
         }
 
-        if has_cx16() && has_popcnt() && has_sse3() && has_sse41() && has_sse42() && has_ssse3() {
+        if has_cx16()
+            && has_lahf_lm()
+            && has_popcnt()
+            && has_sse3()
+            && has_sse41()
+            && has_sse42()
+            && has_ssse3()
+        {
             return FeatureClass::x86_64_v2;
         }
 
@@ -208,6 +218,11 @@ impl CpuSignature {
             }
         }
 
+        // Some emulators report a vendor string, but no leaf 1
+        if max_leaf() < LEAF_1 {
+            return Self::default();
+        }
+
         let res = x86_cpuid(LEAF_1);
         let stepping = res.eax & 0xF;
         let model = (res.eax >> 4) & 0xF;
@@ -227,7 +242,7 @@ impl CpuSignature {
 }
 
 /// Information about a specific core type/cluster in the CPU.
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct CpuCore {
     /// Classification of this core (Performance, Efficiency, Super)
     pub kind: CoreType,
@@ -243,8 +258,65 @@ pub struct CpuCore {
     pub threads: u32,
 }
 
+/// Cache flush capabilities relevant to persistent memory programming.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PmemCapabilities {
+    /// CLFLUSH instruction (flush and invalidate, strongly ordered)
+    pub clflush: bool,
+    /// CLFLUSHOPT instruction (flush and invalidate, weakly ordered)
+    pub clflushopt: bool,
+    /// CLWB instruction (write back, line may stay in the cache)
+    pub clwb: bool,
+    /// The FPU CS and DS values are no longer saved
+    pub fpu_csds_deprecated: bool,
+    /// Size of the cache line flushed by CLFLUSH, in bytes
+    pub flush_line_size: u32,
+}
+
+impl PmemCapabilities {
+    /// Detects the cache flush capabilities used for persistent memory.
+    #[must_use]
+    pub fn detect() -> Self {
+        let clflush = has_clflush();
+
+        // Leaf 1 EBX[15:8] is the CLFLUSH line size, in 8-byte units
+        let flush_line_size = if clflush {
+            ((x86_cpuid(LEAF_1).ebx >> 8) & 0xFF) * 8
+        } else {
+            0
+        };
+
+        Self {
+            clflush,
+            clflushopt: has_clflushopt(),
+            clwb: has_clwb(),
+            fpu_csds_deprecated: has_fpu_csds_deprecated(),
+            flush_line_size,
+        }
+    }
+}
+
+/// CPU signature and core type read from one logical processor.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct CoreSignature {
+    /// Index of the logical processor (or CPU in a dump file)
+    pub cpu: usize,
+    /// CPU signature (family, model, stepping) seen on this processor
+    pub signature: CpuSignature,
+    /// Core type of this processor (Intel hybrid only)
+    pub core_type: Option<CoreType>,
+}
+
 /// Represents a complete x86/x86_64 CPU with all detected information.
-#[derive(Debug, Default, PartialEq)]
+///
+/// CPUID reports on the logical processor that executes it, so on hybrid
+/// CPUs most of this describes whichever core ran detection. Only `cores`
+/// is collected from every logical processor. See
+/// [`Cpu::current_core_type`], [`Cpu::detect_on_all_cores`] and
+/// [`Cpu::detect_all`].
+///
+/// The `Debug` output is a compact, stable summary; see the impl below.
+#[derive(Default, PartialEq)]
 pub struct Cpu {
     pub has_cpuid: bool,
     /// CPU architecture and microarchitecture details
@@ -263,16 +335,370 @@ pub struct Cpu {
     pub topology: Topology,
     /// Per-core-type breakdown of CPU cores
     pub cores: Vec<CpuCore>,
+    /// Core type of the logical processor that ran detection (Intel hybrid only)
+    pub core_type: Option<CoreType>,
+    /// Package type (socket), such as AM4 (AMD only)
+    pub package: Option<&'static str>,
+    /// Product line, such as Ryzen or EPYC (AMD only)
+    pub product_line: Option<AMDCpuFamily>,
+    /// Feature level, such as x86_64-v3
+    pub feature_class: FeatureClass,
+    /// Leaf 7 sub-leaf 1 is reported, but empty, see [`leaf_7_1_unreliable`]
+    pub leaf_7_1_unreliable: bool,
+    /// Mode the CPU was running detection in, or `None` for a dump
+    pub mode: Option<CpuMode>,
+    /// Highest basic CPUID leaf
+    pub max_leaf: u32,
+    /// Is the BIOS limiting the max leaf? See [`cpuid_maxval_limited`]
+    pub maxval_limited: Option<bool>,
+    /// Features the hardware has, but the OS hasn't enabled
+    pub os_disabled: FeatureSet,
+    /// AMD memory encryption support
+    pub sev: SevInfo,
+    /// Intel Total Memory Encryption support
+    pub has_tme: bool,
+    /// Intel Total Memory Encryption state, only read on the DOS build
+    pub tme: Option<TmeInfo>,
+    /// Cache flush capabilities used for persistent memory
+    pub pmem: PmemCapabilities,
+    /// Physical and linear address sizes
+    pub address_bits: AddressBits,
+}
+
+/// Compact `Debug` output, stable enough for bug reports and snapshot tests.
+///
+/// Fields are always printed in the same order:
+///
+/// - `vendor`, `model`, `micro_arch`, `code_name`, `technology`
+/// - `signature` as `family/model/stepping` in hex (display values), and
+///   its data source
+/// - `brand_id`, `has_cpuid`, `hypervisor`, `easter_egg`, `core_type`
+/// - `topology` as `sockets/cores/threads`, their data sources, the speed
+///   in MHz, and the cache
+/// - `cores` as `Kind xCount/Threads` entries, followed by the core type's
+///   cache, if it has its own
+/// - `features` as `group: list` pairs, sorted by group
+impl core::fmt::Debug for Cpu {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let sig = &self.signature;
+        let topo = &self.topology;
+
+        f.debug_struct("Cpu")
+            .field("vendor", &self.arch.vendor_string)
+            .field("model", &self.arch.model)
+            .field("micro_arch", &self.arch.micro_arch)
+            .field("code_name", &self.arch.code_name)
+            .field("technology", &self.arch.technology)
+            .field(
+                "signature",
+                &format_args!(
+                    "{}h/{}h/{}h",
+                    fmt_hex(sig.display_family, 0),
+                    fmt_hex(sig.display_model, 0),
+                    fmt_hex(sig.stepping, 0)
+                ),
+            )
+            .field("signature_source", &sig.source)
+            .field("brand_id", &self.brand_id)
+            .field("has_cpuid", &self.has_cpuid)
+            .field("hypervisor", &self.hyp_vendor_str)
+            .field("easter_egg", &self.easter_egg)
+            .field("core_type", &self.core_type)
+            .field(
+                "topology",
+                &format_args!(
+                    "{}/{}/{}",
+                    topo.sockets.count, topo.cores.count, topo.threads.count
+                ),
+            )
+            .field(
+                "topology_source",
+                &format_args!(
+                    "{:?}/{:?}/{:?}",
+                    topo.sockets.source, topo.cores.source, topo.threads.source
+                ),
+            )
+            .field(
+                "speed",
+                &format_args!("{}/{} MHz", topo.speed.base, topo.speed.boost),
+            )
+            .field("cache", &topo.cache)
+            .field(
+                "cores",
+                &self.cores.iter().map(CoreSummary).collect::<Vec<_>>(),
+            )
+            .field("features", &self.features)
+            .finish()
+    }
+}
+
+/// One `cores` entry of the compact `Cpu` debug output.
+struct CoreSummary<'a>(&'a CpuCore);
+
+impl core::fmt::Debug for CoreSummary<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?} x{}/{}", self.0.kind, self.0.count, self.0.threads)?;
+
+        match &self.0.cache {
+            Some(cache) => write!(f, " {cache:?}"),
+            None => Ok(()),
+        }
+    }
 }
 
 impl Cpu {
+    /// Runs detection against the text of a CPUID dump, as written by the
+    /// dump command, instead of the current CPU.
+    ///
+    /// The first CPU in the dump is used. The current thread's CPUID provider
+    /// is put back afterwards.
+    #[cfg(not(dos))]
+    pub fn from_dump(text: &str) -> Result<Self, provider::ParseError> {
+        let dump = provider::CpuDump::parse(text)?;
+        provider::set_dump_cpu(0);
+
+        Ok(provider::with_cpuid_provider(dump, Self::detect))
+    }
+
+    /// Returns the core type of the logical processor this is called on.
+    ///
+    /// The OS can move the calling thread between cores, so on hybrid CPUs
+    /// this can differ from call to call, and from [`Cpu::core_type`].
+    #[must_use]
+    pub fn current_core_type() -> Option<CoreType> {
+        core_type()
+    }
+
+    /// Returns the number of SMT threads per core.
+    ///
+    /// Uses the SMT level of leaf 0xB, then the extended leaf 0x8000001E of
+    /// AMD and Hygon.
+    /// On older CPUs, HTT only means the package has more than one logical
+    /// processor, so the threads are divided by the cores from
+    /// [`core_counts`]. That gives 2 for a Pentium 4 with Hyper-Threading,
+    /// and 1 for a dual-core without it.
+    #[must_use]
+    pub fn threads_per_core() -> u32 {
+        if is_valid_leaf(LEAF_0B) {
+            let res = x86_cpuid_count(LEAF_0B, 0);
+            let level_type = (res.ecx >> 8) & 0xFF;
+            let threads = res.ebx & 0xFFFF;
+
+            if level_type == 1 && threads > 0 {
+                return threads;
+            }
+        }
+
+        if (is_amd() || is_hygon()) && is_valid_leaf(EXT_LEAF_1E) {
+            return amd_threads_per_core();
+        }
+
+        if !has_ht() {
+            return 1;
+        }
+
+        let counts = core_counts();
+        (counts.logical / counts.physical.max(1)).max(1)
+    }
+
+    /// Checks whether the OS set `IA32_TSC_AUX` to the APIC ID of the
+    /// processor, by running RDTSCP and comparing the low 12 bits of the
+    /// value to the APIC ID from CPUID.
+    ///
+    /// Linux and Windows store their own processor number there instead,
+    /// so a mismatch isn't an error on those; this is mostly for checking
+    /// bare-metal setup code. Returns `None` without RDTSCP, when reading a
+    /// dump file, with [probes](probes_enabled) off, or if the thread keeps
+    /// moving between processors.
+    #[must_use]
+    pub fn tsc_aux_matches_apic() -> Option<bool> {
+        #[cfg(not(dos))]
+        if info_source() != provider::CpuidInfoSource::Cpu {
+            return None;
+        }
+
+        if !has_rdtscp() || !probes_enabled() {
+            return None;
+        }
+
+        // The x2APIC ID, or the 8-bit initial APIC ID on older CPUs
+        let apic_id = || {
+            if is_valid_leaf(LEAF_0B) {
+                x86_cpuid_count(LEAF_0B, 0).edx
+            } else {
+                x86_cpuid(LEAF_1).ebx >> 24
+            }
+        };
+
+        // The thread can be moved between reading the APIC ID and
+        // TSC_AUX, so only trust a result with the same APIC ID both sides
+        for _ in 0..3 {
+            let before = apic_id();
+            let aux: u32;
+            unsafe {
+                core::arch::asm!(
+                    "rdtscp",
+                    out("eax") _,
+                    out("edx") _,
+                    out("ecx") aux,
+                    options(nomem, nostack, preserves_flags),
+                );
+            }
+
+            if apic_id() == before {
+                return Some(aux & 0xFFF == before & 0xFFF);
+            }
+        }
+
+        None
+    }
+
+    /// Returns true if the CPU has any AVX-512 support, which always
+    /// includes AVX512F (Foundation).
+    #[must_use]
+    pub fn has_any_avx512(&self) -> bool {
+        self.has_listed_feature("AVX512", "F")
+    }
+
+    /// Is `name` in the `group` list of [`Cpu::features`]?
+    ///
+    /// Unlike the `has_*` functions, this doesn't read CPUID, so it's also
+    /// right for a `Cpu` loaded from a dump.
+    #[must_use]
+    pub fn has_listed_feature(&self, group: &str, name: &str) -> bool {
+        self.features
+            .get(group)
+            .is_some_and(|names| names.split(' ').any(|listed| listed == name))
+    }
+
+    /// The flags of [`FEATURE_SPECS`] in [`Cpu::features`].
+    ///
+    /// Like [`Cpu::has_listed_feature`], this is also right for a `Cpu`
+    /// loaded from a dump.
+    #[must_use]
+    pub fn feature_set(&self) -> FeatureSet {
+        let names: Vec<&str> = self
+            .features
+            .values()
+            .flat_map(|names| names.split(' '))
+            .collect();
+
+        FeatureSet::from_names(&names)
+    }
+
+    /// Returns the AVX-512 subfeatures in [`Cpu::features`], the "AVX512"
+    /// group of [`FEATURE_SPECS`].
+    ///
+    /// Empty without AVX512F, since the others build on it.
+    #[must_use]
+    pub fn avx512_subfeatures(&self) -> FeatureSet {
+        if !self.has_any_avx512() {
+            return FeatureSet::default();
+        }
+
+        let names: Vec<&str> = self
+            .features
+            .get("AVX512")
+            .map(|names| names.split(' ').collect())
+            .unwrap_or_default();
+
+        FeatureSet::from_names(&names).intersection(FeatureSet::from_group("AVX512"))
+    }
+
     /// Gets the CPU model string.
     pub fn raw_model_string() -> String {
         read_multi_leaf_str(EXT_LEAF_2, EXT_LEAF_4)
     }
 
+    /// Can the reported physical address width be taken at face value?
+    ///
+    /// Hypervisors may clamp MAXPHYADDR below what the host CPU supports,
+    /// so this is false when running as a guest.
+    #[must_use]
+    pub fn phys_addr_trustworthy(&self) -> bool {
+        !self.address_bits.virtualized
+    }
+
+    /// Detected CPU features, by their LLVM names, formatted for
+    /// `-C target-feature`, e.g. `+sse4.2,+avx2,+fma`
+    #[must_use]
+    pub fn suggested_target_features(&self) -> String {
+        // The target feature, then the group and name in the feature list
+        const TARGET_FEATURES: &[(&str, &str, &str)] = &[
+            ("sse", "SSE", "SSE"),
+            ("sse2", "SSE", "SSE2"),
+            ("sse3", "SSE", "SSE3"),
+            ("ssse3", "SSE", "SSSE3"),
+            ("sse4.1", "SSE", "SSE4.1"),
+            ("sse4.2", "SSE", "SSE4.2"),
+            ("sse4a", "SSE", "SSE4A"),
+            ("popcnt", "Other", "POPCNT"),
+            ("cmpxchg16b", "Base", "CX16"),
+            ("sahf", "Base", "LAHF-SAHF"),
+            ("prfchw", "Base", "3DNow!-Prefetch"),
+            ("aes", "Security", "AES"),
+            ("vaes", "Security", "VAES"),
+            ("vpclmulqdq", "AVX", "VPCLMULQDQ"),
+            ("sha", "Security", "SHA"),
+            ("rdrand", "Security", "RDRAND"),
+            ("rdseed", "Security", "RDSEED"),
+            ("fsgsbase", "Other", "FSGSBASE"),
+            ("ermsb", "Other", "ERMS"),
+            ("avx", "AVX", "AVX"),
+            ("f16c", "Math", "F16C"),
+            ("fma", "Math", "FMA"),
+            ("bmi1", "Math", "BMI1"),
+            ("bmi2", "Math", "BMI2"),
+            ("lzcnt", "Math", "LZCNT"),
+            ("avx2", "AVX", "AVX2"),
+            ("avxifma", "AVX", "AVX-IFMA"),
+            ("avx512f", "AVX512", "F"),
+            ("avx512dq", "AVX512", "DQ"),
+            ("avx512ifma", "AVX512", "IFMA"),
+            ("avx512cd", "AVX512", "CD"),
+            ("avx512bw", "AVX512", "BW"),
+            ("avx512vl", "AVX512", "VL"),
+            ("avx512bitalg", "AVX512", "BITALG"),
+            ("avx512vpopcntdq", "AVX512", "VPOPCNTDQ"),
+            ("avx512vp2intersect", "AVX512", "VP2INTERSECT"),
+            ("avx512fp16", "AVX512", "FP16"),
+        ];
+
+        TARGET_FEATURES
+            .iter()
+            .filter(|(_, group, name)| self.has_listed_feature(group, name))
+            .map(|(target_feature, _, _)| alloc::format!("+{target_feature}"))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// The `-C target-cpu` name for the detected microarchitecture, if known
+    #[must_use]
+    pub fn suggested_target_cpu(&self) -> Option<&'static str> {
+        self.arch.micro_arch.llvm_target_cpu()
+    }
+
+    /// Suggested `RUSTFLAGS` for building for this CPU
+    ///
+    /// The `-C target-cpu` part is left out if the microarchitecture is unknown.
+    #[must_use]
+    pub fn suggested_rustflags(&self) -> String {
+        let mut flags = Vec::new();
+
+        if let Some(cpu) = self.suggested_target_cpu() {
+            flags.push(alloc::format!("-C target-cpu={cpu}"));
+        }
+
+        let features = self.suggested_target_features();
+        if !features.is_empty() {
+            flags.push(alloc::format!("-C target-feature={features}"));
+        }
+
+        flags.join(" ")
+    }
+
     fn intel_brand_index(&self) -> Option<&'static str> {
-        let brand_id = get_brand_id();
+        let brand_id = self.brand_id;
 
         const CELERON: &str = "Intel(R) Celeron(R) processor";
         const XEON: &str = "Intel(R) Xeon(R) processor";
@@ -347,6 +773,99 @@ impl Cpu {
         result
     }
 
+    /// Returns the public name of the CPU's stepping (e.g. "B2"), for some
+    /// popular CPUs.
+    ///
+    /// The names are from the vendors' spec updates and revision guides.
+    /// Returns `None` for CPUs that aren't in the table.
+    #[must_use]
+    pub fn stepping_name(&self) -> Option<&'static str> {
+        let sig = &self.signature;
+
+        let name = match (
+            self.arch.vendor_string.as_str(),
+            sig.display_family,
+            sig.display_model,
+            sig.stepping,
+        ) {
+            // Core 2 (Conroe/Merom)
+            (VENDOR_INTEL, 6, 0x0F, 6) => "B2",
+            (VENDOR_INTEL, 6, 0x0F, 0xB) => "G0",
+            (VENDOR_INTEL, 6, 0x0F, 0xD) => "M0",
+            // Core 2 (Wolfdale/Yorkfield)
+            (VENDOR_INTEL, 6, 0x17, 6) => "C0",
+            (VENDOR_INTEL, 6, 0x17, 0xA) => "E0",
+            // Nehalem (Bloomfield)
+            (VENDOR_INTEL, 6, 0x1A, 4) => "C0",
+            (VENDOR_INTEL, 6, 0x1A, 5) => "D0",
+            // Sandy Bridge, Ivy Bridge, Haswell
+            (VENDOR_INTEL, 6, 0x2A, 7) => "D2",
+            (VENDOR_INTEL, 6, 0x3A, 9) => "E1",
+            (VENDOR_INTEL, 6, 0x3C, 3) => "C0",
+            // Kaby Lake and Coffee Lake desktop
+            (VENDOR_INTEL, 6, 0x9E, 9) => "B0",
+            (VENDOR_INTEL, 6, 0x9E, 0xA) => "U0",
+            (VENDOR_INTEL, 6, 0x9E, 0xB) => "B0",
+            (VENDOR_INTEL, 6, 0x9E, 0xC) => "P0",
+            (VENDOR_INTEL, 6, 0x9E, 0xD) => "R0",
+            // Alder Lake and Raptor Lake desktop
+            (VENDOR_INTEL, 6, 0x97, 2) => "C0",
+            (VENDOR_INTEL, 6, 0x97, 5) => "H0",
+            (VENDOR_INTEL, 6, 0xB7, 1) => "B0",
+
+            // Phenom (B2 has the TLB erratum, fixed in B3)
+            (VENDOR_AMD, 0x10, 2, 2) => "B2",
+            (VENDOR_AMD, 0x10, 2, 3) => "B3",
+            // Phenom II (Deneb)
+            (VENDOR_AMD, 0x10, 4, 2) => "C2",
+            (VENDOR_AMD, 0x10, 4, 3) => "C3",
+            // Zen (Summit Ridge), Zen+ (Pinnacle Ridge), Zen 2 (Matisse)
+            (VENDOR_AMD, 0x17, 1, 1) => "B1",
+            (VENDOR_AMD, 0x17, 8, 2) => "B2",
+            (VENDOR_AMD, 0x17, 0x71, 0) => "B0",
+            // Zen 3 (Vermeer), Zen 4 (Raphael)
+            (VENDOR_AMD, 0x19, 0x21, 0) => "B0",
+            (VENDOR_AMD, 0x19, 0x21, 2) => "B2",
+            (VENDOR_AMD, 0x19, 0x61, 2) => "B2",
+
+            _ => return None,
+        };
+
+        Some(name)
+    }
+
+    /// Model string built from the brand and microarchitecture, for CPUs
+    /// without a brand string or a more specific name.
+    fn synth_model_string(&self) -> Option<String> {
+        if self.arch.micro_arch == MicroArch::Unknown || self.arch.brand_name == UNK {
+            return None;
+        }
+
+        let micro_arch = self.arch.micro_arch.as_str();
+        let mut name = alloc::format!("{} {}", self.arch.brand_name, micro_arch);
+        if self.arch.code_name != UNK && self.arch.code_name != micro_arch {
+            name.push(' ');
+            name.push_str(self.arch.code_name);
+        }
+
+        Some(name)
+    }
+
+    /// Model string for 386 and 486-class CPUs without a usable CPUID
+    fn classic_model_string(&self) -> &'static str {
+        if is_386() {
+            return "'Classic' 386";
+        }
+
+        match (self.signature.family, self.signature.model) {
+            (4, 2) => "'Classic' 486 SX",
+            (4, 3) => "'Classic' 486 DX2",
+            (4, 4) => "Intel 486SL",
+            (4, 5) => "'Classic' 486 SX2",
+            _ => "'Classic' 486",
+        }
+    }
+
     /// Returns a human-readable display name for the CPU model.
     ///
     /// This attempts to produce a marketing-style name based on the
@@ -374,30 +893,19 @@ impl Cpu {
                 }
             }
             CpuBrand::SiS => return String::from("SiS 550/551/552 SoC"),
-            CpuBrand::Unknown => 'nocpuid: {
-                // Not a 386 or 486
-                if self.arch.model != UNK || self.signature.family > 4 {
-                    break 'nocpuid;
-                }
-
-                // 486s without cpuid
-                let s = if is_386() {
-                    "'Classic' 386"
-                } else {
-                    match (self.signature.family, self.signature.model) {
-                        (4, 2) => "'Classic' 486 SX",
-                        (4, 3) => "'Classic' 486 DX2",
-                        (4, 4) => "Intel 486SL",
-                        (4, 5) => "'Classic' 486 SX2",
-                        _ => "'Classic' 486",
-                    }
-                };
-
-                return String::from(s);
+            // Only a 386 or 486
+            CpuBrand::Unknown if self.arch.model == UNK && self.signature.family <= 4 => {
+                return String::from(self.classic_model_string());
             }
             _ => (),
         }
 
+        // CPUID without leaf 1, so there's no signature to go on
+        if has_cpuid() && max_leaf() < LEAF_1 {
+            return String::from(self.classic_model_string());
+        }
+
+        let synth_model;
         let s = match self.arch.micro_arch {
             // AMD
             MicroArch::Am486 => match self.arch.code_name {
@@ -478,6 +986,9 @@ impl Cpu {
             _ => {
                 if self.arch.model != UNK {
                     &self.arch.model
+                } else if let Some(name) = self.synth_model_string() {
+                    synth_model = name;
+                    &synth_model
                 } else {
                     UNK
                 }
@@ -485,10 +996,18 @@ impl Cpu {
         };
 
         #[cfg(not(dos))]
-        return Self::cleanup_model_string(s);
+        let mut model = Self::cleanup_model_string(s);
 
         #[cfg(dos)]
-        String::from(s)
+        let mut model = String::from(s);
+
+        // Not every APU brand string mentions the integrated graphics, but
+        // some APU dies are sold with it disabled
+        if self.arch.is_apu() && !model.contains("Radeon") && Amd::model_has_igpu(&model) {
+            model.push_str(" with Radeon Graphics");
+        }
+
+        model
     }
 
     fn easter_egg() -> Option<String> {
@@ -531,25 +1050,239 @@ impl Cpu {
     }
 }
 
+impl Cpu {
+    /// Detects the CPU, limited to what the [`DetectionMode`] allows.
+    ///
+    /// With [`DetectionMode::CpuidOnly`], the result is the same as
+    /// [`TDetect::detect`], except for anything needing other
+    /// instructions, such as the measured clock speed.
+    #[must_use]
+    pub fn detect_with_mode(mode: DetectionMode) -> Self {
+        with_detection_mode(mode, Self::detect)
+    }
+
+    /// Checks the CPUID data for signs it can't be trusted, such as from a
+    /// virtual machine that changes or drops leaves.
+    ///
+    /// Returns a description of each problem found, or an empty list.
+    #[must_use]
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = self
+            .arch
+            .micro_arch
+            .feature_contradictions(self.feature_set());
+
+        if self.leaf_7_1_unreliable {
+            problems.push(String::from(
+                "Leaf 7 sub-leaf 1 is reported, but empty, so its features may be missing",
+            ));
+        }
+
+        if !self.brand_signature_consistent() {
+            problems.push(String::from(
+                "The brand string doesn't match the CPU signature",
+            ));
+        }
+
+        problems
+    }
+
+    /// Checks that the brand string claims a CPU the signature could be.
+    ///
+    /// A remarked or counterfeit CPU, or a virtual machine passing through a
+    /// different brand string, can name a product that doesn't match the
+    /// family and model. This compares:
+    ///
+    /// - the vendor named in the brand string with the vendor string
+    /// - the product line, such as "Core i7" or "Ryzen", with the detected
+    ///   microarchitecture
+    /// - the Celeron/Pentium/Xeon tier with the one from the brand index,
+    ///   on the older Intel CPUs that have both
+    ///
+    /// Anything that can't be compared, such as an unknown microarchitecture,
+    /// counts as consistent.
+    #[must_use]
+    pub fn brand_signature_consistent(&self) -> bool {
+        let model = self.arch.model.as_str();
+        if model.trim().is_empty() || model == UNK {
+            return true;
+        }
+
+        let vendor = self.arch.vendor_string.as_str();
+        if (model.contains("Intel") && vendor != VENDOR_INTEL)
+            || (model.starts_with("AMD") && vendor != VENDOR_AMD)
+        {
+            return false;
+        }
+
+        let ma = self.arch.micro_arch;
+        if ma != MicroArch::Unknown {
+            let line_matches = if model.contains("Pentium(R) 4") {
+                matches!(
+                    ma,
+                    MicroArch::Willamette
+                        | MicroArch::Northwood
+                        | MicroArch::Prescott
+                        | MicroArch::CedarMill
+                )
+            } else if model.contains("Core(TM)2") {
+                matches!(ma, MicroArch::Core | MicroArch::Merom | MicroArch::Penryn)
+            } else if model.contains("Core(TM) i") || model.contains("Core(TM) Ultra") {
+                !matches!(
+                    ma,
+                    MicroArch::P5
+                        | MicroArch::PentiumPro
+                        | MicroArch::PentiumII
+                        | MicroArch::PentiumIII
+                        | MicroArch::Willamette
+                        | MicroArch::Northwood
+                        | MicroArch::Prescott
+                        | MicroArch::CedarMill
+                        | MicroArch::Dothan
+                        | MicroArch::Yonah
+                        | MicroArch::Core
+                        | MicroArch::Merom
+                        | MicroArch::Penryn
+                        | MicroArch::Bonnel
+                        | MicroArch::Saltwell
+                )
+            } else if model.contains("Ryzen") {
+                matches!(
+                    ma,
+                    MicroArch::Zen
+                        | MicroArch::ZenPlus
+                        | MicroArch::Zen2
+                        | MicroArch::Zen3
+                        | MicroArch::Zen3Plus
+                        | MicroArch::Zen4
+                        | MicroArch::Zen4C
+                        | MicroArch::Zen5
+                        | MicroArch::Zen5C
+                )
+            } else if model.contains("Athlon(tm) XP") || model.contains("Athlon(tm) MP") {
+                ma == MicroArch::K7
+            } else if model.contains("Phenom") {
+                ma == MicroArch::K10
+            } else {
+                true
+            };
+
+            if !line_matches {
+                return false;
+            }
+        }
+
+        if vendor == VENDOR_INTEL
+            && let Some(index_name) = self.intel_brand_index()
+            && let (Some(claimed), Some(indexed)) = (brand_tier(model), brand_tier(index_name))
+        {
+            return claimed == indexed;
+        }
+
+        true
+    }
+}
+
+/// The Intel product tier named in a brand string
+fn brand_tier(model: &str) -> Option<&'static str> {
+    ["Celeron", "Xeon", "Pentium"]
+        .into_iter()
+        .find(|tier| model.contains(tier))
+}
+
+/// The shared `Cpu`, leaked so references to it stay valid after a refresh
+static GLOBAL_CPU: AtomicPtr<Cpu> = AtomicPtr::new(core::ptr::null_mut());
+
+impl Cpu {
+    /// Returns a shared `Cpu`, detected on the first call only.
+    ///
+    /// This avoids re-running CPUID for callers that query the CPU
+    /// repeatedly. Like [`TDetect::detect`], the result reflects the core
+    /// that ran the first call, and the CPUID provider active at the time.
+    #[must_use]
+    pub fn global() -> &'static Cpu {
+        let current = GLOBAL_CPU.load(Ordering::Acquire);
+        if !current.is_null() {
+            // SAFETY: only ever set from `Box::leak`, and never freed
+            return unsafe { &*current };
+        }
+
+        let fresh = Box::into_raw(Box::new(Cpu::detect()));
+        match GLOBAL_CPU.compare_exchange(
+            core::ptr::null_mut(),
+            fresh,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            // SAFETY: just leaked above
+            Ok(_) => unsafe { &*fresh },
+            Err(winner) => {
+                // SAFETY: another thread stored its copy first, so ours was
+                // never shared and can be freed
+                drop(unsafe { Box::from_raw(fresh) });
+                // SAFETY: only ever set from a leaked box, and never freed
+                unsafe { &*winner }
+            }
+        }
+    }
+
+    /// Re-runs detection and replaces the shared `Cpu` returned by
+    /// [`Cpu::global`].
+    ///
+    /// References from earlier calls stay valid, but keep the old values.
+    /// Each call leaks the previous `Cpu`, so this is meant for occasional
+    /// use, such as after switching the CPUID provider.
+    pub fn refresh() -> &'static Cpu {
+        let fresh: &'static Cpu = Box::leak(Box::new(Cpu::detect()));
+        GLOBAL_CPU.store(core::ptr::from_ref(fresh).cast_mut(), Ordering::Release);
+
+        fresh
+    }
+
+    /// Clears the shared `Cpu`, so the next [`Cpu::global`] call detects again
+    #[cfg(test)]
+    pub(crate) fn reset_global() {
+        GLOBAL_CPU.store(core::ptr::null_mut(), Ordering::Release);
+    }
+}
+
 impl TDetect for Cpu {
     /// Detects and returns comprehensive CPU information.
     ///
     /// Performs full CPU detection including architecture, microarchitecture,
     /// brand string, signature, features, and topology.
     fn detect() -> Self {
-        let sig = CpuSignature::detect();
-        let arch = CpuArch::find(&Self::raw_model_string(), sig, &vendor_str());
-        let topology = Topology::detect();
+        let cpu = Self::detect_current_core();
 
         #[cfg(not(dos))]
-        let cores = if is_intel() {
-            Self::detect_core_types()
+        if is_intel() {
+            return Self {
+                cores: Self::detect_core_types(),
+                ..cpu
+            };
+        }
+
+        cpu
+    }
+}
+
+impl Cpu {
+    /// Detects everything but the per-core-type breakdown, from the logical
+    /// processor this is called on.
+    fn detect_current_core() -> Self {
+        let sig = CpuSignature::detect();
+        let arch = CpuArch::find(&Self::raw_model_string(), sig, &vendor_str());
+        let package = amd_package_type();
+        let product_line = if arch.vendor_string == VENDOR_AMD {
+            AMDCpuFamily::find(&arch.model, sig, package)
         } else {
-            Vec::new()
+            None
         };
 
         #[cfg(dos)]
-        let cores = Vec::new();
+        let tme = intel_tme();
+        #[cfg(not(dos))]
+        let tme = None;
 
         Self {
             has_cpuid: (is_cyrix() && Cyrix::can_enable_cpuid()) || has_cpuid(),
@@ -563,8 +1296,22 @@ impl TDetect for Cpu {
             brand_id: get_brand_id(),
             signature: sig,
             features: get_feature_list(),
-            topology,
-            cores,
+            topology: Topology::detect(),
+            cores: Vec::new(),
+            core_type: core_type(),
+            package,
+            product_line,
+            feature_class: FeatureClass::detect(),
+            leaf_7_1_unreliable: leaf_7_1_unreliable(),
+            mode: (cpuid_data_source() == DataSource::Cpuid).then(current_mode),
+            max_leaf: max_leaf(),
+            maxval_limited: cpuid_maxval_limited(),
+            os_disabled: FeatureSet::os_disabled(),
+            sev: amd_sev(),
+            has_tme: has_tme(),
+            tme,
+            pmem: PmemCapabilities::detect(),
+            address_bits: address_bits(),
         }
     }
 }
@@ -573,9 +1320,10 @@ impl TDetect for Cpu {
 impl Cpu {
     /// Enumerates all logical processors to discover unique core types.
     ///
-    /// On non-DOS systems, pins to each logical processor and reads CPUID
-    /// leaf 0x1A to detect core type, aggregating separate entries for
-    /// hybrid architectures (e.g., Intel P-cores and E-cores).
+    /// On non-DOS systems, pins a scoped thread to each logical processor
+    /// and reads CPUID leaf 0x1A to detect core type, aggregating separate
+    /// entries for hybrid architectures (e.g., Intel P-cores and E-cores).
+    /// The calling thread's affinity is unchanged.
     /// Falls back to a single entry for DOS or if enumeration fails.
     pub fn detect_core_types() -> Vec<CpuCore> {
         use super::vendor::Intel;
@@ -609,71 +1357,86 @@ impl Cpu {
             }
         }
 
-        if provider::info_source() == provider::CpuidInfoSource::DumpFile {
-            let dump_count = provider::dump_cpu_count();
-            let cache = Cache::detect();
-            for cpu_idx in 0..dump_count {
-                provider::set_dump_cpu(cpu_idx);
-
-                let core_type = core_type_from_cpuid();
-                let sig = CpuSignature::detect();
-                let arch = CpuArch::find(&Cpu::raw_model_string(), sig, &vendor_str());
-                let micro_arch = if is_intel() {
-                    Intel::core_micro_arch(arch.micro_arch, core_type)
-                } else {
-                    arch.micro_arch
-                };
-
-                // Make sure we know the MicroArch before pushing to core types
-                if micro_arch == MicroArch::Unknown {
-                    continue;
-                }
+        let cache = Cache::detect();
+        let core_types = provider::on_each_logical_processor(|_| {
+            let core_type = core_type_from_cpuid();
+            let sig = CpuSignature::detect();
+            let arch = CpuArch::find(&Cpu::raw_model_string(), sig, &vendor_str());
+            let micro_arch = if is_intel() {
+                Intel::core_micro_arch(arch.micro_arch, core_type)
+            } else {
+                arch.micro_arch
+            };
 
-                let name_str = micro_arch.as_str();
-                let name = if name_str != UNK {
-                    Some(name_str)
-                } else {
-                    None
-                };
+            (core_type, micro_arch)
+        });
 
-                find_or_push(&mut cores, core_type, name, micro_arch, cache, 1, 1);
+        for (core_type, micro_arch) in core_types {
+            // Make sure we know the MicroArch before pushing to core types
+            if micro_arch == MicroArch::Unknown {
+                continue;
             }
 
-            return cores;
-        }
-
-        if let Some(core_ids) = core_affinity::get_core_ids() {
-            let cache = Cache::detect();
+            let name_str = micro_arch.as_str();
+            let name = if name_str != UNK {
+                Some(name_str)
+            } else {
+                None
+            };
 
-            for core_id in core_ids {
-                core_affinity::set_for_current(core_id);
+            find_or_push(&mut cores, core_type, name, micro_arch, cache, 1, 1);
+        }
 
-                let core_type = core_type_from_cpuid();
-                let sig = CpuSignature::detect();
-                let arch = CpuArch::find(&Cpu::raw_model_string(), sig, &vendor_str());
-                let micro_arch = if is_intel() {
-                    Intel::core_micro_arch(arch.micro_arch, core_type)
-                } else {
-                    arch.micro_arch
-                };
+        cores
+    }
 
-                // Make sure we know the MicroArch before pushing to core types
-                if micro_arch == MicroArch::Unknown {
-                    continue;
-                }
+    /// Reads the CPU signature and core type from each logical processor.
+    ///
+    /// Pins a scoped thread to each processor in turn, so the calling
+    /// thread's affinity is unchanged. When reading a dump file, each CPU in
+    /// the dump is used instead. Returns an empty list if the processors
+    /// can't be enumerated.
+    #[must_use]
+    pub fn detect_on_all_cores() -> Vec<CoreSignature> {
+        provider::on_each_logical_processor(|cpu| CoreSignature {
+            cpu,
+            signature: CpuSignature::detect(),
+            core_type: core_type(),
+        })
+    }
 
-                let name_str = micro_arch.as_str();
-                let name = if name_str != UNK {
-                    Some(name_str)
-                } else {
-                    None
-                };
+    /// Runs detection on one logical processor of each core type, so the
+    /// details of both the P-cores and E-cores of a hybrid CPU are available.
+    ///
+    /// This needs the OS thread affinity API, so it isn't available on the
+    /// DOS build. Like [`Cpu::detect_on_all_cores`], it pins a scoped thread
+    /// to each processor in turn, leaving the caller's affinity alone, or
+    /// uses each CPU of a dump file.
+    /// Other CPUs give a single `Cpu`, the same as [`Cpu::detect`].
+    #[must_use]
+    pub fn detect_all() -> Vec<Cpu> {
+        let cores = if is_intel() {
+            Self::detect_core_types()
+        } else {
+            Vec::new()
+        };
 
-                find_or_push(&mut cores, core_type, name, micro_arch, cache, 1, 1);
+        let mut all: Vec<Cpu> = Vec::new();
+        provider::on_each_logical_processor(|_| {
+            let core_type = core_type();
+            if !all.iter().any(|cpu| cpu.core_type == core_type) {
+                all.push(Cpu {
+                    cores: cores.clone(),
+                    ..Self::detect_current_core()
+                });
             }
+        });
+
+        if all.is_empty() {
+            all.push(Cpu::detect());
         }
 
-        cores
+        all
     }
 }
 
@@ -695,12 +1458,150 @@ mod tests {
         assert!(!features.is_empty());
     }
 
+    #[test]
+    fn test_tsc_aux_matches_apic() {
+        // Whether it matches depends on the OS, but there is an answer
+        if has_rdtscp() && probes_enabled() {
+            assert!(Cpu::tsc_aux_matches_apic().is_some());
+        } else {
+            assert_eq!(Cpu::tsc_aux_matches_apic(), None);
+        }
+    }
+
     #[test]
     fn test_cpu_new() {
         let cpu = Cpu::detect();
         // Ensure that new() doesn't panic and populates some fields
         assert!(!cpu.arch.vendor_string.is_empty());
         assert!(!cpu.features.is_empty());
+        assert_eq!(cpu.mode, Some(current_mode()));
+    }
+
+    #[test]
+    fn test_cpu_debug_format() {
+        use crate::common::{Speed, TopologyTier};
+        use alloc::format;
+
+        let tier = |count| TopologyTier::new(count, DataSource::Cpuid);
+        let mut features = BTreeMap::new();
+        features.insert("Base", String::from("FPU TSC"));
+        features.insert("SSE", String::from("SSE SSE2"));
+
+        let mut cpu = Cpu {
+            has_cpuid: true,
+            arch: CpuArch::new(
+                "AMD Ryzen 9 5900XT",
+                MicroArch::Zen3,
+                "Vermeer",
+                "AMD",
+                "AuthenticAMD",
+                Some("7nm"),
+            ),
+            signature: CpuSignature {
+                display_family: 0x19,
+                display_model: 0x21,
+                stepping: 2,
+                ..CpuSignature::default()
+            },
+            features,
+            cores: alloc::vec![CpuCore {
+                kind: CoreType::Performance,
+                micro_arch: MicroArch::Zen3,
+                name: Some("Zen 3"),
+                cache: None,
+                count: 16,
+                threads: 32,
+            }],
+            ..Cpu::default()
+        };
+        cpu.topology.sockets = tier(1);
+        cpu.topology.cores = tier(16);
+        cpu.topology.threads = tier(32);
+        cpu.topology.speed = Speed {
+            base: 3300,
+            boost: 4800,
+            measured: false,
+        };
+
+        assert_eq!(
+            format!("{cpu:?}"),
+            "Cpu { vendor: \"AuthenticAMD\", model: \"AMD Ryzen 9 5900XT\", \
+             micro_arch: Zen3, code_name: \"Vermeer\", technology: Some(\"7nm\"), \
+             signature: 19h/21h/2h, signature_source: DefaultValue, brand_id: 0, \
+             has_cpuid: true, hypervisor: None, easter_egg: None, core_type: None, \
+             topology: 1/16/32, topology_source: Cpuid/Cpuid/Cpuid, \
+             speed: 3300/4800 MHz, cache: None, cores: [Performance x16/32], \
+             features: {\"Base\": \"FPU TSC\", \"SSE\": \"SSE SSE2\"} }"
+        );
+    }
+
+    #[test]
+    fn test_stepping_name() {
+        let cpu = |vendor: &str, family, model, stepping| Cpu {
+            arch: CpuArch::new(UNK, MicroArch::Unknown, UNK, UNK, vendor, None),
+            signature: CpuSignature {
+                display_family: family,
+                display_model: model,
+                stepping,
+                ..CpuSignature::default()
+            },
+            ..Cpu::default()
+        };
+
+        assert_eq!(cpu(VENDOR_AMD, 0x10, 2, 3).stepping_name(), Some("B3"));
+        assert_eq!(cpu(VENDOR_INTEL, 6, 0x9E, 0xC).stepping_name(), Some("P0"));
+        assert_eq!(cpu(VENDOR_INTEL, 0x10, 2, 3).stepping_name(), None);
+        assert_eq!(Cpu::default().stepping_name(), None);
+    }
+
+    #[test]
+    fn test_brand_signature_consistent() {
+        let cpu = |model: &str, vendor: &str, micro_arch, brand_id| Cpu {
+            arch: CpuArch::new(model, micro_arch, UNK, UNK, vendor, None),
+            brand_id,
+            signature: CpuSignature {
+                family: 0xF,
+                model: 2,
+                ..CpuSignature::default()
+            },
+            ..Cpu::default()
+        };
+
+        let i7 = "Intel(R) Core(TM) i7-12700H";
+        assert!(cpu(i7, VENDOR_INTEL, MicroArch::AlderLake, 0).brand_signature_consistent());
+        assert!(!cpu(i7, VENDOR_INTEL, MicroArch::Northwood, 0).brand_signature_consistent());
+        assert!(!cpu(i7, VENDOR_AMD, MicroArch::Unknown, 0).brand_signature_consistent());
+
+        let ryzen = "AMD Ryzen 9 5900XT 16-Core Processor";
+        assert!(cpu(ryzen, VENDOR_AMD, MicroArch::Zen3, 0).brand_signature_consistent());
+        assert!(!cpu(ryzen, VENDOR_AMD, MicroArch::K10, 0).brand_signature_consistent());
+
+        // A Northwood with a Pentium 4 brand index, claiming to be a Xeon
+        let p4 = "Intel(R) Pentium(R) 4 CPU 2.40GHz";
+        let xeon = "Intel(R) Xeon(TM) CPU 2.40GHz";
+        assert!(cpu(p4, VENDOR_INTEL, MicroArch::Northwood, 0x09).brand_signature_consistent());
+        assert!(!cpu(xeon, VENDOR_INTEL, MicroArch::Northwood, 0x09).brand_signature_consistent());
+        assert!(cpu(xeon, VENDOR_INTEL, MicroArch::Northwood, 0x0B).brand_signature_consistent());
+
+        // Nothing to compare
+        assert!(Cpu::default().brand_signature_consistent());
+        assert!(cpu(i7, VENDOR_INTEL, MicroArch::Unknown, 0).brand_signature_consistent());
+    }
+
+    #[test]
+    fn test_cpu_global() {
+        let cpu = Cpu::global();
+        assert!(core::ptr::eq(cpu, Cpu::global()));
+        assert_eq!(cpu.signature, CpuSignature::detect());
+
+        // Kept in one test, as the shared value is process-wide
+        let refreshed = Cpu::refresh();
+        assert!(!core::ptr::eq(cpu, refreshed));
+        assert!(core::ptr::eq(refreshed, Cpu::global()));
+        assert_eq!(cpu.signature, refreshed.signature);
+
+        Cpu::reset_global();
+        assert!(!core::ptr::eq(refreshed, Cpu::global()));
     }
 
     #[test]
@@ -2,9 +2,23 @@ use super::cpu::Cpu;
 use super::micro_arch::MicroArch;
 use super::*;
 
-use crate::common::{CliFlags, CpuDisplay, DataSource, TCpuDisplay, UNK};
+use crate::common::display::outln;
+use crate::common::{
+    CliFlags, ConsoleWriter, CpuDisplay, DataSource, Report, TCpuDisplay, UNK, fmt_dec, fmt_hex,
+};
+#[cfg(any(not(dos), feature = "debug"))]
 use crate::println;
 use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A feature's name, prefixed with its group for the groups whose names
+/// are only clear next to it, like `AVX512-VL` or `AMX-TILE`
+fn grouped_feature_name(group: &str, name: &str) -> String {
+    match group {
+        "AVX512" | "AMX" => alloc::format!("{group}-{name}"),
+        _ => String::from(name),
+    }
+}
 
 fn yes_no(b: bool) -> &'static str {
     if b { "Yes" } else { "No" }
@@ -19,6 +33,9 @@ impl Cpu {
         if flags.verbose {
             disp.simple_line("CPUID", yes_no(cpuid));
             disp.simple_line("Overdrive", yes_no(overdrive));
+            if let Some(mode) = self.mode {
+                disp.simple_line("CPU Mode", mode.into());
+            }
         } else {
             if !cpuid {
                 disp.simple_line("CPUID", "No");
@@ -27,6 +44,22 @@ impl Cpu {
                 disp.simple_line("Overdrive", "Yes");
             }
         }
+
+        if self.maxval_limited == Some(true) {
+            outln!(
+                disp,
+                "{}CPUID limited to leaf {} by the BIOS",
+                disp.label("Warning"),
+                self.max_leaf
+            );
+            disp.newline();
+        }
+
+        if flags.verbose {
+            for problem in self.validate() {
+                disp.simple_line("Warning", &problem);
+            }
+        }
     }
 
     fn print_model(&self, flags: CliFlags, disp: &CpuDisplay) {
@@ -38,48 +71,50 @@ impl Cpu {
             } else if raw_model.trim().eq(&disp_model) {
                 disp.simple_line("Model", &disp_model);
             } else {
-                println!("{}{}", disp.label("Model"), &disp_model);
+                outln!(disp, "{}{}", disp.label("Model"), &disp_model);
 
                 if flags.verbose {
-                    println!("{}{}", disp.label("Model (raw)"), &raw_model);
+                    outln!(disp, "{}{}", disp.label("Model (raw)"), &raw_model);
                 }
 
-                CpuDisplay::newline();
+                disp.newline();
             }
         }
     }
 
     fn print_topology(&self, flags: CliFlags, disp: &CpuDisplay) {
         if !self.cores.is_empty() {
-            println!(
+            outln!(
+                disp,
                 "{}{} cores ({} threads) across {} core types",
                 disp.label("Cpu Topology"),
                 self.topology.cores.count,
                 self.topology.threads.count,
                 self.cores.len()
             );
-            CpuDisplay::newline();
+            disp.newline();
 
             for (i, core) in self.cores.iter().enumerate() {
                 let core_label = alloc::format!("Core #{}", i + 1);
-                println!("{}", disp.label(&core_label));
+                outln!(disp, "{}", disp.label(&core_label));
 
                 let type_str: &str = core.kind.into();
-                println!("{}{}", disp.label("Type"), type_str);
+                outln!(disp, "{}{}", disp.label("Type"), type_str);
 
                 if let Some(name) = &core.name {
-                    println!("{}{}", disp.label("Codename"), name);
+                    outln!(disp, "{}{}", disp.label("Codename"), name);
                 }
 
                 if core.count != core.threads {
-                    println!(
+                    outln!(
+                        disp,
                         "{}{} cores ({} threads)",
                         disp.label("Topology"),
                         core.count,
                         core.threads
                     );
                 } else {
-                    println!("{}{} cores", disp.label("Topology"), core.count);
+                    outln!(disp, "{}{} cores", disp.label("Topology"), core.count);
                 }
 
                 let cc = |s: u32| CpuDisplay::cache_count(s, core.count);
@@ -94,7 +129,8 @@ impl Cpu {
         if multi_core || flags.verbose {
             let lbl = disp.label("Topology");
             if self.topology.sockets.count > 1 || flags.verbose {
-                println!(
+                outln!(
+                    disp,
                     "{}{} sockets, {} cores, {} threads",
                     lbl,
                     self.topology.sockets.count,
@@ -102,15 +138,18 @@ impl Cpu {
                     self.topology.threads.count
                 );
             } else if self.topology.cores.count != self.topology.threads.count {
-                println!(
+                outln!(
+                    disp,
                     "{}{} cores ({} threads)",
-                    lbl, self.topology.cores.count, self.topology.threads.count
+                    lbl,
+                    self.topology.cores.count,
+                    self.topology.threads.count
                 );
             } else {
-                println!("{}{} cores", lbl, self.topology.cores.count);
+                outln!(disp, "{}{} cores", lbl, self.topology.cores.count);
             }
 
-            CpuDisplay::newline();
+            disp.newline();
         }
     }
 
@@ -120,25 +159,28 @@ impl Cpu {
             let boost = self.topology.speed.boost;
 
             if boost > base {
-                println!(
+                outln!(
+                    disp,
                     "{}{}",
                     disp.inline_sublabel("Frequency", "Base"),
                     CpuDisplay::format_frequency(base)
                 );
-                println!(
+                outln!(
+                    disp,
                     "{}{}",
                     disp.sublabel("Boost"),
                     CpuDisplay::format_frequency(boost)
                 );
             } else {
-                println!(
+                outln!(
+                    disp,
                     "{}{}",
                     disp.label("Frequency"),
                     CpuDisplay::format_frequency(base)
                 );
             }
 
-            CpuDisplay::newline();
+            disp.newline();
         }
     }
 
@@ -152,45 +194,94 @@ impl Cpu {
                 "Synthetic Sig"
             };
 
-            println!(
-                "{}Family {:X}h, Model {:X}h, Stepping {:X}h",
+            outln!(
+                disp,
+                "{}Family {}h, Model {}h, Stepping {}h{}",
                 disp.label(key),
-                self.signature.display_family,
-                self.signature.display_model,
-                self.signature.stepping
+                fmt_hex(self.signature.display_family, 0),
+                fmt_hex(self.signature.display_model, 0),
+                fmt_hex(self.signature.stepping, 0),
+                self.stepping_name()
+                    .map(|name| alloc::format!(" ({name})"))
+                    .unwrap_or_default()
             );
             if flags.verbose {
-                println!(
-                    "{:>16}({:X}, {:X}, {:X}, {:X}, {:X})",
+                outln!(
+                    disp,
+                    "{}({}, {}, {}, {}, {})",
                     disp.sublabel("hex"),
-                    self.signature.extended_family,
-                    self.signature.family,
-                    self.signature.extended_model,
-                    self.signature.model,
-                    self.signature.stepping
+                    fmt_hex(self.signature.extended_family, 0),
+                    fmt_hex(self.signature.family, 0),
+                    fmt_hex(self.signature.extended_model, 0),
+                    fmt_hex(self.signature.model, 0),
+                    fmt_hex(self.signature.stepping, 0)
                 );
-                println!(
-                    "{:>16}({}, {}, {}, {}, {})",
+                outln!(
+                    disp,
+                    "{}({}, {}, {}, {}, {})",
                     disp.sublabel("dec"),
-                    self.signature.extended_family,
-                    self.signature.family,
-                    self.signature.extended_model,
-                    self.signature.model,
-                    self.signature.stepping
+                    fmt_dec(self.signature.extended_family, 0),
+                    fmt_dec(self.signature.family, 0),
+                    fmt_dec(self.signature.extended_model, 0),
+                    fmt_dec(self.signature.model, 0),
+                    fmt_dec(self.signature.stepping, 0)
                 );
-            } else {
-                println!(
-                    "{:>16}({}, {}, {}, {}, {})",
-                    "",
-                    self.signature.extended_family,
-                    self.signature.family,
-                    self.signature.extended_model,
-                    self.signature.model,
-                    self.signature.stepping
+            } else if flags.raw_signature {
+                outln!(
+                    disp,
+                    "{}({}, {}, {}, {}, {})",
+                    disp.indent(),
+                    fmt_dec(self.signature.extended_family, 0),
+                    fmt_dec(self.signature.family, 0),
+                    fmt_dec(self.signature.extended_model, 0),
+                    fmt_dec(self.signature.model, 0),
+                    fmt_dec(self.signature.stepping, 0)
                 );
             }
 
-            CpuDisplay::newline();
+            disp.newline();
+        }
+    }
+}
+
+impl Cpu {
+    /// Prints one row from [`Cpu::as_pairs`], using the richer table
+    /// layout for the rows that have one
+    fn emit_pair(&self, key: &str, value: &str, flags: CliFlags, disp: &CpuDisplay) {
+        match key {
+            "Model" => self.print_model(flags, disp),
+            "Topology" => {
+                self.print_topology(flags, disp);
+
+                if self.cores.is_empty() {
+                    let cache_count = |share_count: u32| -> String {
+                        #[allow(clippy::manual_checked_ops)]
+                        let count = if share_count == 0 {
+                            self.topology.sockets.count
+                        } else {
+                            self.topology.threads.count / share_count
+                        };
+
+                        if count < 2 {
+                            String::new()
+                        } else {
+                            alloc::format!("{}x ", count)
+                        }
+                    };
+
+                    disp.display_cache(
+                        self.topology.cache,
+                        &cache_count,
+                        self.topology.sockets.count,
+                    );
+                }
+            }
+            "Frequency" => self.print_speed(disp),
+            // Printed with the base frequency
+            "Boost" => {}
+            "Signature" => self.print_signature(flags, disp),
+            "Features" => self.print_features(flags, disp),
+            _ => disp.simple_line(key, value),
         }
     }
 }
@@ -198,33 +289,33 @@ impl Cpu {
 // Cpu features display
 impl Cpu {
     fn print_simple_features_list(&self, disp: &CpuDisplay) {
-        disp.simple_line(
-            "Features",
+        disp.wrapped_line(
+            &disp.label("Features"),
             self.features
                 .get("Base")
                 .expect("There should be at least one key in the features BTreeMap."),
         );
+
+        disp.newline();
     }
 
     fn print_full_features_list(&self, disp: &CpuDisplay) {
         let keys = [
-            "Base", "SSE", "AVX", "AVX512", "Security", "Math", "Other", "Centaur",
+            "Base", "SSE", "AVX", "AVX512", "AMX", "Security", "Math", "Other", "Centaur",
         ];
         for key in keys {
             if self.features.contains_key(key) {
                 if key == "Base" {
-                    println!(
-                        "{}{}",
-                        disp.inline_sublabel("Features", "Base"),
-                        self.features.get(key).expect("Missing Base key?")
-                    )
+                    disp.wrapped_line(
+                        &disp.inline_sublabel("Features", "Base"),
+                        self.features.get(key).expect("Missing Base key?"),
+                    );
                 } else {
-                    println!(
-                        "{}{}",
-                        disp.sublabel(key),
+                    disp.wrapped_line(
+                        &disp.sublabel(key),
                         self.features
                             .get(key)
-                            .expect("Somehow the key in the features BTreeMap disappeared!")
+                            .expect("Somehow the key in the features BTreeMap disappeared!"),
                     );
                 }
             }
@@ -249,11 +340,25 @@ impl Cpu {
             }
 
             if !list.is_empty() {
-                println!("{}{}", disp.sublabel("Centaur"), list.join(", "));
+                outln!(disp, "{}{}", disp.sublabel("Centaur"), list.join(", "));
             }
         }
     }
 
+    /// Lists features the hardware has, but the OS hasn't enabled
+    #[cfg(not(dos))]
+    fn print_os_disabled_features(&self, disp: &CpuDisplay) {
+        let disabled: Vec<String> = self
+            .os_disabled
+            .iter()
+            .map(|spec| grouped_feature_name(spec.group, spec.name))
+            .collect();
+
+        if !disabled.is_empty() {
+            disp.wrapped_line(&disp.sublabel("OS Disabled"), &disabled.join(" "));
+        }
+    }
+
     #[allow(unused_variables)]
     fn print_features(&self, flags: CliFlags, disp: &CpuDisplay) {
         if !self.features.is_empty() {
@@ -264,137 +369,436 @@ impl Cpu {
                 self.print_full_features_list(disp);
             }
 
+            #[cfg(not(dos))]
+            self.print_os_disabled_features(disp);
+
             // Centaur features list
             #[cfg(not(dos))]
             if is_centaur() {
                 self.print_centaur_features(flags, disp);
             }
 
-            CpuDisplay::newline();
+            disp.newline();
         }
     }
 }
 
-impl TCpuDisplay for Cpu {
-    fn debug(&self) {
-        #[cfg(not(dos))]
-        println!("{:#?}", self);
+// CPU-Z style display
+impl Cpu {
+    /// Instruction set extensions, in the order and naming CPU-Z uses
+    fn cpuz_instructions(&self) -> String {
+        let x86_64 = if self.arch.vendor_string == VENDOR_INTEL {
+            "EM64T"
+        } else {
+            "x86-64"
+        };
+
+        // CPU-Z's name, then the group and name in the feature list
+        let list: &[(&str, &str, &str)] = &[
+            ("MMX", "Base", "MMX"),
+            ("MMX+", "Base", "MMX+"),
+            ("3DNow!", "Base", "3DNow!"),
+            ("3DNow!+", "Base", "3DNow!+"),
+            ("SSE", "SSE", "SSE"),
+            ("SSE2", "SSE", "SSE2"),
+            ("SSE3", "SSE", "SSE3"),
+            ("SSSE3", "SSE", "SSSE3"),
+            ("SSE4.1", "SSE", "SSE4.1"),
+            ("SSE4.2", "SSE", "SSE4.2"),
+            ("SSE4A", "SSE", "SSE4A"),
+            (x86_64, "Base", "AMD64"),
+            ("VT-x", "Security", "VT-x"),
+            ("AMD-V", "Security", "AMD-V"),
+            ("AES", "Security", "AES"),
+            ("AVX", "AVX", "AVX"),
+            ("AVX2", "AVX", "AVX2"),
+            ("AVX-VNNI", "AVX", "AVX-VNNI"),
+            ("AVX512F", "AVX512", "F"),
+            ("FMA3", "Math", "FMA"),
+            ("SHA", "Security", "SHA"),
+        ];
 
-        #[cfg(all(dos, feature = "debug"))]
-        {
-            use super::is_cyrix;
+        list.iter()
+            .filter(|(_, group, name)| self.has_listed_feature(group, name))
+            .map(|(cpuz_name, _, _)| *cpuz_name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
 
-            println!("{:?}", self);
-            if is_cyrix() {
-                println!("{:?}", super::vendor::Cyrix::detect());
-            }
-        }
+    /// Display the CPU information grouped like the CPU tab of CPU-Z
+    pub fn display_cpuz(&self, flags: CliFlags) {
+        self.emit_cpuz(flags, &mut ConsoleWriter);
     }
 
-    fn display_table(&self, flags: CliFlags) {
-        let disp = CpuDisplay { flags };
+    /// Write the CPU information grouped like the CPU tab of CPU-Z to `out`
+    pub fn emit_cpuz(&self, flags: CliFlags, out: &mut dyn core::fmt::Write) {
+        let disp = CpuDisplay::new(flags, out);
+        let sig = &self.signature;
 
-        let ma = self.arch.micro_arch.as_str();
+        outln!(
+            disp,
+            "{}{}",
+            disp.label("Name"),
+            self.display_model_string()
+        );
+
+        let code_name = match self.arch.code_name {
+            UNK => self.arch.micro_arch.as_str(),
+            code_name => code_name,
+        };
+        outln!(disp, "{}{}", disp.label("Code Name"), code_name);
+
+        if let Some(package) = self.package {
+            outln!(disp, "{}{}", disp.label("Package"), package);
+        }
+
+        if let Some(tech) = &self.arch.technology {
+            outln!(disp, "{}{}", disp.label("Technology"), tech);
+        }
+
+        if self.arch.model != UNK {
+            outln!(disp, "{}{}", disp.label("Specification"), self.arch.model);
+        }
 
-        disp.simple_line("Architecture", FeatureClass::detect().to_str());
+        if *sig != CpuSignature::default() {
+            // Pad the values, so the columns line up
+            outln!(
+                disp,
+                "{}{:<4}{}{:<4}{}{}",
+                disp.label("Family"),
+                fmt_hex(sig.family, 0),
+                disp.label("Model"),
+                fmt_hex(sig.model, 0),
+                disp.label("Stepping"),
+                fmt_hex(sig.stepping, 0)
+            );
 
-        // Vendor_string (brand_name)
-        if self.arch.brand_name != UNK {
-            println!(
-                "{}{} ({})",
-                disp.label("Vendor"),
-                self.arch.vendor_string,
-                self.arch.brand_name
+            let revision = self
+                .stepping_name()
+                .map(|name| alloc::format!("{}{name}", disp.label("Revision")))
+                .unwrap_or_default();
+            let ext = alloc::format!(
+                "{}{:<4}{}{:<4}{}",
+                disp.label("Ext. Family"),
+                fmt_hex(sig.display_family, 0),
+                disp.label("Ext. Model"),
+                fmt_hex(sig.display_model, 0),
+                revision
             );
+            outln!(disp, "{}", ext.trim_end());
+        }
 
-            CpuDisplay::newline();
+        let instructions = self.cpuz_instructions();
+        if !instructions.is_empty() {
+            disp.wrapped_line(&disp.label("Instructions"), &instructions);
+        }
+        disp.newline();
+
+        if self.topology.speed.base > 0 {
+            outln!(
+                disp,
+                "{}{}",
+                disp.label("Core Speed"),
+                CpuDisplay::format_frequency(self.topology.speed.base)
+            );
+            if self.topology.speed.boost > self.topology.speed.base {
+                outln!(
+                    disp,
+                    "{}{}",
+                    disp.label("Max Speed"),
+                    CpuDisplay::format_frequency(self.topology.speed.boost)
+                );
+            }
+            disp.newline();
+        }
+
+        if let Some(cache) = self.topology.cache {
+            let cc = |s: u32| CpuDisplay::cache_count(s, self.topology.cores.count);
+            disp.display_cache(Some(cache), &cc, self.topology.sockets.count);
+        }
+
+        outln!(
+            disp,
+            "{}{:<4}{}{:<4}{}{}",
+            disp.label("Sockets"),
+            self.topology.sockets.count,
+            disp.label("Cores"),
+            self.topology.cores.count,
+            disp.label("Threads"),
+            self.topology.threads.count
+        );
+    }
+}
+
+// Key/value output
+impl Cpu {
+    /// Is there a vendor string to show? CPUs without CPUID that can't be
+    /// identified don't have one.
+    fn has_vendor_string(&self) -> bool {
+        let vendor = self.arch.vendor_string.as_str();
+
+        !vendor.trim().is_empty() && vendor != UNK
+    }
+
+    /// Returns the detected information as (label, value) pairs, in table
+    /// order, for formats other than the table.
+    ///
+    /// Unknown values are left out. The feature groups are combined into
+    /// one "Features" value, with AVX-512 and AMX names prefixed, as in
+    /// "AVX512-F".
+    #[must_use]
+    pub fn as_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = Vec::new();
+        let mut push = |key: &'static str, value: String| pairs.push((key, value));
+
+        push("Architecture", String::from(self.feature_class.to_str()));
+
+        if self.has_vendor_string() {
+            push(
+                "Vendor",
+                alloc::format!("{} ({})", self.arch.vendor_string, self.arch.brand_name),
+            );
         }
 
-        // Hypervisor vendor_string (brand_name)
-        #[cfg(not(dos))]
         if let Some(hyp_str) = &self.hyp_vendor_str {
-            let hyp = HypervisorBrand::from(hyp_str.as_str());
-            println!("{}{} ({})", disp.label("Hypervisor"), hyp_str, hyp.to_str());
+            #[cfg(not(dos))]
+            push(
+                "Hypervisor",
+                alloc::format!(
+                    "{} ({})",
+                    hyp_str,
+                    HypervisorBrand::from(hyp_str.as_str()).to_str()
+                ),
+            );
 
-            CpuDisplay::newline();
+            #[cfg(dos)]
+            push("Hypervisor", hyp_str.clone());
         }
 
-        // Cpu model string
-        self.print_model(flags, &disp);
+        let model = self.display_model_string();
+        if model != UNK {
+            push("Model", model);
+        }
 
+        let ma = self.arch.micro_arch.as_str();
         if ma != UNK {
-            disp.simple_line("MicroArch", ma);
+            push("MicroArch", String::from(ma));
         }
 
-        if !(self.arch.code_name == "Unknown"
+        if !(self.arch.code_name == UNK
             || self.arch.code_name == ma
             || self.arch.micro_arch == MicroArch::I486)
         {
-            disp.simple_line("Codename", self.arch.code_name);
+            push("Codename", String::from(self.arch.code_name));
         }
 
-        // Process node
         if let Some(tech) = &self.arch.technology {
-            disp.simple_line("Process Node", tech);
+            push("Process Node", String::from(*tech));
+        }
+
+        if let Some(package) = self.package {
+            push("Package", String::from(package));
+        }
+
+        if let Some(family) = self.product_line {
+            push("Product Line", String::from(family.as_str()));
         }
 
-        // Easter Egg (AMD K6, K8, Jaguar or Rise mp6)
         if let Some(easter_egg) = &self.easter_egg {
-            disp.simple_line("Easter Egg", easter_egg);
+            push("Easter Egg", easter_egg.clone());
         }
 
-        // Overdrive, CPUID support, etc
-        self.print_misc_flags(flags, &disp);
+        if let Some(core_type) = self.core_type {
+            push("Current Core", String::from(<&str>::from(core_type)));
+        }
 
-        // Sockets / Cores / Threads
-        self.print_topology(flags, &disp);
+        push(
+            "Topology",
+            alloc::format!(
+                "{} sockets, {} cores, {} threads",
+                self.topology.sockets.count,
+                self.topology.cores.count,
+                self.topology.threads.count
+            ),
+        );
 
-        // Cache
-        if self.cores.is_empty() {
-            let cache_count = |share_count: u32| -> String {
-                #[allow(clippy::manual_checked_ops)]
-                let count = if share_count == 0 {
-                    self.topology.sockets.count
-                } else {
-                    self.topology.threads.count / share_count
-                };
+        if self.topology.speed.base > 0 {
+            push(
+                "Frequency",
+                CpuDisplay::format_frequency(self.topology.speed.base),
+            );
+        }
 
-                if count < 2 {
-                    String::new()
-                } else {
-                    alloc::format!("{}x ", count)
-                }
-            };
+        if self.topology.speed.boost > self.topology.speed.base {
+            push(
+                "Boost",
+                CpuDisplay::format_frequency(self.topology.speed.boost),
+            );
+        }
 
-            disp.display_cache(
-                self.topology.cache,
-                &cache_count,
-                self.topology.sockets.count,
+        if self.signature != CpuSignature::default() {
+            push(
+                "Signature",
+                alloc::format!(
+                    "Family {}h, Model {}h, Stepping {}h",
+                    fmt_hex(self.signature.display_family, 0),
+                    fmt_hex(self.signature.display_model, 0),
+                    fmt_hex(self.signature.stepping, 0)
+                ),
             );
         }
 
-        // Clock Speed (Base/Boost)
-        self.print_speed(&disp);
+        if !self.features.is_empty() {
+            let features: Vec<String> = self
+                .features
+                .iter()
+                .flat_map(|(group, names)| {
+                    names
+                        .split(' ')
+                        .map(move |name| grouped_feature_name(group, name))
+                })
+                .collect();
+            push("Features", features.join(" "));
+        }
+
+        pairs
+    }
+
+    /// Returns the detected information as a two-column Markdown table,
+    /// for pasting into bug reports.
+    ///
+    /// The rows are the same as [`Cpu::as_pairs`].
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("| Field | Value |\n|-------|-------|\n");
+
+        for (key, value) in self.as_pairs() {
+            // A pipe would end the cell early
+            let value = value.replace('|', "\\|");
+            out.push_str(&alloc::format!("| {key} | {value} |\n"));
+        }
+
+        out
+    }
+}
+
+impl TCpuDisplay for Cpu {
+    fn debug(&self) {
+        #[cfg(not(dos))]
+        println!("{:#?}", self);
+
+        #[cfg(all(dos, feature = "debug"))]
+        {
+            use super::is_cyrix;
+
+            println!("{:?}", self);
+            if is_cyrix() {
+                println!("{:?}", super::vendor::Cyrix::detect());
+            }
+        }
+    }
+
+    fn display_table(&self, flags: CliFlags) {
+        self.emit(flags, &mut ConsoleWriter);
+    }
+}
+
+impl Report for Cpu {
+    fn emit(&self, flags: CliFlags, out: &mut dyn core::fmt::Write) {
+        let disp = CpuDisplay::new(flags, out);
+
+        // Overdrive, CPUID support, etc go between the identity rows and
+        // the core counts
+        let pairs = self.as_pairs();
+        let split = pairs
+            .iter()
+            .position(|(key, _)| matches!(*key, "Current Core" | "Topology"))
+            .unwrap_or(pairs.len());
+        let (identity, counts) = pairs.split_at(split);
+
+        for (key, value) in identity {
+            self.emit_pair(key, value, flags, &disp);
+        }
+
+        self.print_misc_flags(flags, &disp);
+
+        for (key, value) in counts {
+            self.emit_pair(key, value, flags, &disp);
+        }
 
-        // CPU Signature
-        self.print_signature(flags, &disp);
+        // AMD memory encryption
+        let sev = self.sev;
+        if sev.is_supported() {
+            let names: Vec<&str> = [
+                (sev.sme, "SME"),
+                (sev.sev, "SEV"),
+                (sev.sev_es, "SEV-ES"),
+                (sev.sev_snp, "SEV-SNP"),
+            ]
+            .iter()
+            .filter(|(supported, _)| *supported)
+            .map(|&(_, name)| name)
+            .collect();
+
+            outln!(disp, "{}{}", disp.label("Encryption"), names.join(" "));
+            outln!(disp, "{}{}", disp.sublabel("C-bit"), sev.c_bit);
+            if sev.sev {
+                outln!(
+                    disp,
+                    "{}{}",
+                    disp.sublabel("Encrypted Guests"),
+                    sev.encrypted_guests
+                );
+            }
+            disp.newline();
+        }
 
-        // CPU Features
-        self.print_features(flags, &disp);
+        // Intel memory encryption
+        if self.has_tme {
+            outln!(disp, "{}TME", disp.label("Encryption"));
+
+            if let Some(tme) = self.tme {
+                outln!(disp, "{}{}", disp.sublabel("Enabled"), yes_no(tme.enabled));
+                if tme.key_ids() > 0 {
+                    outln!(disp, "{}{}", disp.sublabel("MKTME Keys"), tme.key_ids());
+                }
+            }
+
+            disp.newline();
+        }
 
         #[cfg(target_arch = "x86")]
         if is_cyrix() {
             let cyrix = vendor::Cyrix::detect();
 
             if cyrix.dir0 != 0xFF {
-                println!("{}Model number: {:X}h", disp.label("Cyrix"), cyrix.dir0);
-                println!("{}{:X}h", disp.sublabel("Revision"), cyrix.revision);
-                println!("{}{:X}h", disp.sublabel("Stepping"), cyrix.stepping);
+                outln!(
+                    disp,
+                    "{}Model number: {}h",
+                    disp.label("Cyrix"),
+                    fmt_hex(u32::from(cyrix.dir0), 0)
+                );
+                outln!(
+                    disp,
+                    "{}{}h",
+                    disp.sublabel("Revision"),
+                    fmt_hex(u32::from(cyrix.revision), 0)
+                );
+                outln!(
+                    disp,
+                    "{}{}h",
+                    disp.sublabel("Stepping"),
+                    fmt_hex(u32::from(cyrix.stepping), 0)
+                );
                 if !cyrix.multiplier.is_empty() && cyrix.multiplier != "0" {
-                    println!("{}{}x", disp.sublabel("Bus Multiplier"), &cyrix.multiplier);
+                    outln!(
+                        disp,
+                        "{}{}x",
+                        disp.sublabel("Bus Multiplier"),
+                        &cyrix.multiplier
+                    );
                 }
-                #[cfg(not(dos))]
-                println!();
+                disp.newline();
             }
         }
     }
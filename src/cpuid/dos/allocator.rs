@@ -74,9 +74,11 @@ unsafe impl GlobalAlloc for DosAllocator {
     }
 }
 
+#[cfg(feature = "dos-build")]
 #[global_allocator]
 static ALLOCATOR: DosAllocator = DosAllocator::new();
 
+#[cfg(feature = "dos-build")]
 unsafe extern "C" {
     static mut _heap: u8;
 }
@@ -86,6 +88,7 @@ unsafe extern "C" {
 /// # Safety
 /// This function must be called early in the program's execution,
 /// before any allocations occur.
+#[cfg(feature = "dos-build")]
 pub unsafe fn init_heap() {
     let heap_start = &raw mut _heap as usize;
 
@@ -10,11 +10,15 @@ use core::arch::asm;
 use core::fmt::Write;
 
 pub mod allocator;
+#[cfg(feature = "dos-build")]
 pub use allocator::init_heap;
 
 /// Custom panic handler for no-std environments.
 /// Loops indefinitely on panic to prevent undefined behavior.
-#[cfg(not(test))]
+///
+/// Only installed for the DOS executables, so a bare-metal program using
+/// rustid as a library can provide its own.
+#[cfg(all(not(test), feature = "dos-build"))]
 #[cold]
 #[panic_handler]
 fn panic(_info: &core::panic::PanicInfo) -> ! {
@@ -33,41 +37,44 @@ fn panic(_info: &core::panic::PanicInfo) -> ! {
     exit(1);
 }
 
-/// Prints a formatted string to the DOS console.
-/// Supports both literal strings and format strings.
+/// Prints to the DOS console.
+///
+/// Takes the same arguments as `std::print!`. Strings without format
+/// arguments are written directly, skipping the formatting machinery.
 #[macro_export]
 macro_rules! print {
-    ($s:literal) => {
-        $crate::cpuid::dos::_print_str($s)
-    };
     ($($arg:tt)*) => {
-        {
-            use core::fmt::Write;
-            let _ = write!(&mut $crate::cpuid::dos::DosWriter {}, $($arg)*);
-        }
+        $crate::cpuid::dos::_print(::core::format_args!($($arg)*))
     };
 }
 
-/// Prints a formatted string followed by a newline to the DOS console.
+/// Prints to the DOS console, followed by a DOS (CRLF) newline.
+///
+/// Takes the same arguments as `std::println!`.
 #[macro_export]
 macro_rules! println {
     () => {
-        $crate::print!("\r\n")
-    };
-    ($s:literal) => {
-        {
-            $crate::print!($s);
-            $crate::print!("\r\n");
-        }
+        $crate::cpuid::dos::_print_str("\r\n")
     };
     ($($arg:tt)*) => {
         {
             $crate::print!($($arg)*);
-            $crate::print!("\r\n");
+            $crate::cpuid::dos::_print_str("\r\n");
         }
     };
 }
 
+/// Writes pre-formatted arguments to the DOS console.
+#[doc(hidden)]
+pub fn _print(args: core::fmt::Arguments) {
+    match args.as_str() {
+        Some(s) => _print_str(s),
+        None => {
+            let _ = DosWriter.write_fmt(args);
+        }
+    }
+}
+
 /// Writes a string to the DOS console character by character.
 pub fn _print_str(s: &str) {
     for &b in s.as_bytes() {
@@ -101,6 +108,30 @@ fn printc(ch: u8) {
     }
 }
 
+/// `memcpy` using `rep movsb`, replacing the byte loop from `compiler_builtins`.
+///
+/// # Safety
+///
+/// Same requirements as the C `memcpy`.
+#[cfg(feature = "dos-build")]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn memcpy(dst: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    unsafe { super::mem::copy_forward(dst, src, n) };
+    dst
+}
+
+/// `memmove` using `rep movsb`, replacing the byte loop from `compiler_builtins`.
+///
+/// # Safety
+///
+/// Same requirements as the C `memmove`.
+#[cfg(feature = "dos-build")]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn memmove(dst: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    unsafe { super::mem::copy(dst, src, n) };
+    dst
+}
+
 /// Exits the program and returns control to DOS using INT 21h, AH=4Ch.
 pub fn exit(code: u8) -> ! {
     // Exit to DOS via INT 21h, AH=4Ch
@@ -153,6 +184,34 @@ pub fn peek_u16(seg: u16, off: u16) -> u16 {
     val
 }
 
+/// Writes a byte to an I/O port.
+#[inline(never)]
+pub fn outb(port: u16, val: u8) {
+    unsafe {
+        asm!(
+            "out dx, al",
+            in("dx") port,
+            in("al") val,
+            options(nomem, nostack, preserves_flags)
+        );
+    }
+}
+
+/// Reads a byte from an I/O port.
+#[inline(never)]
+pub fn inb(port: u16) -> u8 {
+    let val: u8;
+    unsafe {
+        asm!(
+            "in al, dx",
+            in("dx") port,
+            out("al") val,
+            options(nomem, nostack, preserves_flags)
+        );
+    }
+    val
+}
+
 impl Speed {
     #[inline(never)]
     fn measure_frequency_tsc(t1: u16) -> u32 {
@@ -1,6 +1,6 @@
 use super::*;
 use super::{CENTAUR_LEAF_0, EXT_LEAF_0, TRANSMETA_LEAF_0, VENDOR_AMD};
-use crate::common::TDetect;
+use crate::common::{TDetect, fmt_hex};
 use crate::cpuid;
 use core::fmt::Write;
 
@@ -18,8 +18,14 @@ fn dump_leaf(f: &mut impl Write, leaf: u32, sub_leaf: u32, indent: usize) {
     let prefix = repeat_spaces(indent);
     let _ = writeln!(
         f,
-        "{}0x{:08X} 0x{:02X}: eax=0x{:08X} ebx=0x{:08X} ecx=0x{:08X} edx=0x{:08X}",
-        prefix, leaf, sub_leaf, result.eax, result.ebx, result.ecx, result.edx
+        "{}0x{} 0x{}: eax=0x{} ebx=0x{} ecx=0x{} edx=0x{}",
+        prefix,
+        fmt_hex(leaf, 8),
+        fmt_hex(sub_leaf, 2),
+        fmt_hex(result.eax, 8),
+        fmt_hex(result.ebx, 8),
+        fmt_hex(result.ecx, 8),
+        fmt_hex(result.edx, 8)
     );
 }
 
@@ -39,7 +45,7 @@ fn dump_leaf_maybe_subleaves(f: &mut impl Write, leaf: u32, indent: usize) {
                 sub_leaf += 1;
             }
         }
-        LEAF_7 | LEAF_14 | LEAF_17 => {
+        LEAF_7 | LEAF_14 | LEAF_17 | LEAF_24 => {
             let res = x86_cpuid_count(leaf, 0);
             let max_subleaf = res.eax;
             for sub_leaf in 0..=max_subleaf {
@@ -90,7 +96,8 @@ pub fn dump_cpu(f: &mut impl Write, cpu_idx: usize) {
 
     let _ = writeln!(f, "CPU {}:", cpu_idx);
 
-    let max_leaf = max_leaf();
+    // Skip any trailing leaves that are all zeros
+    let max_leaf = effective_max_leaf();
     for leaf in 0..=max_leaf {
         dump_leaf_maybe_subleaves(f, leaf, 4);
     }
@@ -102,7 +109,7 @@ pub fn dump_cpu(f: &mut impl Write, cpu_idx: usize) {
         }
     }
 
-    let max_ext_leaf = max_extended_leaf();
+    let max_ext_leaf = effective_max_extended_leaf();
     for leaf in EXT_LEAF_0..=max_ext_leaf {
         dump_leaf_maybe_subleaves(f, leaf, 4);
     }
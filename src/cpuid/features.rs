@@ -1,13 +1,14 @@
-use super::CpuBrand;
-use super::constants::{EXT_LEAF_1, LEAF_1, LEAF_7};
-use super::fns::{is_amd, is_cyrix, is_valid_leaf, x86_cpuid};
+use super::constants::{
+    EXT_LEAF_1, EXT_LEAF_8, LEAF_1, LEAF_6, LEAF_7, LEAF_14, XCR0_AMX, XCR0_AVX, XCR0_AVX512,
+};
+use super::fns::{is_amd_or_hygon, is_cyrix, is_valid_leaf, x86_cpuid, x86_cpuid_count};
 use alloc::collections::BTreeMap;
 use alloc::string::String;
 use alloc::vec::Vec;
 
 /// CPUID register selector for feature bit checking.
-#[allow(unused)]
-pub(crate) enum Reg {
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Reg {
     Eax,
     Ebx,
     Ecx,
@@ -16,18 +17,34 @@ pub(crate) enum Reg {
 
 /// Checks if a specific feature bit is set in the given CPUID leaf.
 pub(crate) fn has_feature(leaf: u32, register: Reg, bit: u32) -> bool {
+    has_subleaf_feature(leaf, 0, register, bit)
+}
+
+/// Checks if a specific feature bit is set in the given CPUID leaf and sub-leaf.
+pub(crate) fn has_subleaf_feature(leaf: u32, sub_leaf: u32, register: Reg, bit: u32) -> bool {
     if !is_valid_leaf(leaf) {
         return false;
     }
 
+    let res = x86_cpuid_count(leaf, sub_leaf);
+
     match register {
-        Reg::Eax => (x86_cpuid(leaf).eax & (1 << bit)) != 0,
-        Reg::Ebx => (x86_cpuid(leaf).ebx & (1 << bit)) != 0,
-        Reg::Ecx => (x86_cpuid(leaf).ecx & (1 << bit)) != 0,
-        Reg::Edx => (x86_cpuid(leaf).edx & (1 << bit)) != 0,
+        Reg::Eax => (res.eax & (1 << bit)) != 0,
+        Reg::Ebx => (res.ebx & (1 << bit)) != 0,
+        Reg::Ecx => (res.ecx & (1 << bit)) != 0,
+        Reg::Edx => (res.edx & (1 << bit)) != 0,
     }
 }
 
+/// Checks a feature bit in leaf 7, sub-leaf 1, if the CPU reports that sub-leaf.
+fn has_leaf_7_1_feature(register: Reg, bit: u32) -> bool {
+    if !is_valid_leaf(LEAF_7) || x86_cpuid(LEAF_7).eax < 1 {
+        return false;
+    }
+
+    has_subleaf_feature(LEAF_7, 1, register, bit)
+}
+
 // ------------------------------------------------------------------------
 // ! Leaf 0000_0001h
 // ------------------------------------------------------------------------
@@ -43,12 +60,43 @@ pub fn has_fpu() -> bool {
     has_feature(LEAF_1, Reg::Edx, 0)
 }
 
+/// Returns true if the CPU supports Virtual-8086 Mode Enhancements (VME).
+#[must_use]
+pub fn has_vme() -> bool {
+    has_feature(LEAF_1, Reg::Edx, 1)
+}
+
+/// Returns true if the CPU supports Debugging Extensions (DE), for I/O
+/// breakpoints.
+#[must_use]
+pub fn has_de() -> bool {
+    has_feature(LEAF_1, Reg::Edx, 2)
+}
+
+/// Returns true if the CPU supports Page Size Extensions (PSE), for 4MB pages.
+#[must_use]
+pub fn has_pse() -> bool {
+    has_feature(LEAF_1, Reg::Edx, 3)
+}
+
 /// Returns true if the CPU has a Time Stamp Counter (TSC).
 #[must_use]
 pub fn has_tsc() -> bool {
     has_feature(LEAF_1, Reg::Edx, 4)
 }
 
+/// Returns true if the CPU supports the RDMSR and WRMSR instructions.
+#[must_use]
+pub fn has_msr() -> bool {
+    has_feature(LEAF_1, Reg::Edx, 5)
+}
+
+/// Returns true if the CPU supports Physical Address Extension (PAE).
+#[must_use]
+pub fn has_pae() -> bool {
+    has_feature(LEAF_1, Reg::Edx, 6)
+}
+
 /// Returns true if the CPU supports CMPXCHG8B instruction.
 #[must_use]
 pub fn has_cx8() -> bool {
@@ -61,6 +109,18 @@ pub fn has_cmov() -> bool {
     has_feature(LEAF_1, Reg::Edx, 15)
 }
 
+/// Returns true if the CPU supports 36-bit page size extensions (PSE-36).
+#[must_use]
+pub fn has_pse36() -> bool {
+    has_feature(LEAF_1, Reg::Edx, 17)
+}
+
+/// Returns true if the CPU supports the CLFLUSH instruction.
+#[must_use]
+pub fn has_clflush() -> bool {
+    has_feature(LEAF_1, Reg::Edx, 19)
+}
+
 /// Returns true if the CPU supports MMX instructions.
 #[must_use]
 pub fn has_mmx() -> bool {
@@ -109,6 +169,13 @@ pub fn has_cx16() -> bool {
     has_feature(LEAF_1, Reg::Ecx, 13)
 }
 
+/// Returns true if the CPU supports Direct Cache Access, for prefetching
+/// data from memory mapped devices.
+#[must_use]
+pub fn has_dca() -> bool {
+    has_feature(LEAF_1, Reg::Ecx, 18)
+}
+
 /// Returns true if the CPU supports SSE4.1 instructions.
 #[must_use]
 pub fn has_sse41() -> bool {
@@ -121,6 +188,7 @@ pub fn has_sse42() -> bool {
     has_feature(LEAF_1, Reg::Ecx, 20)
 }
 
+/// Returns true if the CPU supports x2APIC mode.
 #[must_use]
 pub fn has_x2apic() -> bool {
     has_feature(LEAF_1, Reg::Ecx, 21)
@@ -132,6 +200,25 @@ pub fn has_popcnt() -> bool {
     has_feature(LEAF_1, Reg::Ecx, 23)
 }
 
+/// Returns true if the local APIC timer supports TSC-deadline mode.
+#[must_use]
+pub fn has_tsc_deadline() -> bool {
+    has_feature(LEAF_1, Reg::Ecx, 24)
+}
+
+/// Returns true if the CPU supports the XSAVE family of instructions.
+#[must_use]
+pub fn has_xsave() -> bool {
+    has_feature(LEAF_1, Reg::Ecx, 26)
+}
+
+/// Returns true if the OS has enabled XSAVE (CR4.OSXSAVE is set), so XGETBV
+/// can read which state components it saves. See [`xcr0`](super::xcr0).
+#[must_use]
+pub fn has_osxsave() -> bool {
+    has_feature(LEAF_1, Reg::Ecx, 27)
+}
+
 /// Returns true if the CPU supports AVX instructions.
 #[must_use]
 pub fn has_avx() -> bool {
@@ -156,6 +243,7 @@ pub fn is_hypervisor_guest() -> bool {
     has_feature(LEAF_1, Reg::Ecx, 31)
 }
 
+/// Returns true if the CPU has an on-chip local APIC.
 #[must_use]
 pub fn has_apic() -> bool {
     has_feature(LEAF_1, Reg::Edx, 9)
@@ -165,6 +253,13 @@ pub fn has_apic() -> bool {
 // ! Leaf 0000_0007h - Extended feature flags
 // ----------------------------------------------------------------------------
 
+/// Returns true if the CPU supports the RDFSBASE/RDGSBASE/WRFSBASE/WRGSBASE
+/// instructions (FSGSBASE).
+#[must_use]
+pub fn has_fsgsbase() -> bool {
+    has_feature(LEAF_7, Reg::Ebx, 0)
+}
+
 /// Returns true if the CPU supports BMI1 (Bit Manipulation Instructions).
 #[must_use]
 pub fn has_bmi1() -> bool {
@@ -183,6 +278,60 @@ pub fn has_bmi2() -> bool {
     has_feature(LEAF_7, Reg::Ebx, 8)
 }
 
+/// Returns true if the CPU supports Enhanced REP MOVSB/STOSB (ERMS),
+/// making `rep movsb` a fast path for memcpy and memset.
+#[must_use]
+pub fn has_erms() -> bool {
+    has_feature(LEAF_7, Reg::Ebx, 9)
+}
+
+/// Returns true if the CPU supports Hardware Lock Elision (HLE), the
+/// prefix-based half of TSX.
+#[must_use]
+pub fn has_hle() -> bool {
+    has_feature(LEAF_7, Reg::Ebx, 4)
+}
+
+/// Returns true if the CPU advertises Restricted Transactional Memory (RTM).
+///
+/// Microcode updates have disabled TSX on many CPUs that still set this bit,
+/// so see [`has_tsx`] for whether it's usable.
+#[must_use]
+pub fn has_rtm() -> bool {
+    has_feature(LEAF_7, Reg::Ebx, 11)
+}
+
+/// Returns true if the CPU supports Resource Director Technology monitoring
+/// (RDT-M), described by leaf 0x0F.
+#[must_use]
+pub fn has_rdt_m() -> bool {
+    has_feature(LEAF_7, Reg::Ebx, 12)
+}
+
+/// Returns true if the CPU supports Memory Protection Extensions (MPX).
+///
+/// MPX is deprecated: compilers and Linux have dropped support, and Intel
+/// removed it from CPUs from Ice Lake on.
+#[must_use]
+pub fn has_mpx() -> bool {
+    has_feature(LEAF_7, Reg::Ebx, 14)
+}
+
+/// Returns true if the CPU supports Resource Director Technology allocation
+/// (RDT-A), described by leaf 0x10.
+#[must_use]
+pub fn has_rdt_a() -> bool {
+    has_feature(LEAF_7, Reg::Ebx, 15)
+}
+
+/// Returns true if the CPU no longer saves the FPU CS and DS values.
+///
+/// This is informational only, there is no instruction to check for.
+#[must_use]
+pub fn has_fpu_csds_deprecated() -> bool {
+    has_feature(LEAF_7, Reg::Ebx, 13)
+}
+
 /// Returns true if the CPU supports AVX-512 Foundation instructions.
 #[must_use]
 pub fn has_avx512_f() -> bool {
@@ -206,6 +355,31 @@ pub fn has_avx512_ifma() -> bool {
     has_feature(LEAF_7, Reg::Ebx, 21)
 }
 
+/// Returns true if the CPU supports the CLFLUSHOPT instruction.
+#[must_use]
+pub fn has_clflushopt() -> bool {
+    has_feature(LEAF_7, Reg::Ebx, 23)
+}
+
+/// Returns true if the CPU supports the CLWB (Cache Line Write Back) instruction.
+#[must_use]
+pub fn has_clwb() -> bool {
+    has_feature(LEAF_7, Reg::Ebx, 24)
+}
+
+/// Returns true if the CPU supports Intel Processor Trace.
+#[must_use]
+pub fn has_pt() -> bool {
+    has_feature(LEAF_7, Reg::Ebx, 25)
+}
+
+/// Returns true if the CPU supports the PTWRITE instruction, to write
+/// values into the Processor Trace output.
+#[must_use]
+pub fn has_ptwrite() -> bool {
+    has_pt() && has_feature(LEAF_14, Reg::Ebx, 4)
+}
+
 /// Returns true if the CPU supports AVX-512 PF instructions (Xeon Phi).
 #[must_use]
 pub fn has_avx512_pf() -> bool {
@@ -241,6 +415,29 @@ pub fn has_avx512_vl() -> bool {
     has_feature(LEAF_7, Reg::Ebx, 31)
 }
 
+/// Returns true if the CPU supports Memory Protection Keys for user-mode pages (PKU).
+#[must_use]
+pub fn has_pku() -> bool {
+    has_feature(LEAF_7, Reg::Ecx, 3)
+}
+
+/// Returns true if the OS has enabled protection keys (CR4.PKE is set).
+///
+/// This reflects OS support, distinct from hardware support in [`has_pku`].
+#[must_use]
+pub fn has_ospke() -> bool {
+    has_feature(LEAF_7, Reg::Ecx, 4)
+}
+
+/// Returns true if the CPU supports CET shadow stacks.
+///
+/// This is hardware support only; shadow stacks also have to be enabled
+/// by the OS (CR4.CET), like protection keys with [`has_ospke`].
+#[must_use]
+pub fn has_cet_ss() -> bool {
+    has_feature(LEAF_7, Reg::Ecx, 7)
+}
+
 /// Vector version of AES instruction
 #[must_use]
 pub fn has_vaes() -> bool {
@@ -253,9 +450,9 @@ pub fn has_vpclmulqdq() -> bool {
     has_feature(LEAF_7, Reg::Ecx, 10)
 }
 
-/// Returns true if the CPU supports AVX-VNNI instructions.
+/// Returns true if the CPU supports AVX-512 VNNI instructions.
 #[must_use]
-pub fn has_avx_vnni() -> bool {
+pub fn has_avx512_vnni() -> bool {
     has_feature(LEAF_7, Reg::Ecx, 11)
 }
 
@@ -265,18 +462,44 @@ pub fn has_avx512_bitalg() -> bool {
     has_feature(LEAF_7, Reg::Ecx, 12)
 }
 
+/// Returns true if the CPU supports the user-mode wait instructions,
+/// UMONITOR, UMWAIT and TPAUSE.
+#[must_use]
+pub fn has_waitpkg() -> bool {
+    has_feature(LEAF_7, Reg::Ecx, 5)
+}
+
+/// Returns true if the CPU supports Total Memory Encryption (Intel TME).
+#[must_use]
+pub fn has_tme() -> bool {
+    has_feature(LEAF_7, Reg::Ecx, 13)
+}
+
 /// Returns true if the CPU supports AVX-512 VPOPCNTDQ instructions.
 #[must_use]
 pub fn has_avx512_vpopcntdq() -> bool {
     has_feature(LEAF_7, Reg::Ecx, 14)
 }
 
+/// Returns true if the CPU supports 57-bit linear addresses (5-level paging).
+#[must_use]
+pub fn has_la57() -> bool {
+    has_feature(LEAF_7, Reg::Ecx, 16)
+}
+
 /// Returns true if the CPU supports AVX-512 4VNNIW instructions (Xeon Phi).
 #[must_use]
 pub fn has_avx512_4vnniw() -> bool {
     has_feature(LEAF_7, Reg::Edx, 2)
 }
 
+/// Returns true if the CPU supports Fast Short REP MOV (FSRM), for
+/// fast `rep movsb` on short copies.
+#[must_use]
+pub fn has_fsrm() -> bool {
+    has_feature(LEAF_7, Reg::Edx, 4)
+}
+
 /// Returns true if the CPU supports AVX-512 4FMAPS instructions (Xeon Phi).
 #[must_use]
 pub fn has_avx512_4fmaps() -> bool {
@@ -289,6 +512,138 @@ pub fn has_avx512_vp2intersect() -> bool {
     has_feature(LEAF_7, Reg::Edx, 8)
 }
 
+/// Returns true if RTM transactions always abort, because TSX was disabled
+/// by a microcode update.
+#[must_use]
+pub fn has_rtm_always_abort() -> bool {
+    has_feature(LEAF_7, Reg::Edx, 11)
+}
+
+/// Returns true if RTM is advertised, and not disabled by microcode.
+#[must_use]
+pub fn has_tsx() -> bool {
+    has_rtm() && !has_rtm_always_abort()
+}
+
+/// Returns true if the CPU has the `IA32_APERF` and `IA32_MPERF` effective
+/// frequency counters.
+#[must_use]
+pub fn has_aperf_mperf() -> bool {
+    has_feature(LEAF_6, Reg::Ecx, 0)
+}
+
+/// Returns true if the CPU has more than one type of core (Intel hybrid).
+#[must_use]
+pub fn has_hybrid() -> bool {
+    has_feature(LEAF_7, Reg::Edx, 15)
+}
+
+/// Returns true if the CPU supports the SERIALIZE instruction.
+/// See [`serialize`](super::serialize).
+#[must_use]
+pub fn has_serialize() -> bool {
+    has_feature(LEAF_7, Reg::Edx, 14)
+}
+
+/// Returns true if the CPU supports CET indirect branch tracking.
+///
+/// As with [`has_cet_ss`], the OS still has to enable it.
+#[must_use]
+pub fn has_cet_ibt() -> bool {
+    has_feature(LEAF_7, Reg::Edx, 20)
+}
+
+/// Returns true if the CPU supports AMX BF16 tile instructions.
+#[must_use]
+pub fn has_amx_bf16() -> bool {
+    has_feature(LEAF_7, Reg::Edx, 22)
+}
+
+/// Returns true if the CPU supports AVX-512 FP16 instructions.
+#[must_use]
+pub fn has_avx512_fp16() -> bool {
+    has_feature(LEAF_7, Reg::Edx, 23)
+}
+
+/// Returns true if the CPU supports AMX tile architecture (Advanced Matrix Extensions).
+#[must_use]
+pub fn has_amx_tile() -> bool {
+    has_feature(LEAF_7, Reg::Edx, 24)
+}
+
+/// Returns true if the CPU supports AMX INT8 tile instructions.
+#[must_use]
+pub fn has_amx_int8() -> bool {
+    has_feature(LEAF_7, Reg::Edx, 25)
+}
+
+// ----------------------------------------------------------------------------
+// ! Leaf 0000_0007h, Sub-leaf 1 - Extended feature flags
+// ----------------------------------------------------------------------------
+
+/// Returns true if the CPU supports CMPccXADD instructions (Sapphire Rapids and newer).
+#[must_use]
+pub fn has_cmpccxadd() -> bool {
+    has_leaf_7_1_feature(Reg::Eax, 7)
+}
+
+/// Returns true if the CPU supports the HRESET instruction, for resetting
+/// the Thread Director history of hybrid CPUs. See [`history_reset`](super::history_reset).
+#[must_use]
+pub fn has_hreset() -> bool {
+    has_leaf_7_1_feature(Reg::Eax, 22)
+}
+
+/// Returns true if the CPU supports WRMSRNS, a non-serializing WRMSR.
+#[must_use]
+pub fn has_wrmsrns() -> bool {
+    has_leaf_7_1_feature(Reg::Eax, 19)
+}
+
+/// Returns true if the CPU supports LAM (Linear Address Masking).
+///
+/// With LAM, the CPU ignores the upper bits of user pointers, so runtimes
+/// can keep tags in them without masking before every dereference. The OS
+/// has to enable it per process, such as with `ARCH_ENABLE_TAGGED_ADDR` on
+/// Linux.
+#[must_use]
+pub fn has_lam() -> bool {
+    has_leaf_7_1_feature(Reg::Eax, 26)
+}
+
+/// Returns true if the CPU supports the RDMSRLIST and WRMSRLIST instructions,
+/// for reading and writing several MSRs at once.
+#[must_use]
+pub fn has_msrlist() -> bool {
+    has_leaf_7_1_feature(Reg::Eax, 27)
+}
+
+/// Returns true if the CPU supports the CLZERO instruction, to zero a
+/// cache line. This is AMD only.
+#[must_use]
+pub fn has_clzero() -> bool {
+    is_amd_or_hygon() && has_feature(EXT_LEAF_8, Reg::Ebx, 0)
+}
+
+/// Returns true if the CPU supports AVX10, the converged AVX-512 instruction
+/// set. See [`avx10`](super::avx10) for the version and vector lengths.
+#[must_use]
+pub fn has_avx10() -> bool {
+    has_leaf_7_1_feature(Reg::Edx, 19)
+}
+
+/// Returns true if the CPU supports VEX-encoded AVX-VNNI instructions.
+#[must_use]
+pub fn has_avx_vnni() -> bool {
+    has_leaf_7_1_feature(Reg::Eax, 4)
+}
+
+/// Returns true if the CPU supports VEX-encoded AVX-IFMA instructions.
+#[must_use]
+pub fn has_avx_ifma() -> bool {
+    has_leaf_7_1_feature(Reg::Eax, 23)
+}
+
 // ----------------------------------------------------------------------------
 // ! Leaf 8000_0001h - Extended features
 // ----------------------------------------------------------------------------
@@ -326,10 +681,32 @@ pub fn has_virtualization() -> bool {
     has_vtx() || has_amdv()
 }
 
+/// Returns true if the CPU supports LAHF and SAHF in 64-bit mode.
+///
+/// Early 64-bit CPUs lack them, so they're part of x86-64-v2.
+#[must_use]
+pub fn has_lahf_lm() -> bool {
+    has_feature(EXT_LEAF_1, Reg::Ecx, 0)
+}
+
+/// Returns true if the CPU has AMD's extended APIC register space.
+#[must_use]
+pub fn has_ext_apic_space() -> bool {
+    has_feature(EXT_LEAF_1, Reg::Ecx, 3)
+}
+
+/// Returns true if the CPU supports the LZCNT instruction (ABM on AMD).
+///
+/// Checks ECX bit 5 in extended leaf 0x80000001.
+#[must_use]
+pub fn has_lzcnt() -> bool {
+    has_feature(EXT_LEAF_1, Reg::Ecx, 5)
+}
+
 /// Returns true if the CPU supports SSE4A instructions (AMD-specific).
 #[must_use]
 pub fn has_sse4a() -> bool {
-    if CpuBrand::detect() != CpuBrand::AMD {
+    if !is_amd_or_hygon() {
         return false;
     }
 
@@ -341,9 +718,12 @@ pub fn has_3dnow_prefetch() -> bool {
     has_feature(EXT_LEAF_1, Reg::Ecx, 8)
 }
 
+/// Returns true if the CPU supports AMD's MMX extensions, added with the
+/// K7, or Cyrix's extended MMX, which has its own flag.
+#[doc(alias = "has_mmx_ext")]
 #[must_use]
 pub fn has_mmx_plus() -> bool {
-    if is_amd() {
+    if is_amd_or_hygon() {
         has_feature(EXT_LEAF_1, Reg::Edx, 22)
     } else if is_cyrix() {
         has_feature(EXT_LEAF_1, Reg::Edx, 24)
@@ -352,13 +732,22 @@ pub fn has_mmx_plus() -> bool {
     }
 }
 
+/// Returns true if the CPU supports the RDTSCP instruction, which also
+/// returns the value of `IA32_TSC_AUX`.
+#[must_use]
+pub fn has_rdtscp() -> bool {
+    has_feature(EXT_LEAF_1, Reg::Edx, 27)
+}
+
 /// Returns true if the CPU supports AMD64 (x86-64) instructions.
 #[must_use]
 pub fn has_amd64() -> bool {
     has_feature(EXT_LEAF_1, Reg::Edx, 29)
 }
 
-/// Returns true if the CPU supports 3DNow!+ instructions.
+/// Returns true if the CPU supports 3DNow!+ instructions, AMD's 3DNow!
+/// extensions, also known as Enhanced 3DNow!.
+#[doc(alias = "has_3dnow_ext")]
 #[must_use]
 pub fn has_3dnow_plus() -> bool {
     has_feature(EXT_LEAF_1, Reg::Edx, 30)
@@ -375,153 +764,1230 @@ pub fn has_3dnow() -> bool {
 // ----------------------------------------------------------------------------
 
 pub type FeatureFn = fn() -> bool;
-type FeatureMap<'a> = &'a [(&'static str, FeatureFn)];
-
+/// Get the list of detected features, in the same order as the full list.
+///
+/// Only the older flags are listed, in one group, to fit on the screen.
 #[cfg(dos)]
 pub fn get_feature_list() -> BTreeMap<&'static str, String> {
+    const FEATURES: [&str; 20] = [
+        "FPU", "TSC", "CX8", "APIC", "CMOV", "MMX", "CX16", "MMX+", "AMD64", "3DNow!+", "3DNow!",
+        "SSE", "SSE2", "SSE3", "SSSE3", "SSE4.1", "SSE4.2", "SSE4A", "AES", "SHA",
+    ];
+
     let mut map = BTreeMap::new();
+    let features: Vec<&'static str> = FEATURE_SPECS
+        .iter()
+        .filter(|spec| FEATURES.contains(&spec.name) && spec.is_set())
+        .map(|spec| spec.name)
+        .collect();
 
-    const FEATURES: FeatureMap = &[
-        ("FPU", has_fpu),
-        ("TSC", has_tsc),
-        ("CMPXCHG8B", has_cx8),
-        ("CMPXCHG16B", has_cx16),
-        ("CMOV", has_cmov),
-        ("MMX", has_mmx),
-        ("MMX+", has_mmx_plus),
-        ("3DNow!", has_3dnow),
-        ("3DNow!+", has_3dnow_plus),
-        ("APIC", has_apic),
-        ("AMD64", has_amd64),
-        ("SSE", has_sse),
-        ("SSE2", has_sse2),
-        ("SSE3", has_sse3),
-        ("SSE4A", has_sse4a),
-        ("SSE4.1", has_sse41),
-        ("SSE4.2", has_sse42),
-        ("SSSE3", has_ssse3),
-        ("AES", has_aes),
-        ("SHA", has_sha),
-    ];
+    if !features.is_empty() {
+        map.insert("Base", features.join(" "));
+    }
+
+    map
+}
+
+/// How to spell a feature name, for [`FeatureSpec::name_styled`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum FeatureNameStyle {
+    /// The name in the feature list, such as "F" in the AVX512 group
+    #[default]
+    Short,
+    /// The flag's name in the Intel or AMD CPUID documentation, such as
+    /// "AVX512F", which doesn't need the group to make sense
+    Canonical,
+    /// The flag's name in Linux's `/proc/cpuinfo`, such as "avx512f"
+    Linux,
+}
+
+/// A feature flag, as a single CPUID bit.
+#[derive(Debug, Copy, Clone)]
+pub struct FeatureSpec {
+    /// The feature list group
+    pub group: &'static str,
+    /// The display name
+    pub name: &'static str,
+    pub leaf: u32,
+    pub sub_leaf: u32,
+    pub reg: Reg,
+    pub bit: u32,
+    /// Limits the flag to a vendor, for bits that others use for something else
+    pub vendor: Option<FeatureFn>,
+    /// Another check the feature needs to be usable, besides its own bit
+    pub requires: Option<FeatureFn>,
+    /// What the OS has to enable before the feature can be used
+    pub os: Option<OsSupport>,
+    /// The name in the Intel or AMD documentation, if it's not the display name
+    pub canonical: Option<&'static str>,
+    /// The `/proc/cpuinfo` flag, if Linux shows one
+    pub linux: Option<&'static str>,
+}
+
+/// OS support a feature needs, besides the hardware.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OsSupport {
+    /// These XCR0 state components, for register state the OS must save
+    Xcr0(u64),
+    /// Protection keys, enabled with CR4.PKE
+    Pke,
+    /// The FS/GS base instructions, enabled with CR4.FSGSBASE
+    FsGsBase,
+    /// Enabled in a way that can't be checked from here, such as CET in
+    /// CR4 and the supervisor state MSRs
+    Unchecked,
+}
 
-    let mut features: Vec<&'static str> = Vec::with_capacity(FEATURES.len());
+/// Whether a detected feature can be used, for [`feature_status`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FeatureStatus {
+    /// Supported by the hardware, and enabled by the OS if it needs to be
+    Usable,
+    /// Supported by the hardware, but not enabled by the OS
+    OsDisabled,
+    /// Supported by the hardware, but the OS support can't be checked
+    OsUnknown,
+}
 
-    for (name, check) in FEATURES {
-        if check() {
-            features.push(name);
+impl FeatureSpec {
+    #[must_use]
+    pub const fn new(
+        group: &'static str,
+        name: &'static str,
+        leaf: u32,
+        sub_leaf: u32,
+        reg: Reg,
+        bit: u32,
+    ) -> Self {
+        Self {
+            group,
+            name,
+            leaf,
+            sub_leaf,
+            reg,
+            bit,
+            vendor: None,
+            requires: None,
+            os: None,
+            canonical: None,
+            linux: None,
         }
     }
 
-    if !features.is_empty() {
-        map.insert("Base", features.join(" "));
+    /// Only reports the flag when the given vendor check passes
+    #[must_use]
+    pub const fn vendor(self, check: FeatureFn) -> Self {
+        Self {
+            vendor: Some(check),
+            ..self
+        }
     }
 
-    map
+    /// Only reports the flag when the given check also passes
+    #[must_use]
+    pub const fn requires(self, check: FeatureFn) -> Self {
+        Self {
+            requires: Some(check),
+            ..self
+        }
+    }
+
+    /// Marks the feature as needing OS support
+    #[must_use]
+    pub const fn os(self, support: OsSupport) -> Self {
+        Self {
+            os: Some(support),
+            ..self
+        }
+    }
+
+    /// Sets the documented name, for [`FeatureNameStyle::Canonical`]
+    #[must_use]
+    pub const fn canonical(self, name: &'static str) -> Self {
+        Self {
+            canonical: Some(name),
+            ..self
+        }
+    }
+
+    /// Sets the `/proc/cpuinfo` flag, for [`FeatureNameStyle::Linux`]
+    #[must_use]
+    pub const fn linux(self, name: &'static str) -> Self {
+        Self {
+            linux: Some(name),
+            ..self
+        }
+    }
+
+    /// Returns whether the OS has enabled the feature, assuming the
+    /// hardware supports it.
+    #[must_use]
+    pub fn os_status(&self) -> FeatureStatus {
+        match self.os {
+            None => FeatureStatus::Usable,
+            Some(OsSupport::Xcr0(mask)) => {
+                if !has_osxsave() {
+                    return FeatureStatus::OsDisabled;
+                }
+
+                match super::xcr0() {
+                    Some(xcr0) if xcr0 & mask == mask => FeatureStatus::Usable,
+                    Some(_) => FeatureStatus::OsDisabled,
+                    None => FeatureStatus::OsUnknown,
+                }
+            }
+            Some(OsSupport::Pke) if has_ospke() => FeatureStatus::Usable,
+            Some(OsSupport::Pke) => FeatureStatus::OsDisabled,
+            Some(OsSupport::FsGsBase) => match super::fsgsbase_usable() {
+                Some(true) => FeatureStatus::Usable,
+                Some(false) => FeatureStatus::OsDisabled,
+                None => FeatureStatus::OsUnknown,
+            },
+            Some(OsSupport::Unchecked) => FeatureStatus::OsUnknown,
+        }
+    }
+
+    /// Returns true if the flag is set for the current CPU.
+    ///
+    /// Sub-leaves above 0 are checked against the highest sub-leaf the
+    /// leaf reports in EAX, as with leaf 7.
+    #[must_use]
+    pub fn is_set(&self) -> bool {
+        if let Some(check) = self.vendor
+            && !check()
+        {
+            return false;
+        }
+
+        if let Some(check) = self.requires
+            && !check()
+        {
+            return false;
+        }
+
+        if self.sub_leaf > 0
+            && (!is_valid_leaf(self.leaf) || x86_cpuid(self.leaf).eax < self.sub_leaf)
+        {
+            return false;
+        }
+
+        has_subleaf_feature(self.leaf, self.sub_leaf, self.reg, self.bit)
+    }
+
+    /// Returns the name of the feature in the given style.
+    ///
+    /// Falls back to the short name when the style has no other name for
+    /// the feature, such as a flag that `/proc/cpuinfo` doesn't show.
+    #[must_use]
+    pub fn name_styled(&self, style: FeatureNameStyle) -> &'static str {
+        let styled = match style {
+            FeatureNameStyle::Short => None,
+            FeatureNameStyle::Canonical => self.canonical,
+            FeatureNameStyle::Linux => self.linux,
+        };
+
+        styled.unwrap_or(self.name)
+    }
+
+    /// Finds the feature for a CPUID flag, from [`FEATURE_SPECS`], then
+    /// `UNLISTED_FEATURE_SPECS`, which the DOS build doesn't have.
+    ///
+    /// Bits that mean different things per vendor are resolved for the
+    /// current CPU. Returns `None` for bits that aren't in either table, or
+    /// that belong to another vendor.
+    #[must_use]
+    pub fn from_leaf_bit(leaf: u32, sub_leaf: u32, reg: Reg, bit: u32) -> Option<&'static Self> {
+        let specs = FEATURE_SPECS.iter();
+        #[cfg(not(dos))]
+        let specs = specs.chain(UNLISTED_FEATURE_SPECS.iter());
+
+        specs
+            .filter(|spec| {
+                (spec.leaf, spec.sub_leaf, spec.reg, spec.bit) == (leaf, sub_leaf, reg, bit)
+            })
+            .find(|spec| spec.vendor.is_none_or(|check| check()))
+    }
+}
+
+/// Feature flags for [`get_feature_list`], in display order.
+///
+/// Within a group, features are in a fixed order: by leaf, then sub-leaf,
+/// then register, then bit. Leaves 0x1 and 0x8000_0001 list EDX before ECX,
+/// since EDX has their older features; other leaves go from EAX to EDX.
+/// A new feature only needs a row here; the `has_*` functions are kept
+/// for direct checks.
+pub static FEATURE_SPECS: [FeatureSpec; 81] = [
+    FeatureSpec::new("Base", "FPU", LEAF_1, 0, Reg::Edx, 0).linux("fpu"),
+    FeatureSpec::new("Base", "TSC", LEAF_1, 0, Reg::Edx, 4).linux("tsc"),
+    FeatureSpec::new("Base", "CX8", LEAF_1, 0, Reg::Edx, 8).linux("cx8"),
+    FeatureSpec::new("Base", "APIC", LEAF_1, 0, Reg::Edx, 9).linux("apic"),
+    FeatureSpec::new("Base", "CMOV", LEAF_1, 0, Reg::Edx, 15).linux("cmov"),
+    FeatureSpec::new("Base", "MMX", LEAF_1, 0, Reg::Edx, 23).linux("mmx"),
+    FeatureSpec::new("Base", "HT", LEAF_1, 0, Reg::Edx, 28)
+        .canonical("HTT")
+        .linux("ht"),
+    FeatureSpec::new("Base", "CX16", LEAF_1, 0, Reg::Ecx, 13).linux("cx16"),
+    FeatureSpec::new("Base", "MMX+", EXT_LEAF_1, 0, Reg::Edx, 22)
+        .vendor(is_amd_or_hygon)
+        .canonical("MmxExt")
+        .linux("mmxext"),
+    FeatureSpec::new("Base", "MMX+", EXT_LEAF_1, 0, Reg::Edx, 24)
+        .vendor(is_cyrix)
+        .linux("cxmmx"),
+    FeatureSpec::new("Base", "AMD64", EXT_LEAF_1, 0, Reg::Edx, 29)
+        .canonical("LM")
+        .linux("lm"),
+    FeatureSpec::new("Base", "3DNow!+", EXT_LEAF_1, 0, Reg::Edx, 30)
+        .canonical("3DNowExt")
+        .linux("3dnowext"),
+    FeatureSpec::new("Base", "3DNow!", EXT_LEAF_1, 0, Reg::Edx, 31).linux("3dnow"),
+    FeatureSpec::new("Base", "LAHF-SAHF", EXT_LEAF_1, 0, Reg::Ecx, 0)
+        .canonical("LahfSahf")
+        .linux("lahf_lm"),
+    FeatureSpec::new("Base", "3DNow!-Prefetch", EXT_LEAF_1, 0, Reg::Ecx, 8)
+        .canonical("3DNowPrefetch")
+        .linux("3dnowprefetch"),
+    FeatureSpec::new("SSE", "SSE", LEAF_1, 0, Reg::Edx, 25).linux("sse"),
+    FeatureSpec::new("SSE", "SSE2", LEAF_1, 0, Reg::Edx, 26).linux("sse2"),
+    FeatureSpec::new("SSE", "SSE3", LEAF_1, 0, Reg::Ecx, 0).linux("pni"),
+    FeatureSpec::new("SSE", "SSSE3", LEAF_1, 0, Reg::Ecx, 9).linux("ssse3"),
+    FeatureSpec::new("SSE", "SSE4.1", LEAF_1, 0, Reg::Ecx, 19)
+        .canonical("SSE4_1")
+        .linux("sse4_1"),
+    FeatureSpec::new("SSE", "SSE4.2", LEAF_1, 0, Reg::Ecx, 20)
+        .canonical("SSE4_2")
+        .linux("sse4_2"),
+    FeatureSpec::new("SSE", "SSE4A", EXT_LEAF_1, 0, Reg::Ecx, 6)
+        .vendor(is_amd_or_hygon)
+        .linux("sse4a"),
+    FeatureSpec::new("AVX", "AVX", LEAF_1, 0, Reg::Ecx, 28)
+        .os(OsSupport::Xcr0(XCR0_AVX))
+        .linux("avx"),
+    FeatureSpec::new("AVX", "AVX2", LEAF_7, 0, Reg::Ebx, 5)
+        .os(OsSupport::Xcr0(XCR0_AVX))
+        .linux("avx2"),
+    FeatureSpec::new("AVX", "VPCLMULQDQ", LEAF_7, 0, Reg::Ecx, 10)
+        .os(OsSupport::Xcr0(XCR0_AVX))
+        .linux("vpclmulqdq"),
+    FeatureSpec::new("AVX", "AVX-VNNI", LEAF_7, 1, Reg::Eax, 4)
+        .os(OsSupport::Xcr0(XCR0_AVX))
+        .linux("avx_vnni"),
+    FeatureSpec::new("AVX", "AVX-IFMA", LEAF_7, 1, Reg::Eax, 23)
+        .os(OsSupport::Xcr0(XCR0_AVX))
+        .linux("avx_ifma"),
+    FeatureSpec::new("AVX512", "F", LEAF_7, 0, Reg::Ebx, 16)
+        .os(OsSupport::Xcr0(XCR0_AVX512))
+        .canonical("AVX512F")
+        .linux("avx512f"),
+    FeatureSpec::new("AVX512", "DQ", LEAF_7, 0, Reg::Ebx, 17)
+        .os(OsSupport::Xcr0(XCR0_AVX512))
+        .canonical("AVX512DQ")
+        .linux("avx512dq"),
+    FeatureSpec::new("AVX512", "IFMA", LEAF_7, 0, Reg::Ebx, 21)
+        .os(OsSupport::Xcr0(XCR0_AVX512))
+        .canonical("AVX512_IFMA")
+        .linux("avx512ifma"),
+    FeatureSpec::new("AVX512", "PF", LEAF_7, 0, Reg::Ebx, 26)
+        .os(OsSupport::Xcr0(XCR0_AVX512))
+        .canonical("AVX512PF")
+        .linux("avx512pf"),
+    FeatureSpec::new("AVX512", "ER", LEAF_7, 0, Reg::Ebx, 27)
+        .os(OsSupport::Xcr0(XCR0_AVX512))
+        .canonical("AVX512ER")
+        .linux("avx512er"),
+    FeatureSpec::new("AVX512", "CD", LEAF_7, 0, Reg::Ebx, 28)
+        .os(OsSupport::Xcr0(XCR0_AVX512))
+        .canonical("AVX512CD")
+        .linux("avx512cd"),
+    FeatureSpec::new("AVX512", "BW", LEAF_7, 0, Reg::Ebx, 30)
+        .os(OsSupport::Xcr0(XCR0_AVX512))
+        .canonical("AVX512BW")
+        .linux("avx512bw"),
+    FeatureSpec::new("AVX512", "VL", LEAF_7, 0, Reg::Ebx, 31)
+        .os(OsSupport::Xcr0(XCR0_AVX512))
+        .canonical("AVX512VL")
+        .linux("avx512vl"),
+    FeatureSpec::new("AVX512", "VBMI", LEAF_7, 0, Reg::Ecx, 1)
+        .os(OsSupport::Xcr0(XCR0_AVX512))
+        .canonical("AVX512_VBMI")
+        .linux("avx512vbmi"),
+    FeatureSpec::new("AVX512", "VBMI2", LEAF_7, 0, Reg::Ecx, 6)
+        .os(OsSupport::Xcr0(XCR0_AVX512))
+        .canonical("AVX512_VBMI2")
+        .linux("avx512_vbmi2"),
+    FeatureSpec::new("AVX512", "VNNI", LEAF_7, 0, Reg::Ecx, 11)
+        .os(OsSupport::Xcr0(XCR0_AVX512))
+        .canonical("AVX512_VNNI")
+        .linux("avx512_vnni"),
+    FeatureSpec::new("AVX512", "BITALG", LEAF_7, 0, Reg::Ecx, 12)
+        .os(OsSupport::Xcr0(XCR0_AVX512))
+        .canonical("AVX512_BITALG")
+        .linux("avx512_bitalg"),
+    FeatureSpec::new("AVX512", "VPOPCNTDQ", LEAF_7, 0, Reg::Ecx, 14)
+        .os(OsSupport::Xcr0(XCR0_AVX512))
+        .canonical("AVX512_VPOPCNTDQ")
+        .linux("avx512_vpopcntdq"),
+    FeatureSpec::new("AVX512", "4VNNIW", LEAF_7, 0, Reg::Edx, 2)
+        .os(OsSupport::Xcr0(XCR0_AVX512))
+        .canonical("AVX512_4VNNIW")
+        .linux("avx512_4vnniw"),
+    FeatureSpec::new("AVX512", "4FMAPS", LEAF_7, 0, Reg::Edx, 3)
+        .os(OsSupport::Xcr0(XCR0_AVX512))
+        .canonical("AVX512_4FMAPS")
+        .linux("avx512_4fmaps"),
+    FeatureSpec::new("AVX512", "VP2INTERSECT", LEAF_7, 0, Reg::Edx, 8)
+        .os(OsSupport::Xcr0(XCR0_AVX512))
+        .canonical("AVX512_VP2INTERSECT")
+        .linux("avx512_vp2intersect"),
+    FeatureSpec::new("AVX512", "FP16", LEAF_7, 0, Reg::Edx, 23)
+        .os(OsSupport::Xcr0(XCR0_AVX512))
+        .canonical("AVX512_FP16")
+        .linux("avx512_fp16"),
+    FeatureSpec::new("AMX", "BF16", LEAF_7, 0, Reg::Edx, 22)
+        .os(OsSupport::Xcr0(XCR0_AMX))
+        .canonical("AMX-BF16")
+        .linux("amx_bf16"),
+    FeatureSpec::new("AMX", "TILE", LEAF_7, 0, Reg::Edx, 24)
+        .os(OsSupport::Xcr0(XCR0_AMX))
+        .canonical("AMX-TILE")
+        .linux("amx_tile"),
+    FeatureSpec::new("AMX", "INT8", LEAF_7, 0, Reg::Edx, 25)
+        .os(OsSupport::Xcr0(XCR0_AMX))
+        .canonical("AMX-INT8")
+        .linux("amx_int8"),
+    FeatureSpec::new("Security", "VT-x", LEAF_1, 0, Reg::Ecx, 5)
+        .canonical("VMX")
+        .linux("vmx"),
+    FeatureSpec::new("Security", "AES", LEAF_1, 0, Reg::Ecx, 25).linux("aes"),
+    FeatureSpec::new("Security", "RDRAND", LEAF_1, 0, Reg::Ecx, 30).linux("rdrand"),
+    // Deprecated, but still present on Skylake-era CPUs
+    FeatureSpec::new("Security", "MPX", LEAF_7, 0, Reg::Ebx, 14).linux("mpx"),
+    FeatureSpec::new("Security", "RDSEED", LEAF_7, 0, Reg::Ebx, 18).linux("rdseed"),
+    FeatureSpec::new("Security", "SHA", LEAF_7, 0, Reg::Ebx, 29).linux("sha_ni"),
+    FeatureSpec::new("Security", "PKU", LEAF_7, 0, Reg::Ecx, 3)
+        .os(OsSupport::Pke)
+        .linux("pku"),
+    // Protection keys enabled by the OS, not just supported by the hardware
+    FeatureSpec::new("Security", "OSPKE", LEAF_7, 0, Reg::Ecx, 4).linux("ospke"),
+    FeatureSpec::new("Security", "CET-SS", LEAF_7, 0, Reg::Ecx, 7)
+        .os(OsSupport::Unchecked)
+        .canonical("CET_SS")
+        .linux("shstk"),
+    FeatureSpec::new("Security", "VAES", LEAF_7, 0, Reg::Ecx, 9)
+        .os(OsSupport::Xcr0(XCR0_AVX))
+        .linux("vaes"),
+    FeatureSpec::new("Security", "CET-IBT", LEAF_7, 0, Reg::Edx, 20)
+        .os(OsSupport::Unchecked)
+        .canonical("CET_IBT")
+        .linux("ibt"),
+    FeatureSpec::new("Security", "NX", EXT_LEAF_1, 0, Reg::Edx, 20).linux("nx"),
+    FeatureSpec::new("Security", "AMD-V", EXT_LEAF_1, 0, Reg::Ecx, 2)
+        .canonical("SVM")
+        .linux("svm"),
+    FeatureSpec::new("Math", "FMA", LEAF_1, 0, Reg::Ecx, 12)
+        .os(OsSupport::Xcr0(XCR0_AVX))
+        .linux("fma"),
+    FeatureSpec::new("Math", "F16C", LEAF_1, 0, Reg::Ecx, 29)
+        .os(OsSupport::Xcr0(XCR0_AVX))
+        .linux("f16c"),
+    FeatureSpec::new("Math", "BMI1", LEAF_7, 0, Reg::Ebx, 3).linux("bmi1"),
+    FeatureSpec::new("Math", "BMI2", LEAF_7, 0, Reg::Ebx, 8).linux("bmi2"),
+    FeatureSpec::new("Math", "LZCNT", EXT_LEAF_1, 0, Reg::Ecx, 5).linux("abm"),
+    FeatureSpec::new("Other", "x2apic", LEAF_1, 0, Reg::Ecx, 21)
+        .canonical("x2APIC")
+        .linux("x2apic"),
+    FeatureSpec::new("Other", "POPCNT", LEAF_1, 0, Reg::Ecx, 23).linux("popcnt"),
+    FeatureSpec::new("Other", "TSC-Deadline", LEAF_1, 0, Reg::Ecx, 24).linux("tsc_deadline_timer"),
+    FeatureSpec::new("Other", "FSGSBASE", LEAF_7, 0, Reg::Ebx, 0)
+        .os(OsSupport::FsGsBase)
+        .linux("fsgsbase"),
+    FeatureSpec::new("Other", "ERMS", LEAF_7, 0, Reg::Ebx, 9).linux("erms"),
+    // Only when not disabled by microcode
+    FeatureSpec::new("Other", "TSX", LEAF_7, 0, Reg::Ebx, 11)
+        .requires(has_tsx)
+        .canonical("RTM")
+        .linux("rtm"),
+    FeatureSpec::new("Other", "PT", LEAF_7, 0, Reg::Ebx, 25).linux("intel_pt"),
+    FeatureSpec::new("Other", "LA57", LEAF_7, 0, Reg::Ecx, 16).linux("la57"),
+    FeatureSpec::new("Other", "FSRM", LEAF_7, 0, Reg::Edx, 4).linux("fsrm"),
+    FeatureSpec::new("Other", "SERIALIZE", LEAF_7, 0, Reg::Edx, 14).linux("serialize"),
+    FeatureSpec::new("Other", "CMPccXADD", LEAF_7, 1, Reg::Eax, 7)
+        .canonical("CMPCCXADD")
+        .linux("cmpccxadd"),
+    FeatureSpec::new("Other", "WRMSRNS", LEAF_7, 1, Reg::Eax, 19).linux("wrmsrns"),
+    FeatureSpec::new("Other", "HRESET", LEAF_7, 1, Reg::Eax, 22),
+    FeatureSpec::new("Other", "LAM", LEAF_7, 1, Reg::Eax, 26).linux("lam"),
+    FeatureSpec::new("Other", "MSRLIST", LEAF_7, 1, Reg::Eax, 27),
+    FeatureSpec::new("Other", "CLZERO", EXT_LEAF_8, 0, Reg::Ebx, 0)
+        .vendor(is_amd_or_hygon)
+        .linux("clzero"),
+];
+
+/// Named flags that aren't in the feature list, for [`describe_leaf`](super::describe_leaf).
+///
+/// These have no group, and are in the same order as [`FEATURE_SPECS`].
+#[cfg(not(dos))]
+pub static UNLISTED_FEATURE_SPECS: [FeatureSpec; 85] = [
+    // Leaf 1
+    FeatureSpec::new("", "VME", LEAF_1, 0, Reg::Edx, 1).linux("vme"),
+    FeatureSpec::new("", "DE", LEAF_1, 0, Reg::Edx, 2).linux("de"),
+    FeatureSpec::new("", "PSE", LEAF_1, 0, Reg::Edx, 3).linux("pse"),
+    FeatureSpec::new("", "MSR", LEAF_1, 0, Reg::Edx, 5).linux("msr"),
+    FeatureSpec::new("", "PAE", LEAF_1, 0, Reg::Edx, 6).linux("pae"),
+    FeatureSpec::new("", "MCE", LEAF_1, 0, Reg::Edx, 7).linux("mce"),
+    FeatureSpec::new("", "SEP", LEAF_1, 0, Reg::Edx, 11).linux("sep"),
+    FeatureSpec::new("", "MTRR", LEAF_1, 0, Reg::Edx, 12).linux("mtrr"),
+    FeatureSpec::new("", "PGE", LEAF_1, 0, Reg::Edx, 13).linux("pge"),
+    FeatureSpec::new("", "MCA", LEAF_1, 0, Reg::Edx, 14).linux("mca"),
+    FeatureSpec::new("", "PAT", LEAF_1, 0, Reg::Edx, 16).linux("pat"),
+    FeatureSpec::new("", "PSE-36", LEAF_1, 0, Reg::Edx, 17).linux("pse36"),
+    FeatureSpec::new("", "PSN", LEAF_1, 0, Reg::Edx, 18).linux("pn"),
+    FeatureSpec::new("", "CLFLUSH", LEAF_1, 0, Reg::Edx, 19).linux("clflush"),
+    FeatureSpec::new("", "DS", LEAF_1, 0, Reg::Edx, 21).linux("dts"),
+    FeatureSpec::new("", "ACPI", LEAF_1, 0, Reg::Edx, 22).linux("acpi"),
+    FeatureSpec::new("", "FXSR", LEAF_1, 0, Reg::Edx, 24).linux("fxsr"),
+    FeatureSpec::new("", "SS", LEAF_1, 0, Reg::Edx, 27).linux("ss"),
+    FeatureSpec::new("", "TM", LEAF_1, 0, Reg::Edx, 29).linux("tm"),
+    FeatureSpec::new("", "PBE", LEAF_1, 0, Reg::Edx, 31).linux("pbe"),
+    FeatureSpec::new("", "PCLMULQDQ", LEAF_1, 0, Reg::Ecx, 1).linux("pclmulqdq"),
+    FeatureSpec::new("", "DTES64", LEAF_1, 0, Reg::Ecx, 2).linux("dtes64"),
+    FeatureSpec::new("", "MONITOR", LEAF_1, 0, Reg::Ecx, 3).linux("monitor"),
+    FeatureSpec::new("", "DS-CPL", LEAF_1, 0, Reg::Ecx, 4).linux("ds_cpl"),
+    FeatureSpec::new("", "SMX", LEAF_1, 0, Reg::Ecx, 6).linux("smx"),
+    FeatureSpec::new("", "EST", LEAF_1, 0, Reg::Ecx, 7).linux("est"),
+    FeatureSpec::new("", "TM2", LEAF_1, 0, Reg::Ecx, 8).linux("tm2"),
+    FeatureSpec::new("", "CNXT-ID", LEAF_1, 0, Reg::Ecx, 10).linux("cid"),
+    FeatureSpec::new("", "SDBG", LEAF_1, 0, Reg::Ecx, 11).linux("sdbg"),
+    FeatureSpec::new("", "xTPR", LEAF_1, 0, Reg::Ecx, 14).linux("xtpr"),
+    FeatureSpec::new("", "PDCM", LEAF_1, 0, Reg::Ecx, 15).linux("pdcm"),
+    FeatureSpec::new("", "PCID", LEAF_1, 0, Reg::Ecx, 17).linux("pcid"),
+    FeatureSpec::new("", "DCA", LEAF_1, 0, Reg::Ecx, 18).linux("dca"),
+    FeatureSpec::new("", "MOVBE", LEAF_1, 0, Reg::Ecx, 22).linux("movbe"),
+    FeatureSpec::new("", "XSAVE", LEAF_1, 0, Reg::Ecx, 26).linux("xsave"),
+    FeatureSpec::new("", "OSXSAVE", LEAF_1, 0, Reg::Ecx, 27),
+    FeatureSpec::new("", "Hypervisor", LEAF_1, 0, Reg::Ecx, 31).linux("hypervisor"),
+    // Leaf 7
+    FeatureSpec::new("", "TSC_ADJUST", LEAF_7, 0, Reg::Ebx, 1).linux("tsc_adjust"),
+    FeatureSpec::new("", "SGX", LEAF_7, 0, Reg::Ebx, 2).linux("sgx"),
+    FeatureSpec::new("", "HLE", LEAF_7, 0, Reg::Ebx, 4).linux("hle"),
+    FeatureSpec::new("", "FDP_EXCPTN_ONLY", LEAF_7, 0, Reg::Ebx, 6),
+    FeatureSpec::new("", "SMEP", LEAF_7, 0, Reg::Ebx, 7).linux("smep"),
+    FeatureSpec::new("", "INVPCID", LEAF_7, 0, Reg::Ebx, 10).linux("invpcid"),
+    FeatureSpec::new("", "RDT-M", LEAF_7, 0, Reg::Ebx, 12).linux("cqm"),
+    FeatureSpec::new("", "FPU-CSDS-Deprecated", LEAF_7, 0, Reg::Ebx, 13),
+    FeatureSpec::new("", "RDT-A", LEAF_7, 0, Reg::Ebx, 15).linux("rdt_a"),
+    FeatureSpec::new("", "ADX", LEAF_7, 0, Reg::Ebx, 19).linux("adx"),
+    FeatureSpec::new("", "SMAP", LEAF_7, 0, Reg::Ebx, 20).linux("smap"),
+    FeatureSpec::new("", "CLFLUSHOPT", LEAF_7, 0, Reg::Ebx, 23).linux("clflushopt"),
+    FeatureSpec::new("", "CLWB", LEAF_7, 0, Reg::Ebx, 24).linux("clwb"),
+    FeatureSpec::new("", "PREFETCHWT1", LEAF_7, 0, Reg::Ecx, 0),
+    FeatureSpec::new("", "UMIP", LEAF_7, 0, Reg::Ecx, 2).linux("umip"),
+    FeatureSpec::new("", "WAITPKG", LEAF_7, 0, Reg::Ecx, 5).linux("waitpkg"),
+    FeatureSpec::new("", "GFNI", LEAF_7, 0, Reg::Ecx, 8).linux("gfni"),
+    FeatureSpec::new("", "TME", LEAF_7, 0, Reg::Ecx, 13).linux("tme"),
+    FeatureSpec::new("", "RDPID", LEAF_7, 0, Reg::Ecx, 22).linux("rdpid"),
+    FeatureSpec::new("", "MD_CLEAR", LEAF_7, 0, Reg::Edx, 10).linux("md_clear"),
+    FeatureSpec::new("", "RTM_ALWAYS_ABORT", LEAF_7, 0, Reg::Edx, 11).linux("rtm_always_abort"),
+    FeatureSpec::new("", "Hybrid", LEAF_7, 0, Reg::Edx, 15).linux("hybrid_cpu"),
+    FeatureSpec::new("", "PCONFIG", LEAF_7, 0, Reg::Edx, 18).linux("pconfig"),
+    FeatureSpec::new("", "IBRS-IBPB", LEAF_7, 0, Reg::Edx, 26).canonical("IBRS_IBPB"),
+    FeatureSpec::new("", "STIBP", LEAF_7, 0, Reg::Edx, 27).linux("intel_stibp"),
+    FeatureSpec::new("", "L1D_FLUSH", LEAF_7, 0, Reg::Edx, 28).linux("flush_l1d"),
+    FeatureSpec::new("", "ARCH_CAPABILITIES", LEAF_7, 0, Reg::Edx, 29).linux("arch_capabilities"),
+    FeatureSpec::new("", "SSBD", LEAF_7, 0, Reg::Edx, 31).linux("spec_ctrl_ssbd"),
+    // Not listed in the AVX512 group, since AMX already has a BF16
+    FeatureSpec::new("", "AVX512-BF16", LEAF_7, 1, Reg::Eax, 5)
+        .canonical("AVX512_BF16")
+        .linux("avx512_bf16"),
+    FeatureSpec::new("", "AVX10", LEAF_7, 1, Reg::Edx, 19),
+    // Leaf 8000_0001h
+    FeatureSpec::new("", "SYSCALL", EXT_LEAF_1, 0, Reg::Edx, 11).linux("syscall"),
+    FeatureSpec::new("", "FXSR", EXT_LEAF_1, 0, Reg::Edx, 24)
+        .vendor(is_amd_or_hygon)
+        .linux("fxsr"),
+    FeatureSpec::new("", "FFXSR", EXT_LEAF_1, 0, Reg::Edx, 25).linux("fxsr_opt"),
+    FeatureSpec::new("", "Page1GB", EXT_LEAF_1, 0, Reg::Edx, 26).linux("pdpe1gb"),
+    FeatureSpec::new("", "RDTSCP", EXT_LEAF_1, 0, Reg::Edx, 27).linux("rdtscp"),
+    FeatureSpec::new("", "CMP-Legacy", EXT_LEAF_1, 0, Reg::Ecx, 1).linux("cmp_legacy"),
+    FeatureSpec::new("", "ExtApicSpace", EXT_LEAF_1, 0, Reg::Ecx, 3).linux("extapic"),
+    FeatureSpec::new("", "AltMovCr8", EXT_LEAF_1, 0, Reg::Ecx, 4).linux("cr8_legacy"),
+    FeatureSpec::new("", "MisAlignSse", EXT_LEAF_1, 0, Reg::Ecx, 7).linux("misalignsse"),
+    FeatureSpec::new("", "OSVW", EXT_LEAF_1, 0, Reg::Ecx, 9).linux("osvw"),
+    FeatureSpec::new("", "IBS", EXT_LEAF_1, 0, Reg::Ecx, 10).linux("ibs"),
+    FeatureSpec::new("", "XOP", EXT_LEAF_1, 0, Reg::Ecx, 11).linux("xop"),
+    FeatureSpec::new("", "SKINIT", EXT_LEAF_1, 0, Reg::Ecx, 12).linux("skinit"),
+    FeatureSpec::new("", "WDT", EXT_LEAF_1, 0, Reg::Ecx, 13).linux("wdt"),
+    FeatureSpec::new("", "LWP", EXT_LEAF_1, 0, Reg::Ecx, 15).linux("lwp"),
+    FeatureSpec::new("", "FMA4", EXT_LEAF_1, 0, Reg::Ecx, 16).linux("fma4"),
+    FeatureSpec::new("", "TBM", EXT_LEAF_1, 0, Reg::Ecx, 21).linux("tbm"),
+    FeatureSpec::new("", "TopoExt", EXT_LEAF_1, 0, Reg::Ecx, 22).linux("topoext"),
+];
+
+/// A set of flags from [`FEATURE_SPECS`], with one bit per table row.
+///
+/// Cheap to copy and compare, for checking many feature requirements, such
+/// as each binary a launcher could start, against one CPU.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FeatureSet(u128);
+
+// Every row needs a bit
+const _: () = assert!(FEATURE_SPECS.len() <= 128);
+
+impl FeatureSet {
+    /// Returns the flags that are set for the current CPU.
+    #[must_use]
+    pub fn detect() -> Self {
+        Self::from_specs(|spec| spec.is_set())
+    }
+
+    /// Returns the flags with any of the given names. For flags listed
+    /// once per vendor, all of them are included.
+    #[must_use]
+    pub fn from_names(names: &[&str]) -> Self {
+        Self::from_specs(|spec| names.contains(&spec.name))
+    }
+
+    /// Returns the flags in a feature list group, such as "AVX512".
+    #[must_use]
+    pub fn from_group(group: &str) -> Self {
+        Self::from_specs(|spec| spec.group == group)
+    }
+
+    /// Returns the flags that are set for the current CPU, but that the OS
+    /// hasn't enabled. See [`feature_status`].
+    #[must_use]
+    pub fn os_disabled() -> Self {
+        Self::from_specs(|spec| spec.is_set() && spec.os_status() == FeatureStatus::OsDisabled)
+    }
+
+    fn from_specs(filter: impl Fn(&FeatureSpec) -> bool) -> Self {
+        let bits = FEATURE_SPECS
+            .iter()
+            .enumerate()
+            .filter(|(_, spec)| filter(spec))
+            .fold(0u128, |bits, (i, _)| bits | (1 << i));
+
+        Self(bits)
+    }
+
+    /// Is a flag with this name in the set?
+    #[must_use]
+    pub fn contains(self, name: &str) -> bool {
+        !self.intersection(Self::from_names(&[name])).is_empty()
+    }
+
+    /// The number of flags in the set
+    #[must_use]
+    pub const fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    #[must_use]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    #[must_use]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    #[must_use]
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// The flags in this set, but not in `other`
+    #[must_use]
+    pub const fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// Is every flag in this set also in `other`?
+    #[must_use]
+    pub const fn is_subset(self, other: Self) -> bool {
+        self.difference(other).is_empty()
+    }
+
+    /// The flags in the set, in table order
+    pub fn iter(self) -> impl Iterator<Item = &'static FeatureSpec> {
+        let mut bits = self.0;
+
+        core::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+
+            let i = bits.trailing_zeros() as usize;
+            // Clear the lowest set bit
+            bits &= bits - 1;
+
+            Some(&FEATURE_SPECS[i])
+        })
+    }
+}
+
+/// Returns the flags from [`FEATURE_SPECS`] that are set for the current CPU,
+/// in table order.
+#[cfg(not(dos))]
+#[must_use]
+pub fn detect_from_table() -> Vec<&'static FeatureSpec> {
+    FEATURE_SPECS.iter().filter(|spec| spec.is_set()).collect()
+}
+
+/// Returns each detected feature from [`FEATURE_SPECS`], with whether the OS
+/// has enabled it.
+///
+/// The feature list shows hardware support only, so a listed feature, such as
+/// AVX-512, may still be unusable if the OS doesn't save its register state.
+#[must_use]
+pub fn feature_status() -> Vec<(&'static FeatureSpec, FeatureStatus)> {
+    FEATURE_SPECS
+        .iter()
+        .filter(|spec| spec.is_set())
+        .map(|spec| (spec, spec.os_status()))
+        .collect()
 }
 
 /// Get the full list of detected features.
+///
+/// Features within a group are always in the order of [`FEATURE_SPECS`],
+/// so the output can be compared between versions.
 #[cfg(not(dos))]
 #[must_use]
 pub fn get_feature_list() -> BTreeMap<&'static str, String> {
-    const BASIC_FEATURES: FeatureMap = &[
-        ("FPU", has_fpu),
-        ("TSC", has_tsc),
-        ("CX8", has_cx8),
-        ("CX16", has_cx16),
-        ("CMOV", has_cmov),
-        ("MMX", has_mmx),
-        ("MMX+", has_mmx_plus),
-        ("3DNow!", has_3dnow),
-        ("3DNow!+", has_3dnow_plus),
-        ("3DNow!-Prefetch", has_3dnow_prefetch),
-        ("HT", has_ht),
-        ("APIC", has_apic),
-        ("AMD64", has_amd64),
-    ];
+    let mut groups: BTreeMap<&'static str, Vec<&'static str>> = BTreeMap::new();
 
-    const SSE_FEATURES: FeatureMap = &[
-        ("SSE", has_sse),
-        ("SSE2", has_sse2),
-        ("SSE3", has_sse3),
-        ("SSE4A", has_sse4a),
-        ("SSE4.1", has_sse41),
-        ("SSE4.2", has_sse42),
-        ("SSSE3", has_ssse3),
-    ];
+    for spec in detect_from_table() {
+        groups.entry(spec.group).or_default().push(spec.name);
+    }
 
-    const AVX_FEATURES: FeatureMap = &[
-        ("AVX", has_avx),
-        ("AVX2", has_avx2),
-        ("AVX-VNNI", has_avx_vnni),
-        ("VPCLMULQDQ", has_vpclmulqdq),
-    ];
+    groups
+        .into_iter()
+        .map(|(group, names)| (group, names.join(" ")))
+        .collect()
+}
 
-    const AVX512_FEATURES: FeatureMap = &[
-        ("F", has_avx512_f),
-        ("DQ", has_avx512_dq),
-        ("IFMA", has_avx512_ifma),
-        ("PF", has_avx512_pf),
-        ("ER", has_avx512_er),
-        ("CD", has_avx512_cd),
-        ("BW", has_avx512_bw),
-        ("VL", has_avx512_vl),
-        ("BITALG", has_avx512_bitalg),
-        ("VPOPCNTDQ", has_avx512_vpopcntdq),
-        ("4VNNIW", has_avx512_4vnniw),
-        ("4FMAPS", has_avx512_4fmaps),
-        ("VP2INTERSECT", has_avx512_vp2intersect),
+#[cfg(all(test, not(dos)))]
+mod tests {
+    use super::*;
+    use crate::cpuid::Cpuid;
+    use crate::cpuid::constants::{
+        EXT_LEAF_0, LEAF_0, VENDOR_AMD, VENDOR_CYRIX, VENDOR_HYGON, VENDOR_INTEL,
+    };
+    use crate::cpuid::provider::{CpuDump, with_cpuid_provider};
+    use std::collections::HashMap;
+
+    /// Feature flag registers, as (leaf, sub-leaf, register index), in the
+    /// canonical order of `FEATURE_SPECS`
+    const FLAG_REGISTERS: [(u32, u32, usize); 10] = [
+        (LEAF_1, 0, 3),
+        (LEAF_1, 0, 2),
+        (LEAF_7, 0, 1),
+        (LEAF_7, 0, 2),
+        (LEAF_7, 0, 3),
+        (LEAF_7, 1, 0),
+        (EXT_LEAF_1, 0, 3),
+        (EXT_LEAF_1, 0, 2),
+        (EXT_LEAF_8, 0, 1),
+        // Last, since its bit 0 is always set to expose sub-leaf 1
+        (LEAF_7, 0, 0),
     ];
 
-    const SECURITY_FEATURES: FeatureMap = &[
-        ("NX", has_nx),
-        ("RDSEED", has_rdseed),
-        ("RDRAND", has_rdrand),
-        ("AES", has_aes),
-        ("VAES", has_vaes),
-        ("SHA", has_sha),
-        ("VT-x", has_vtx),
-        ("AMD-V", has_amdv),
-    ];
+    /// A CPU with the given vendor, all the feature leaves, and at most one flag set
+    fn dump_with_flag(vendor: &str, flag: Option<(u32, u32, usize, u32)>) -> CpuDump {
+        let vendor = vendor.as_bytes();
+        let reg = |i: usize| {
+            u32::from_le_bytes(
+                vendor[i..i + 4]
+                    .try_into()
+                    .expect("Vendor string is 12 bytes"),
+            )
+        };
+
+        let mut map = HashMap::new();
+        map.insert(
+            (LEAF_0, 0),
+            Cpuid {
+                eax: LEAF_7,
+                ebx: reg(0),
+                ecx: reg(8),
+                edx: reg(4),
+            },
+        );
+        map.insert(
+            (EXT_LEAF_0, 0),
+            Cpuid {
+                eax: EXT_LEAF_8,
+                ..Cpuid::default()
+            },
+        );
+        map.insert(
+            (LEAF_7, 0),
+            Cpuid {
+                eax: 1,
+                ..Cpuid::default()
+            },
+        );
+
+        if let Some((leaf, sub_leaf, reg, bit)) = flag {
+            let res = map.entry((leaf, sub_leaf)).or_default();
+            let reg = match reg {
+                0 => &mut res.eax,
+                1 => &mut res.ebx,
+                2 => &mut res.ecx,
+                _ => &mut res.edx,
+            };
+            *reg |= 1 << bit;
+        }
 
-    const MATH_FEATURES: FeatureMap = &[
-        ("FMA", has_fma),
-        ("BMI1", has_bmi1),
-        ("BMI2", has_bmi2),
-        ("F16C", has_f16c),
-    ];
+        CpuDump { cpus: vec![map] }
+    }
 
-    const OTHER_FEATURES: FeatureMap = &[("x2apic", has_x2apic), ("POPCNT", has_popcnt)];
+    /// Where a feature's flag is, as its position in the canonical order
+    fn flag_position(spec: &FeatureSpec) -> usize {
+        let reg = match spec.reg {
+            Reg::Eax => 0,
+            Reg::Ebx => 1,
+            Reg::Ecx => 2,
+            Reg::Edx => 3,
+        };
+        let i = FLAG_REGISTERS
+            .iter()
+            .position(|&flags| flags == (spec.leaf, spec.sub_leaf, reg))
+            .unwrap_or_else(|| panic!("Unknown register for {} {}", spec.group, spec.name));
+
+        i * 32 + spec.bit as usize
+    }
 
-    let mut map = BTreeMap::new();
+    #[test]
+    fn test_feature_list_order() {
+        for pair in FEATURE_SPECS.windows(2) {
+            let [a, b] = pair else { unreachable!() };
+            if a.group != b.group {
+                continue;
+            }
+
+            assert!(
+                flag_position(a) < flag_position(b),
+                "{}: {} should come after {}",
+                a.group,
+                a.name,
+                b.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_feature_specs_detected() {
+        for spec in &FEATURE_SPECS {
+            let reg = flag_position(spec) / 32;
+            let (leaf, sub_leaf, reg) = FLAG_REGISTERS[reg];
+            let flag = Some((leaf, sub_leaf, reg, spec.bit));
+
+            let detected = [VENDOR_INTEL, VENDOR_AMD, VENDOR_CYRIX]
+                .iter()
+                .any(|vendor| {
+                    with_cpuid_provider(dump_with_flag(vendor, flag), || {
+                        spec.is_set()
+                            && get_feature_list()
+                                .get(spec.group)
+                                .is_some_and(|names| names.split(' ').any(|name| name == spec.name))
+                    })
+                });
+            assert!(detected, "{} {} not detected", spec.group, spec.name);
+        }
+    }
+
+    #[test]
+    fn test_has_fns_match_specs() {
+        // The direct checks read the same bits as the table rows
+        let checks: [(FeatureFn, &str); 102] = [
+            (has_fpu, "FPU"),
+            (has_tsc, "TSC"),
+            (has_cx8, "CX8"),
+            (has_apic, "APIC"),
+            (has_cmov, "CMOV"),
+            (has_mmx, "MMX"),
+            (has_ht, "HT"),
+            (has_cx16, "CX16"),
+            (has_mmx_plus, "MMX+"),
+            (has_amd64, "AMD64"),
+            (has_3dnow_plus, "3DNow!+"),
+            (has_3dnow, "3DNow!"),
+            (has_lahf_lm, "LAHF-SAHF"),
+            (has_3dnow_prefetch, "3DNow!-Prefetch"),
+            (has_sse, "SSE"),
+            (has_sse2, "SSE2"),
+            (has_sse3, "SSE3"),
+            (has_ssse3, "SSSE3"),
+            (has_sse41, "SSE4.1"),
+            (has_sse42, "SSE4.2"),
+            (has_sse4a, "SSE4A"),
+            (has_avx, "AVX"),
+            (has_avx2, "AVX2"),
+            (has_vpclmulqdq, "VPCLMULQDQ"),
+            (has_avx_vnni, "AVX-VNNI"),
+            (has_avx_ifma, "AVX-IFMA"),
+            (has_avx512_f, "F"),
+            (has_avx512_dq, "DQ"),
+            (has_avx512_ifma, "IFMA"),
+            (has_avx512_pf, "PF"),
+            (has_avx512_er, "ER"),
+            (has_avx512_cd, "CD"),
+            (has_avx512_bw, "BW"),
+            (has_avx512_vl, "VL"),
+            (has_avx512_vnni, "VNNI"),
+            (has_avx512_bitalg, "BITALG"),
+            (has_avx512_vpopcntdq, "VPOPCNTDQ"),
+            (has_avx512_4vnniw, "4VNNIW"),
+            (has_avx512_4fmaps, "4FMAPS"),
+            (has_avx512_vp2intersect, "VP2INTERSECT"),
+            (has_avx512_fp16, "FP16"),
+            (has_amx_bf16, "BF16"),
+            (has_amx_tile, "TILE"),
+            (has_amx_int8, "INT8"),
+            (has_vtx, "VT-x"),
+            (has_aes, "AES"),
+            (has_rdrand, "RDRAND"),
+            (has_mpx, "MPX"),
+            (has_rdseed, "RDSEED"),
+            (has_sha, "SHA"),
+            (has_pku, "PKU"),
+            (has_ospke, "OSPKE"),
+            (has_cet_ss, "CET-SS"),
+            (has_vaes, "VAES"),
+            (has_cet_ibt, "CET-IBT"),
+            (has_nx, "NX"),
+            (has_amdv, "AMD-V"),
+            (has_fma, "FMA"),
+            (has_f16c, "F16C"),
+            (has_bmi1, "BMI1"),
+            (has_bmi2, "BMI2"),
+            (has_lzcnt, "LZCNT"),
+            (has_x2apic, "x2apic"),
+            (has_popcnt, "POPCNT"),
+            (has_tsc_deadline, "TSC-Deadline"),
+            (has_fsgsbase, "FSGSBASE"),
+            (has_erms, "ERMS"),
+            (has_tsx, "TSX"),
+            (has_pt, "PT"),
+            (has_la57, "LA57"),
+            (has_fsrm, "FSRM"),
+            (has_serialize, "SERIALIZE"),
+            (has_cmpccxadd, "CMPccXADD"),
+            (has_wrmsrns, "WRMSRNS"),
+            (has_hreset, "HRESET"),
+            (has_lam, "LAM"),
+            (has_msrlist, "MSRLIST"),
+            (has_clzero, "CLZERO"),
+            (has_vme, "VME"),
+            (has_de, "DE"),
+            (has_pse, "PSE"),
+            (has_msr, "MSR"),
+            (has_pae, "PAE"),
+            (has_pse36, "PSE-36"),
+            (has_clflush, "CLFLUSH"),
+            (has_dca, "DCA"),
+            (has_xsave, "XSAVE"),
+            (has_osxsave, "OSXSAVE"),
+            (is_hypervisor_guest, "Hypervisor"),
+            (has_hle, "HLE"),
+            (has_rdt_m, "RDT-M"),
+            (has_fpu_csds_deprecated, "FPU-CSDS-Deprecated"),
+            (has_rdt_a, "RDT-A"),
+            (has_clflushopt, "CLFLUSHOPT"),
+            (has_clwb, "CLWB"),
+            (has_waitpkg, "WAITPKG"),
+            (has_tme, "TME"),
+            (has_rtm_always_abort, "RTM_ALWAYS_ABORT"),
+            (has_hybrid, "Hybrid"),
+            (has_avx10, "AVX10"),
+            (has_rdtscp, "RDTSCP"),
+            (has_ext_apic_space, "ExtApicSpace"),
+        ];
+
+        for (check, name) in checks {
+            let specs: Vec<_> = FEATURE_SPECS
+                .iter()
+                .chain(UNLISTED_FEATURE_SPECS.iter())
+                .filter(|spec| spec.name == name)
+                .collect();
+            assert!(!specs.is_empty(), "{name} isn't a feature");
+
+            for spec in specs {
+                let reg = match spec.reg {
+                    Reg::Eax => 0,
+                    Reg::Ebx => 1,
+                    Reg::Ecx => 2,
+                    Reg::Edx => 3,
+                };
+                let flag = Some((spec.leaf, spec.sub_leaf, reg, spec.bit));
+
+                for vendor in [VENDOR_INTEL, VENDOR_AMD, VENDOR_HYGON, VENDOR_CYRIX] {
+                    with_cpuid_provider(dump_with_flag(vendor, flag), || {
+                        assert_eq!(check(), spec.is_set(), "{vendor}: {name}");
+                    });
+                    with_cpuid_provider(dump_with_flag(vendor, None), || {
+                        assert!(!check(), "{vendor}: {name}");
+                    });
+                }
+            }
+        }
+    }
 
-    let mut basic: Vec<&'static str> = Vec::with_capacity(BASIC_FEATURES.len());
-    let mut sse: Vec<&'static str> = Vec::with_capacity(SSE_FEATURES.len());
-    let mut avx: Vec<&'static str> = Vec::with_capacity(AVX_FEATURES.len());
-    let mut avx512: Vec<&'static str> = Vec::with_capacity(AVX512_FEATURES.len());
-    let mut encryption: Vec<&'static str> = Vec::with_capacity(SECURITY_FEATURES.len());
-    let mut math: Vec<&'static str> = Vec::with_capacity(MATH_FEATURES.len());
-    let mut other: Vec<&'static str> = Vec::with_capacity(OTHER_FEATURES.len());
-
-    for (v, key, checks) in [
-        (&mut basic, "Base", BASIC_FEATURES),
-        (&mut sse, "SSE", SSE_FEATURES),
-        (&mut avx, "AVX", AVX_FEATURES),
-        (&mut avx512, "AVX512", AVX512_FEATURES),
-        (&mut encryption, "Security", SECURITY_FEATURES),
-        (&mut math, "Math", MATH_FEATURES),
-        (&mut other, "Other", OTHER_FEATURES),
-    ] {
-        for (name, check) in checks {
-            if check() {
-                v.push(name);
+    #[test]
+    fn test_feature_list_all_flags() {
+        // Every listed flag at once, so nothing is dropped as the list grows
+        for vendor in [VENDOR_INTEL, VENDOR_AMD, VENDOR_CYRIX] {
+            let mut dump = dump_with_flag(vendor, None);
+            for spec in &FEATURE_SPECS {
+                let (leaf, sub_leaf, reg) = FLAG_REGISTERS[flag_position(spec) / 32];
+                let res = dump.cpus[0].entry((leaf, sub_leaf)).or_default();
+                let reg = match reg {
+                    0 => &mut res.eax,
+                    1 => &mut res.ebx,
+                    2 => &mut res.ecx,
+                    _ => &mut res.edx,
+                };
+                *reg |= 1 << spec.bit;
             }
+
+            let (expected, listed) = with_cpuid_provider(dump, || {
+                let expected = FEATURE_SPECS.iter().filter(|spec| spec.is_set()).count();
+                let listed: usize = get_feature_list()
+                    .values()
+                    .map(|names| names.split(' ').count())
+                    .sum();
+                (expected, listed)
+            });
+
+            assert!(expected > 0, "{vendor}: no features detected");
+            assert_eq!(listed, expected, "{vendor}: features missing from the list");
         }
+    }
 
-        if !v.is_empty() {
-            let s = v.join(" ");
-            map.insert(key, s);
+    #[test]
+    fn test_feature_set_ops() {
+        // Checks each operation against a bit by bit version
+        let naive = |bits: u128| -> Vec<bool> { (0..128).map(|i| bits & (1 << i) != 0).collect() };
+        let patterns = [
+            0,
+            u128::MAX,
+            1,
+            1 << 127,
+            0x5555_5555_5555_5555_5555_5555_5555_5555,
+            0x0123_4567_89AB_CDEF_FEDC_BA98_7654_3210,
+        ];
+
+        for &a in &patterns {
+            for &b in &patterns {
+                let (set_a, set_b) = (FeatureSet(a), FeatureSet(b));
+                let (bits_a, bits_b) = (naive(a), naive(b));
+
+                let count = bits_a.iter().filter(|&&bit| bit).count();
+                assert_eq!(set_a.count() as usize, count);
+
+                for i in 0..128 {
+                    let bit = |set: FeatureSet| set.0 & (1 << i) != 0;
+                    assert_eq!(bit(set_a.union(set_b)), bits_a[i] || bits_b[i]);
+                    assert_eq!(bit(set_a.intersection(set_b)), bits_a[i] && bits_b[i]);
+                    assert_eq!(bit(set_a.difference(set_b)), bits_a[i] && !bits_b[i]);
+                }
+
+                let subset = (0..128).all(|i| !bits_a[i] || bits_b[i]);
+                assert_eq!(set_a.is_subset(set_b), subset);
+            }
         }
     }
 
-    map
+    #[test]
+    fn test_feature_set_detect() {
+        let flag = |name: &str| {
+            let spec = FEATURE_SPECS
+                .iter()
+                .find(|spec| spec.name == name)
+                .expect("Missing feature spec");
+            let (leaf, sub_leaf, reg) = FLAG_REGISTERS[flag_position(spec) / 32];
+            Some((leaf, sub_leaf, reg, spec.bit))
+        };
+
+        let set = with_cpuid_provider(dump_with_flag(VENDOR_INTEL, flag("SSE2")), || {
+            FeatureSet::detect()
+        });
+
+        assert_eq!(set.count(), 1);
+        assert!(set.contains("SSE2"));
+        assert!(!set.contains("AVX"));
+        assert!(set.is_subset(FeatureSet::from_names(&["SSE", "SSE2"])));
+        assert_eq!(
+            set.iter().map(|spec| spec.name).collect::<Vec<_>>(),
+            ["SSE2"]
+        );
+    }
+
+    #[test]
+    fn test_feature_os_status() {
+        let status = |name: &str| {
+            let spec = FEATURE_SPECS
+                .iter()
+                .find(|spec| spec.name == name)
+                .expect("Missing feature spec");
+            let flag = flag_position(spec) / 32;
+            let (leaf, sub_leaf, reg) = FLAG_REGISTERS[flag];
+
+            with_cpuid_provider(
+                dump_with_flag(VENDOR_INTEL, Some((leaf, sub_leaf, reg, spec.bit))),
+                feature_status,
+            )
+            .into_iter()
+            .map(|(spec, status)| (spec.name, status))
+            .collect::<Vec<_>>()
+        };
+
+        // Only the hardware flag is set, so the OS hasn't enabled anything
+        assert_eq!(status("AVX"), [("AVX", FeatureStatus::OsDisabled)]);
+        assert_eq!(status("PKU"), [("PKU", FeatureStatus::OsDisabled)]);
+        assert_eq!(status("CET-SS"), [("CET-SS", FeatureStatus::OsUnknown)]);
+        // Only checked through the OS, which doesn't apply to a dump
+        assert_eq!(status("FSGSBASE"), [("FSGSBASE", FeatureStatus::OsUnknown)]);
+        assert_eq!(status("SSE2"), [("SSE2", FeatureStatus::Usable)]);
+    }
+
+    #[test]
+    fn test_name_styled() {
+        let spec = |name: &str, bit: u32| {
+            FEATURE_SPECS
+                .iter()
+                .chain(UNLISTED_FEATURE_SPECS.iter())
+                .find(|spec| spec.name == name && spec.bit == bit)
+                .expect("Missing feature")
+        };
+
+        let sse41 = spec("SSE4.1", 19);
+        assert_eq!(sse41.name_styled(FeatureNameStyle::Short), "SSE4.1");
+        assert_eq!(sse41.name_styled(FeatureNameStyle::Canonical), "SSE4_1");
+        assert_eq!(sse41.name_styled(FeatureNameStyle::Linux), "sse4_1");
+
+        let avx512f = spec("F", 16);
+        assert_eq!(avx512f.name_styled(FeatureNameStyle::Canonical), "AVX512F");
+        assert_eq!(avx512f.name_styled(FeatureNameStyle::Linux), "avx512f");
+
+        assert_eq!(spec("CX8", 8).name_styled(FeatureNameStyle::Linux), "cx8");
+        assert_eq!(spec("SSE3", 0).name_styled(FeatureNameStyle::Linux), "pni");
+        assert_eq!(
+            spec("3DNow!+", 30).name_styled(FeatureNameStyle::Linux),
+            "3dnowext"
+        );
+        assert_eq!(
+            spec("MMX+", 22).name_styled(FeatureNameStyle::Linux),
+            "mmxext"
+        );
+        assert_eq!(
+            spec("MMX+", 24).name_styled(FeatureNameStyle::Linux),
+            "cxmmx"
+        );
+
+        // Leaf 7 ECX bit 11 is the AVX-512 one, not the VEX-encoded AVX-VNNI
+        let vnni = spec("VNNI", 11);
+        assert_eq!(vnni.group, "AVX512");
+        assert_eq!(vnni.name_styled(FeatureNameStyle::Canonical), "AVX512_VNNI");
+        assert_eq!(vnni.name_styled(FeatureNameStyle::Linux), "avx512_vnni");
+        let avx_vnni = spec("AVX-VNNI", 4);
+        assert_eq!((avx_vnni.leaf, avx_vnni.sub_leaf), (LEAF_7, 1));
+        assert_eq!(avx_vnni.name_styled(FeatureNameStyle::Linux), "avx_vnni");
+
+        // No other name, so the short one is used
+        let avx = spec("AVX", 28);
+        assert_eq!(avx.name_styled(FeatureNameStyle::Canonical), "AVX");
+        let hreset = spec("HRESET", 22);
+        assert_eq!(hreset.name_styled(FeatureNameStyle::Linux), "HRESET");
+    }
+
+    #[test]
+    fn test_styled_names() {
+        for spec in FEATURE_SPECS.iter().chain(UNLISTED_FEATURE_SPECS.iter()) {
+            if let Some(canonical) = spec.canonical {
+                assert_ne!(spec.name, canonical);
+            }
+
+            if let Some(linux) = spec.linux {
+                assert!(
+                    linux
+                        .bytes()
+                        .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_'),
+                    "{linux}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_leaf_bit() {
+        with_cpuid_provider(dump_with_flag(VENDOR_INTEL, None), || {
+            for spec in FEATURE_SPECS.iter().chain(UNLISTED_FEATURE_SPECS.iter()) {
+                if spec.vendor.is_some() {
+                    continue;
+                }
+
+                let found =
+                    FeatureSpec::from_leaf_bit(spec.leaf, spec.sub_leaf, spec.reg, spec.bit);
+                assert_eq!(found.map(|found| found.name), Some(spec.name));
+            }
+
+            assert!(FeatureSpec::from_leaf_bit(LEAF_1, 0, Reg::Ecx, 16).is_none());
+            // AMD only
+            assert!(FeatureSpec::from_leaf_bit(EXT_LEAF_1, 0, Reg::Ecx, 6).is_none());
+            assert!(FeatureSpec::from_leaf_bit(EXT_LEAF_1, 0, Reg::Edx, 24).is_none());
+        });
+
+        // The same bit, for two vendors
+        let name = |vendor: &str| {
+            with_cpuid_provider(dump_with_flag(vendor, None), || {
+                FeatureSpec::from_leaf_bit(EXT_LEAF_1, 0, Reg::Edx, 24).map(|spec| spec.name)
+            })
+        };
+        assert_eq!(name(VENDOR_AMD), Some("FXSR"));
+        assert_eq!(name(VENDOR_CYRIX), Some("MMX+"));
+    }
+
+    #[test]
+    fn test_feature_list_no_duplicates() {
+        // Names are only repeated for the same feature on different vendors
+        for spec in &FEATURE_SPECS {
+            let same_name = FEATURE_SPECS
+                .iter()
+                .filter(|other| other.name == spec.name)
+                .count();
+            assert!(
+                same_name == 1 || spec.vendor.is_some(),
+                "{} is listed {same_name} times",
+                spec.name
+            );
+        }
+
+        // Each flag has one name per vendor
+        let specs: Vec<&FeatureSpec> = FEATURE_SPECS
+            .iter()
+            .chain(UNLISTED_FEATURE_SPECS.iter())
+            .collect();
+        for (i, a) in specs.iter().enumerate() {
+            for b in &specs[i + 1..] {
+                assert!(
+                    (a.leaf, a.sub_leaf, a.reg, a.bit) != (b.leaf, b.sub_leaf, b.reg, b.bit)
+                        || (a.vendor.is_some() && b.vendor.is_some()),
+                    "{} and {} share a flag",
+                    a.name,
+                    b.name
+                );
+            }
+        }
+
+        // Without any flags set, nothing is detected
+        for vendor in [VENDOR_INTEL, VENDOR_AMD] {
+            with_cpuid_provider(dump_with_flag(vendor, None), || {
+                assert!(get_feature_list().is_empty());
+            });
+        }
+    }
 }
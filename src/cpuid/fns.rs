@@ -13,9 +13,13 @@ use super::constants::*;
 #[cfg(target_arch = "x86")]
 use super::quirks::get_vendor_by_quirk;
 
-use super::is_hypervisor_guest;
+#[cfg(not(dos))]
+use super::Reg;
+use super::brand::CpuBrand;
+use super::{CpuSignature, has_ht, has_la57, has_pae, has_pse36, has_sse2, is_hypervisor_guest};
 use crate::common::{CoreType, DataSource};
 use alloc::string::String;
+use alloc::vec::Vec;
 
 /// Represents the result of a CPUID instruction call.
 ///
@@ -50,16 +54,32 @@ pub fn x86_cpuid(leaf: u32) -> Cpuid {
     x86_cpuid_count(leaf, 0)
 }
 
+/// Runs the CPUID instruction. Every CPUID call, from the DOS build or the
+/// real CPU provider, goes through here, so [`has_cpuid`] is always checked.
 #[inline]
 pub(crate) fn real_x86_cpuid_count(leaf: u32, sub_leaf: u32) -> Cpuid {
-    if !has_cpuid() {
+    checked_cpuid_count(has_cpuid(), leaf, sub_leaf, |leaf, sub_leaf| {
+        #[allow(unused_unsafe)]
+        unsafe {
+            __cpuid_count(leaf, sub_leaf).into()
+        }
+    })
+}
+
+/// Runs `cpuid` if `supported`, otherwise returns all zeros, as if every
+/// leaf were empty.
+#[inline]
+fn checked_cpuid_count(
+    supported: bool,
+    leaf: u32,
+    sub_leaf: u32,
+    cpuid: impl FnOnce(u32, u32) -> Cpuid,
+) -> Cpuid {
+    if !supported {
         return Cpuid::default();
     }
 
-    #[allow(unused_unsafe)]
-    unsafe {
-        __cpuid_count(leaf, sub_leaf).into()
-    }
+    cpuid(leaf, sub_leaf)
 }
 
 /// Calls CPUID with the given leaf (EAX) and sub-leaf (ECX).
@@ -94,6 +114,11 @@ pub fn cpuid_data_source() -> DataSource {
 
 /// Returns true if the CPUID instruction is supported.
 ///
+/// Always true on x86_64. On 32-bit x86, this checks whether the ID flag
+/// (bit 21) of EFLAGS can be toggled, which is only possible on CPUs with
+/// CPUID. Without it, [`x86_cpuid`] and [`x86_cpuid_count`] return all
+/// zeros rather than running the instruction.
+///
 /// Verified on real hardware
 #[must_use]
 pub fn has_cpuid() -> bool {
@@ -125,259 +150,2189 @@ pub fn has_cpuid() -> bool {
     }
 }
 
-/// Returns the maximum basic CPUID leaf supported.
-#[must_use]
-pub fn max_leaf() -> u32 {
-    x86_cpuid(LEAF_0).eax
+/// The operating mode the CPU is currently running in.
+///
+/// Compare with [`has_amd64`](super::has_amd64), which is whether the CPU
+/// *can* run 64-bit code, not whether it currently is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CpuMode {
+    /// Real mode (CR0.PE clear)
+    Real,
+    /// 16 or 32-bit protected mode, including virtual 8086 mode
+    Protected,
+    /// Long mode (EFER.LMA set), 64-bit or compatibility mode
+    Long,
 }
 
-#[must_use]
-pub fn max_hypervisor_leaf() -> u32 {
-    x86_cpuid(HYP_LEAF_0).eax
+impl From<CpuMode> for &str {
+    fn from(val: CpuMode) -> &'static str {
+        match val {
+            CpuMode::Real => "Real",
+            CpuMode::Protected => "Protected",
+            CpuMode::Long => "Long",
+        }
+    }
 }
 
-/// Returns the maximum extended CPUID leaf supported.
+/// Returns the mode the CPU is currently running in.
+///
+/// On the bare-metal (DOS) build, this reads CR0.PE with SMSW. Virtual 8086
+/// mode, such as under EMM386, shows as protected mode. Elsewhere, the mode
+/// is inferred from the target architecture, since a program under an OS
+/// can't be in real mode.
 #[must_use]
-pub fn max_extended_leaf() -> u32 {
-    x86_cpuid(EXT_LEAF_0).eax
+pub fn current_mode() -> CpuMode {
+    #[cfg(dos)]
+    {
+        // The DOS build is real mode code, so with CR0.PE set it's in
+        // virtual 8086 mode, which long mode doesn't have
+        if is_privileged() {
+            CpuMode::Real
+        } else {
+            CpuMode::Protected
+        }
+    }
+
+    #[cfg(all(not(dos), target_arch = "x86_64"))]
+    return CpuMode::Long;
+
+    #[cfg(all(not(dos), target_arch = "x86"))]
+    return CpuMode::Protected;
 }
 
-/// Returns the maximum vendor-specific CPUID leaf, if one exists,
-/// otherwise returns the maximum extended leaf.
+/// Can privileged instructions, such as RDMSR, be used?
+///
+/// The DOS build is real mode code, so it only runs at CPL 0 when the CPU
+/// is really in real mode. Under a memory manager like EMM386 or QEMM, or
+/// in a Windows DOS box, it runs in virtual 8086 mode at CPL 3, where
+/// RDMSR and control register moves raise #GP. SMSW is allowed at any
+/// privilege level without UMIP, which DOS never enables, so CR0.PE tells
+/// them apart.
+#[cfg(dos)]
 #[must_use]
-pub fn max_vendor_leaf() -> u32 {
-    match &*vendor_str() {
-        VENDOR_CENTAUR => x86_cpuid(CENTAUR_LEAF_0).eax,
-        VENDOR_TRANSMETA => x86_cpuid(TRANSMETA_LEAF_0).eax,
-        _ => max_extended_leaf(),
+pub fn is_privileged() -> bool {
+    let msw: u16;
+    // Safety: SMSW only reads the low word of CR0
+    unsafe {
+        core::arch::asm!(
+        "smsw {0:x}",
+        out(reg) msw,
+        options(nomem, nostack, preserves_flags),
+        );
     }
+
+    msw & 1 == 0
 }
 
-/// Returns true if the given leaf is valid and supported by the CPU.
+/// Can the RDFSBASE/RDGSBASE/WRFSBASE/WRGSBASE instructions be used?
 ///
-/// Checks whether the specified CPUID leaf is within the range supported by
-/// the processor (basic, extends, or vendor-specific).
+/// The [`has_fsgsbase`](super::has_fsgsbase) flag is only hardware support.
+/// The instructions raise #UD unless the OS sets CR4.FSGSBASE, and only run
+/// in 64-bit code, so this is always `false` for a 32-bit build, including
+/// the bare-metal (DOS) one. On Linux, it checks `HWCAP2_FSGSBASE`, which
+/// kernels from 5.9 set once they enable it.
+///
+/// Returns `None` when the OS support can't be checked, such as on other
+/// OSes, or with a CPUID dump loaded.
 #[must_use]
-pub fn is_valid_leaf(leaf: u32) -> bool {
-    if !has_cpuid() {
-        return false;
+pub fn fsgsbase_usable() -> Option<bool> {
+    if !super::has_fsgsbase() {
+        return Some(false);
     }
 
-    // Optimization: Leaf 1 is always valid if CPUID is present.
-    // This avoids redundant max_leaf() -> x86_cpuid(0) calls for common features.
-    if leaf == LEAF_1 {
-        return true;
-    }
+    #[cfg(any(dos, target_arch = "x86"))]
+    return Some(false);
 
-    match leaf {
-        0..EXT_LEAF_0 => leaf <= max_leaf(),
-        EXT_LEAF_0..=EXT_LEAF_MAX => leaf <= max_extended_leaf(),
-        _ => leaf <= max_vendor_leaf(),
+    #[cfg(all(not(dos), target_arch = "x86_64", target_os = "linux"))]
+    if info_source() == super::provider::CpuidInfoSource::Cpu {
+        const HWCAP2_FSGSBASE: u64 = 1 << 1;
+
+        // Safety: getauxval has no preconditions
+        let hwcap2 = unsafe { libc::getauxval(libc::AT_HWCAP2) };
+        return Some(hwcap2 & HWCAP2_FSGSBASE != 0);
     }
-}
 
-const CORE_TYPE_PERFORMANCE: u32 = 0x40;
-const CORE_TYPE_EFFICIENCY: u32 = 0x20;
+    #[cfg(not(any(dos, target_arch = "x86")))]
+    None
+}
 
-/// Detects the CPU core type from CPUID leaf 0x1A (Hybrid Information).
+/// Can the UMONITOR/UMWAIT/TPAUSE instructions be used?
 ///
-/// This is the authoritative source for distinguishing P-cores from E-cores
-/// on Intel hybrid architectures (Alder Lake and later).
-/// Falls back to `CoreType::Performance` if leaf 0x1A is not available.
+/// The [`has_waitpkg`](super::has_waitpkg) flag is only hardware support.
+/// Outside of ring 0, the instructions raise #UD when CR4.TSD is set, the
+/// same as RDTSC. Real mode is ring 0, so they always work there. On Linux,
+/// CR4.TSD is set per thread, with `prctl(PR_SET_TSC)`, so this asks
+/// `prctl(PR_GET_TSC)`.
+///
+/// Returns `None` when the OS support can't be checked, such as on other
+/// OSes, under a DOS memory manager's virtual 8086 mode, or with a CPUID
+/// dump loaded.
 #[must_use]
-pub fn core_type_from_cpuid() -> CoreType {
-    if !is_valid_leaf(LEAF_1A) {
-        return CoreType::Performance;
+pub fn waitpkg_usable() -> Option<bool> {
+    if !super::has_waitpkg() {
+        return Some(false);
     }
 
-    let res = x86_cpuid(LEAF_1A);
-    let core_type = (res.eax >> 24) & 0xFF;
+    #[cfg(dos)]
+    return is_privileged().then_some(true);
 
-    match core_type {
-        CORE_TYPE_EFFICIENCY => CoreType::Efficiency,
-        CORE_TYPE_PERFORMANCE => CoreType::Performance,
-        _ => CoreType::Performance,
+    #[cfg(all(not(dos), target_os = "linux"))]
+    if info_source() == super::provider::CpuidInfoSource::Cpu {
+        let mut tsc_mode: libc::c_int = 0;
+
+        // Safety: PR_GET_TSC writes one int through the pointer
+        let result = unsafe { libc::prctl(libc::PR_GET_TSC, &raw mut tsc_mode) };
+        if result == 0 {
+            return Some(tsc_mode == libc::PR_TSC_ENABLE);
+        }
     }
+
+    #[cfg(not(dos))]
+    None
 }
 
-/// Gets the CPU vendor ID string (e.g., "`GenuineIntel`", "`AuthenticAMD`").
+/// Can the RDPKRU/WRPKRU instructions be used?
 ///
-/// Returns a 12-character vendor string from CPUID leaf 0.
+/// They need the hardware flag ([`has_pku`](super::has_pku)), and the OS to
+/// have set CR4.PKE, which it reports with
+/// [`has_ospke`](super::has_ospke). Otherwise, they raise #UD.
 #[must_use]
-pub fn vendor_str() -> String {
-    #[cfg(target_arch = "x86")]
-    if !has_cpuid() {
-        return String::from(get_vendor_by_quirk());
-    }
-
-    raw_vendor_str(LEAF_0)
+pub fn pkru_usable() -> bool {
+    super::has_pku() && super::has_ospke()
 }
 
+/// Index port for the Cyrix configuration control registers
+#[cfg(dos)]
+const CYRIX_CCR_INDEX_PORT: u16 = 0x22;
+
+/// Data port for the Cyrix configuration control registers
+#[cfg(dos)]
+const CYRIX_CCR_DATA_PORT: u16 = 0x23;
+
+/// Reads a Cyrix configuration control register (CCR0-CCR6, DIR0/DIR1, etc.)
+///
+/// The register index is written to port 0x22, and the value read back
+/// from port 0x23. Each data port access resets the index, so every read
+/// needs both steps. Only call this on Cyrix CPUs (see [`is_cyrix`]), as
+/// other hardware may be decoded at these ports.
+///
+/// See: <https://www.ardent-tool.com/CPU/docs/Cyrix/detect.pdf>
+#[cfg(dos)]
 #[must_use]
-pub fn hypervisor_str() -> String {
-    if is_hypervisor_guest() {
-        let res = x86_cpuid(HYP_LEAF_0);
-        let mut bytes = [0u8; 12];
+pub fn cyrix_ccr(index: u8) -> u8 {
+    use super::dos::{inb, outb};
 
-        bytes[0..4].copy_from_slice(&res.ebx.to_le_bytes());
-        bytes[4..8].copy_from_slice(&res.ecx.to_le_bytes());
-        bytes[8..12].copy_from_slice(&res.edx.to_le_bytes());
+    outb(CYRIX_CCR_INDEX_PORT, index);
+    inb(CYRIX_CCR_DATA_PORT)
+}
 
-        let s = core::str::from_utf8(&bytes)
-            .unwrap_or(UNK)
-            .trim_matches('\0');
+/// Reads the Cyrix device identification registers, DIR0 and DIR1.
+///
+/// DIR0 identifies the exact model and clock multiplier, and DIR1 holds
+/// the stepping and revision. Returns `None` if the CPU isn't a Cyrix,
+/// or doesn't implement the registers (early Cx486SLC/DLC chips read
+/// back 0xFF). The model can be decoded with
+/// [`CyrixModel::detect_with_ids`](super::vendor::cyrix::CyrixModel::detect_with_ids).
+#[cfg(dos)]
+#[must_use]
+pub fn cyrix_device_id() -> Option<(u8, u8)> {
+    if !is_cyrix() {
+        return None;
+    }
 
-        String::from(s)
+    let dir0 = cyrix_ccr(0xFE);
+    let dir1 = cyrix_ccr(0xFF);
+
+    if dir0 == 0xFF {
+        None
     } else {
-        String::from(UNK)
+        Some((dir0, dir1))
     }
 }
 
-fn raw_vendor_str(leaf: u32) -> String {
-    let res = x86_cpuid(leaf);
-    let mut bytes = [0u8; 12];
+/// Reads the given Model Specific Register with RDMSR.
+///
+/// Only available on the bare-metal (DOS) build, since RDMSR raises a
+/// general protection fault outside of ring 0.
+///
+/// # Safety
+///
+/// The caller must be running at CPL 0 (real mode, or ring 0 in protected
+/// mode, see [`is_privileged`]), the CPU must support MSRs (see [`has_msr`](super::has_msr)), and
+/// `msr` must be a register the CPU implements. Reading an unimplemented
+/// MSR raises #GP.
+#[cfg(dos)]
+#[must_use]
+pub unsafe fn read_msr(msr: u32) -> u64 {
+    let lo: u32;
+    let hi: u32;
+    unsafe {
+        core::arch::asm!(
+        "rdmsr",
+        in("ecx") msr,
+        out("eax") lo,
+        out("edx") hi,
+        options(nomem, nostack, preserves_flags),
+        );
+    }
+    (u64::from(hi) << 32) | u64::from(lo)
+}
 
-    bytes[0..4].copy_from_slice(&res.ebx.to_le_bytes());
-    bytes[4..8].copy_from_slice(&res.edx.to_le_bytes());
-    bytes[8..12].copy_from_slice(&res.ecx.to_le_bytes());
+/// Whether WRMSRNS is supported: 0 if not checked yet, 1 if not, 2 if so
+#[cfg(dos)]
+static WRMSRNS: core::sync::atomic::AtomicU8 = core::sync::atomic::AtomicU8::new(0);
 
-    let s = core::str::from_utf8(&bytes)
-        .unwrap_or(UNK)
-        .trim_matches('\0');
+/// Writes the given value to a Model Specific Register.
+///
+/// Uses the non-serializing WRMSRNS when the CPU supports it, and WRMSR
+/// otherwise. Only available on the bare-metal (DOS) build, since both
+/// raise a general protection fault outside of ring 0.
+///
+/// # Safety
+///
+/// Same requirements as [`read_msr`]. In addition, the caller is responsible
+/// for the effects of the write: setting reserved bits raises #GP, and many
+/// MSRs change processor behaviour. The write isn't serializing on CPUs
+/// with WRMSRNS, so call [`serialize`] afterwards if later instructions
+/// depend on it.
+#[cfg(dos)]
+pub unsafe fn write_msr(msr: u32, value: u64) {
+    use core::sync::atomic::Ordering;
 
-    String::from(s)
-}
+    let lo = value as u32;
+    let hi = (value >> 32) as u32;
 
-#[must_use]
-pub fn read_multi_leaf_str(min_leaf: u32, max_leaf: u32) -> String {
-    if !is_valid_leaf(max_leaf) {
-        return String::from(UNK);
-    }
+    // Check the feature once, since CPUID would cost more than WRMSRNS saves
+    let wrmsrns = match WRMSRNS.load(Ordering::Relaxed) {
+        0 => {
+            let has = super::has_wrmsrns();
+            WRMSRNS.store(if has { 2 } else { 1 }, Ordering::Relaxed);
+            has
+        }
+        state => state == 2,
+    };
 
-    let mut model = String::new();
-    for leaf in min_leaf..=max_leaf {
-        let res = x86_cpuid(leaf);
-        for reg in [res.eax, res.ebx, res.ecx, res.edx] {
-            let bytes = reg.to_le_bytes();
-            let s = core::str::from_utf8(&bytes).unwrap_or("");
-            model.push_str(s);
+    unsafe {
+        if wrmsrns {
+            // WRMSRNS, as bytes for assemblers without the mnemonic
+            core::arch::asm!(
+            ".byte 0x0f, 0x01, 0xc6",
+            in("ecx") msr,
+            in("eax") lo,
+            in("edx") hi,
+            options(nostack, preserves_flags),
+            );
+        } else {
+            core::arch::asm!(
+            "wrmsr",
+            in("ecx") msr,
+            in("eax") lo,
+            in("edx") hi,
+            options(nostack, preserves_flags),
+            );
         }
     }
-
-    String::from(model.trim().trim_matches('\0'))
 }
 
-fn is_vendor(v: &str) -> bool {
-    vendor_str() == v
+/// How much detection may do besides running CPUID.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum DetectionMode {
+    /// Only CPUID: no instruction probes, TSC timing or MSR reads, for
+    /// sandboxes and hypervisors that trap them
+    CpuidOnly,
+    /// Everything available on the platform
+    #[default]
+    Full,
 }
 
-/// Returns true if the CPU is from AMD.
-#[must_use]
-pub fn is_amd() -> bool {
-    is_vendor(VENDOR_AMD)
+// Whether detection is limited to CPUID. Per thread, like the CPUID provider
+#[cfg(not(dos))]
+std::thread_local! {
+    static CPUID_ONLY: core::cell::Cell<bool> = const { core::cell::Cell::new(false) };
 }
+#[cfg(dos)]
+static CPUID_ONLY: core::sync::atomic::AtomicBool = core::sync::atomic::AtomicBool::new(false);
 
-/// Returns true if the CPU is from Centaur (IDT/VIA/Zhaoxin).
-#[must_use]
-pub fn is_centaur() -> bool {
-    is_vendor(VENDOR_CENTAUR)
-}
+/// Sets whether detection is limited to CPUID, returning the previous value
+fn set_cpuid_only(cpuid_only: bool) -> bool {
+    #[cfg(not(dos))]
+    return CPUID_ONLY.replace(cpuid_only);
 
-/// Returns true if the CPU is from Cyrix.
-#[must_use]
-pub fn is_cyrix() -> bool {
-    is_vendor(VENDOR_CYRIX) || is_vendor(VENDOR_NSC)
+    #[cfg(dos)]
+    return CPUID_ONLY.swap(cpuid_only, core::sync::atomic::Ordering::Relaxed);
 }
 
-/// Is the CPU a Vortex86 or very similar RDC chip?
+/// Returns the current [`DetectionMode`].
 #[must_use]
-pub fn is_vortex() -> bool {
-    is_vendor(VENDOR_DMP) || is_vendor(VENDOR_RDC)
-}
+pub fn detection_mode() -> DetectionMode {
+    #[cfg(not(dos))]
+    let cpuid_only = CPUID_ONLY.get();
 
-/// Returns true if the CPU is from Intel.
-#[must_use]
-pub fn is_intel() -> bool {
-    is_vendor(VENDOR_INTEL)
-}
+    #[cfg(dos)]
+    let cpuid_only = CPUID_ONLY.load(core::sync::atomic::Ordering::Relaxed);
 
-#[must_use]
-pub fn is_umc() -> bool {
-    is_vendor(VENDOR_UMC)
+    if cpuid_only {
+        DetectionMode::CpuidOnly
+    } else {
+        DetectionMode::Full
+    }
 }
 
-/// Returns true if the CPU is from Zhaoxin.
-#[must_use]
-pub fn is_zhaoxin() -> bool {
-    is_vendor(VENDOR_ZHAOXIN)
+/// Runs `f` with the given [`DetectionMode`] on the current thread, then
+/// restores the previous one.
+pub fn with_detection_mode<R>(mode: DetectionMode, f: impl FnOnce() -> R) -> R {
+    let previous = set_cpuid_only(mode == DetectionMode::CpuidOnly);
+    let result = f();
+    set_cpuid_only(previous);
+
+    result
 }
 
-/// Returns true if the CPU is an Intel Overdrive processor.
+/// Whether probes are allowed: 0 if not checked yet, 1 if not, 2 if so
+#[cfg(not(dos))]
+static PROBES: core::sync::atomic::AtomicU8 = core::sync::atomic::AtomicU8::new(0);
+
+/// Returns false if instruction probes are turned off with the
+/// `RUSTID_NO_PROBE` environment variable, or by
+/// [`DetectionMode::CpuidOnly`].
 ///
-/// Checks the Overdrive bit (EAX bit 12) in CPUID leaf 1.
+/// Probes run an instruction after checking its CPUID bit, which faults if
+/// the bit is wrong, as on some emulators. With probes off, [`lzcnt_is_real`]
+/// trusts the CPUID bit, [`serialize`] uses CPUID, [`popcnt_u64`] counts in
+/// software, and [`xcr0`] returns `None`. The variable is ignored on the DOS
+/// build.
 #[must_use]
-pub fn is_overdrive() -> bool {
-    (x86_cpuid(LEAF_1).eax & (1 << 12)) != 0
-}
+pub fn probes_enabled() -> bool {
+    if detection_mode() == DetectionMode::CpuidOnly {
+        return false;
+    }
 
-/// Returns the number of logical cores.
-#[must_use]
-pub fn amd_logical_cores() -> u32 {
-    // Since AMD has a handy flag for getting logical cores,
-    // try that first
-    if is_amd() {
-        if is_valid_leaf(EXT_LEAF_8) {
-            let res = x86_cpuid(EXT_LEAF_8);
-            let count = (res.ecx & 0xFF) + 1;
-            if count > 1 {
-                return count;
-            }
-        }
+    #[cfg(dos)]
+    return true;
 
-        if max_leaf() > 0 {
-            let res = x86_cpuid(1);
-            let count = (res.ebx >> 16) & 0xFF;
-            if count != 0 {
-                return count;
+    #[cfg(not(dos))]
+    {
+        use core::sync::atomic::Ordering;
+
+        // Check the variable once, since these run in hot paths
+        match PROBES.load(Ordering::Relaxed) {
+            0 => {
+                let enabled = std::env::var_os("RUSTID_NO_PROBE").is_none();
+                PROBES.store(if enabled { 2 } else { 1 }, Ordering::Relaxed);
+                enabled
             }
+            state => state == 2,
         }
     }
-
-    1
 }
 
+/// Checks that the LZCNT encoding actually counts leading zeros.
+///
+/// Without LZCNT support, its encoding runs as BSR instead, which silently
+/// gives different results. This runs the instruction on a known input,
+/// rather than trusting the CPUID bit, which some hypervisors get wrong.
+/// The encoding is safe to run on any CPU with BSR (386 and later).
+///
+/// When reading a dump file, or with [probes](probes_enabled) off, this
+/// falls back to the CPUID bit.
 #[must_use]
-pub fn amd_threads_per_core() -> u32 {
-    if is_amd() && is_valid_leaf(EXT_LEAF_1E) {
-        let res = x86_cpuid(EXT_LEAF_1E);
-        let count = (res.ebx >> 8) & 0xFF;
+pub fn lzcnt_is_real() -> bool {
+    #[cfg(not(dos))]
+    if info_source() == super::provider::CpuidInfoSource::DumpFile {
+        return super::has_lzcnt();
+    }
 
-        count + 1
-    } else {
-        1u32
+    if !probes_enabled() {
+        return super::has_lzcnt();
     }
+
+    // LZCNT(1) is 31, BSR(1) is 0
+    let count: u32;
+    unsafe {
+        core::arch::asm!(
+        "lzcnt {count:e}, {input:e}",
+        input = in(reg) 1u32,
+        count = lateout(reg) count,
+        options(pure, nomem, nostack),
+        );
+    }
+
+    count == 31
 }
 
-// ------------------------------------------------------------------------
-// ! Leaf 0000_0001h
-// ------------------------------------------------------------------------
+/// Waits for all earlier instructions to finish, and all earlier stores to
+/// be written, before continuing.
+///
+/// Uses the SERIALIZE instruction when the CPU has it, and CPUID, which is
+/// also serializing but much slower, otherwise. Without CPUID, this does
+/// nothing. When reading a dump file, CPUID is always used, since the dump
+/// says nothing about the CPU running this, as it is with
+/// [probes](probes_enabled) off.
+#[inline]
+pub fn serialize() {
+    #[cfg(not(dos))]
+    let native = info_source() == super::provider::CpuidInfoSource::Cpu && probes_enabled();
 
-/// Returns the CPU brand ID from basic leaf 0x00000001.
+    #[cfg(dos)]
+    let native = true;
+
+    if native && super::has_serialize() {
+        // Safety: SERIALIZE support was just checked
+        unsafe {
+            // SERIALIZE, as bytes for assemblers without the mnemonic
+            core::arch::asm!(".byte 0x0f, 0x01, 0xe8", options(nostack, preserves_flags));
+        }
+    } else {
+        let _ = real_x86_cpuid_count(LEAF_0, 0);
+    }
+}
+
+/// Reads XCR0, the state components the OS has enabled for XSAVE.
 ///
-/// The brand ID is a value that identifies the specific CPU brand variant.
+/// Returns `None` when the OS hasn't set OSXSAVE, as XGETBV is then
+/// undefined, when reading a dump file, which doesn't record XCR0, and
+/// with [probes](probes_enabled) off.
 #[must_use]
-pub fn get_brand_id() -> u32 {
-    let res = x86_cpuid(LEAF_1);
-    res.ebx & 0xFF
-}
+pub fn xcr0() -> Option<u64> {
+    #[cfg(not(dos))]
+    if info_source() != super::provider::CpuidInfoSource::Cpu {
+        return None;
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::cpuid::vendor_str;
+    if !probes_enabled() {
+        return None;
+    }
 
-    #[test]
-    fn test_from_cpuid_result_for_cpu_info() {
+    if !super::has_osxsave() {
+        return None;
+    }
+
+    let lo: u32;
+    let hi: u32;
+    // Safety: OSXSAVE was just checked, so XGETBV is enabled
+    unsafe {
+        core::arch::asm!(
+        "xgetbv",
+        in("ecx") 0,
+        out("eax") lo,
+        out("edx") hi,
+        options(nomem, nostack, preserves_flags),
+        );
+    }
+
+    Some((u64::from(hi) << 32) | u64::from(lo))
+}
+
+/// Counts the set bits in `x`.
+///
+/// Uses the POPCNT instruction when the CPU has it, and a software count
+/// otherwise. When reading a dump file, the dump says nothing about the
+/// CPU running this, so the software count is used, as it is with
+/// [probes](probes_enabled) off.
+#[must_use]
+pub fn popcnt_u64(x: u64) -> u32 {
+    #[cfg(not(dos))]
+    let native = info_source() == super::provider::CpuidInfoSource::Cpu && probes_enabled();
+
+    #[cfg(dos)]
+    let native = true;
+
+    if native && super::has_popcnt() {
+        // Safety: POPCNT support was just checked
+        unsafe { popcnt_hw(x) }
+    } else {
+        popcnt_sw(x)
+    }
+}
+
+/// `count_ones` compiled with POPCNT enabled
+#[target_feature(enable = "popcnt")]
+unsafe fn popcnt_hw(x: u64) -> u32 {
+    x.count_ones()
+}
+
+/// Software bit count, for CPUs without POPCNT
+fn popcnt_sw(x: u64) -> u32 {
+    let x = x - ((x >> 1) & 0x5555_5555_5555_5555);
+    let x = (x & 0x3333_3333_3333_3333) + ((x >> 2) & 0x3333_3333_3333_3333);
+    let x = (x + (x >> 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+
+    (x.wrapping_mul(0x0101_0101_0101_0101) >> 56) as u32
+}
+
+/// Returns the maximum basic CPUID leaf supported.
+#[must_use]
+pub fn max_leaf() -> u32 {
+    x86_cpuid(LEAF_0).eax
+}
+
+/// Is the BIOS hiding most of the basic CPUID leaves?
+///
+/// Some BIOSes set the "Limit CPUID Maxval" bit (22) of `IA32_MISC_ENABLE`,
+/// which makes leaf 0 report a max leaf of 2 or 3 on Intel CPUs, hiding
+/// leaf 7 and later. On the DOS build this reads the MSR, when running in
+/// real mode. Otherwise, it's a guess: an Intel 64 CPU with such a low max
+/// leaf must be limited.
+///
+/// Returns `None` when it can't tell, and `Some(false)` for other vendors.
+#[must_use]
+pub fn cpuid_maxval_limited() -> Option<bool> {
+    if vendor_str() != VENDOR_INTEL {
+        return Some(false);
+    }
+
+    #[cfg(dos)]
+    if super::has_msr() && detection_mode() == DetectionMode::Full && is_privileged() {
+        let sig = CpuSignature::detect();
+
+        // The bit exists from the Pentium 4 and Core Duo on
+        if sig.display_family == 0xF || (sig.display_family == 6 && sig.display_model >= 0x0E) {
+            // Safety: real mode is ring 0, and the CPU check above ensures
+            // the MSR exists
+            let misc_enable = unsafe { read_msr(MSR_MISC_ENABLE) };
+            return Some(misc_enable & (1 << 22) != 0);
+        }
+    }
+
+    if max_leaf() > 3 {
+        Some(false)
+    } else if super::has_amd64() {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Is leaf 7, sub-leaf 1 reported, but empty?
+///
+/// Some virtual machines pass through leaf 7's max sub-leaf of 1, but
+/// return zeros for sub-leaf 1 itself. Every real CPU reporting the
+/// sub-leaf has at least one flag there, so an empty one on a CPU with
+/// AVX2 means the sub-leaf 1 features can't be trusted.
+#[must_use]
+pub fn leaf_7_1_unreliable() -> bool {
+    if !is_valid_leaf(LEAF_7) {
+        return false;
+    }
+
+    let sub_leaf_0 = x86_cpuid_count(LEAF_7, 0);
+    if sub_leaf_0.eax < 1 || !super::has_avx2() {
+        return false;
+    }
+
+    let sub_leaf_1 = x86_cpuid_count(LEAF_7, 1);
+
+    sub_leaf_1.eax == 0 && sub_leaf_1.ebx == 0 && sub_leaf_1.ecx == 0 && sub_leaf_1.edx == 0
+}
+
+#[must_use]
+pub fn max_hypervisor_leaf() -> u32 {
+    x86_cpuid(HYP_LEAF_0).eax
+}
+
+/// Returns the maximum extended CPUID leaf supported.
+#[must_use]
+pub fn max_extended_leaf() -> u32 {
+    x86_cpuid(EXT_LEAF_0).eax
+}
+
+/// Returns the highest leaf from `first` to `max` with any non-zero
+/// register in sub-leaf 0, or `first` if they are all zero.
+fn last_nonzero_leaf(first: u32, max: u32) -> u32 {
+    (first..=max)
+        .rev()
+        .find(|&leaf| x86_cpuid_count(leaf, 0) != Cpuid::default())
+        .unwrap_or(first)
+}
+
+/// Returns the highest basic leaf that actually returns data.
+///
+/// Some CPUs report a max leaf well past the last leaf they fill in, and
+/// return zeros for the rest. Leaves below this can still be empty.
+#[must_use]
+pub fn effective_max_leaf() -> u32 {
+    if !has_cpuid() {
+        return 0;
+    }
+
+    last_nonzero_leaf(LEAF_0, max_leaf())
+}
+
+/// Returns the highest extended leaf that actually returns data, like
+/// [`effective_max_leaf`].
+#[must_use]
+pub fn effective_max_extended_leaf() -> u32 {
+    let max = max_extended_leaf();
+    if max < EXT_LEAF_0 {
+        return max;
+    }
+
+    last_nonzero_leaf(EXT_LEAF_0, max)
+}
+
+/// Returns the maximum vendor-specific CPUID leaf, if one exists,
+/// otherwise returns the maximum extended leaf.
+#[must_use]
+pub fn max_vendor_leaf() -> u32 {
+    match &*vendor_str() {
+        VENDOR_CENTAUR => x86_cpuid(CENTAUR_LEAF_0).eax,
+        VENDOR_TRANSMETA => x86_cpuid(TRANSMETA_LEAF_0).eax,
+        _ => max_extended_leaf(),
+    }
+}
+
+/// Returns true if the given leaf is valid and supported by the CPU.
+///
+/// Checks whether the specified CPUID leaf is within the range supported by
+/// the processor (basic, extends, or vendor-specific).
+#[must_use]
+pub fn is_valid_leaf(leaf: u32) -> bool {
+    if !has_cpuid() {
+        return false;
+    }
+
+    // Optimization: Leaf 1 is always valid if CPUID is present.
+    // This avoids redundant max_leaf() -> x86_cpuid(0) calls for common features.
+    if leaf == LEAF_1 {
+        return true;
+    }
+
+    match leaf {
+        0..EXT_LEAF_0 => leaf <= max_leaf(),
+        EXT_LEAF_0..=EXT_LEAF_MAX => leaf <= max_extended_leaf(),
+        _ => leaf <= max_vendor_leaf(),
+    }
+}
+
+const CORE_TYPE_PERFORMANCE: u32 = 0x40;
+const CORE_TYPE_EFFICIENCY: u32 = 0x20;
+
+/// Decodes the core type of the current logical processor from CPUID
+/// leaf 0x1A (Hybrid Information), EAX bits 24-31.
+///
+/// Returns `None` on non-hybrid CPUs, where the leaf is absent or
+/// does not report a core type.
+#[must_use]
+pub fn core_type() -> Option<CoreType> {
+    if !is_valid_leaf(LEAF_1A) {
+        return None;
+    }
+
+    let res = x86_cpuid(LEAF_1A);
+    let core_type = (res.eax >> 24) & 0xFF;
+
+    match core_type {
+        CORE_TYPE_EFFICIENCY => Some(CoreType::Efficiency),
+        CORE_TYPE_PERFORMANCE => Some(CoreType::Performance),
+        _ => None,
+    }
+}
+
+/// Detects the CPU core type from CPUID leaf 0x1A (Hybrid Information).
+///
+/// This is the authoritative source for distinguishing P-cores from E-cores
+/// on Intel hybrid architectures (Alder Lake and later).
+/// Falls back to `CoreType::Performance` if leaf 0x1A is not available.
+#[must_use]
+pub fn core_type_from_cpuid() -> CoreType {
+    core_type().unwrap_or(CoreType::Performance)
+}
+
+/// Gets the CPU vendor ID string (e.g., "`GenuineIntel`", "`AuthenticAMD`").
+///
+/// Returns a 12-character vendor string from CPUID leaf 0.
+#[must_use]
+pub fn vendor_str() -> String {
+    #[cfg(target_arch = "x86")]
+    if !has_cpuid() {
+        return String::from(get_vendor_by_quirk());
+    }
+
+    raw_vendor_str(LEAF_0)
+}
+
+/// Identifies the CPU vendor with a single CPUID call (leaf 0).
+///
+/// Unlike [`CpuBrand::detect`], this skips the Cyrix/386/486 probing
+/// needed for CPUs without CPUID, which is pure overhead on modern CPUs.
+/// Those older CPUs are reported as `CpuBrand::Unknown`.
+#[must_use]
+pub fn quick_vendor() -> CpuBrand {
+    CpuBrand::from(raw_vendor_str(LEAF_0))
+}
+
+#[must_use]
+pub fn hypervisor_str() -> String {
+    if is_hypervisor_guest() {
+        let res = x86_cpuid(HYP_LEAF_0);
+        let mut bytes = [0u8; 12];
+
+        bytes[0..4].copy_from_slice(&res.ebx.to_le_bytes());
+        bytes[4..8].copy_from_slice(&res.ecx.to_le_bytes());
+        bytes[8..12].copy_from_slice(&res.edx.to_le_bytes());
+
+        let s = core::str::from_utf8(&bytes)
+            .unwrap_or(UNK)
+            .trim_matches('\0');
+
+        String::from(s)
+    } else {
+        String::from(UNK)
+    }
+}
+
+fn raw_vendor_str(leaf: u32) -> String {
+    let res = x86_cpuid(leaf);
+    let mut bytes = [0u8; 12];
+
+    bytes[0..4].copy_from_slice(&res.ebx.to_le_bytes());
+    bytes[4..8].copy_from_slice(&res.edx.to_le_bytes());
+    bytes[8..12].copy_from_slice(&res.ecx.to_le_bytes());
+
+    let s = core::str::from_utf8(&bytes)
+        .unwrap_or(UNK)
+        .trim_matches('\0');
+
+    String::from(s)
+}
+
+#[must_use]
+pub fn read_multi_leaf_str(min_leaf: u32, max_leaf: u32) -> String {
+    if !is_valid_leaf(max_leaf) {
+        return String::from(UNK);
+    }
+
+    let mut model = String::new();
+    for leaf in min_leaf..=max_leaf {
+        let res = x86_cpuid(leaf);
+        for reg in [res.eax, res.ebx, res.ecx, res.edx] {
+            let bytes = reg.to_le_bytes();
+            let s = core::str::from_utf8(&bytes).unwrap_or("");
+            model.push_str(s);
+        }
+    }
+
+    String::from(model.trim().trim_matches('\0'))
+}
+
+/// Returns the 48-byte brand string from leaves 0x8000_0002 to 0x8000_0004,
+/// exactly as the CPU reports it, including any padding and NUL bytes.
+///
+/// All zeros if the CPU doesn't have those leaves.
+#[must_use]
+pub fn raw_brand_bytes() -> [u8; 48] {
+    let mut bytes = [0u8; 48];
+    if !is_valid_leaf(EXT_LEAF_4) {
+        return bytes;
+    }
+
+    let regs = (EXT_LEAF_2..=EXT_LEAF_4).flat_map(|leaf| {
+        let res = x86_cpuid(leaf);
+        [res.eax, res.ebx, res.ecx, res.edx]
+    });
+    for (chunk, reg) in bytes.chunks_exact_mut(4).zip(regs) {
+        chunk.copy_from_slice(&reg.to_le_bytes());
+    }
+
+    bytes
+}
+
+/// Returns the nominal clock speed from the end of the brand string, in MHz.
+///
+/// Intel brand strings usually end with the frequency, like "@ 3.40GHz".
+/// Returns `None` if the brand string doesn't have one.
+#[must_use]
+pub fn brand_frequency_mhz() -> Option<u32> {
+    parse_brand_frequency(&read_multi_leaf_str(EXT_LEAF_2, EXT_LEAF_4))
+}
+
+/// Parses a trailing "X.YZGHz", "XXXMHz" or "X.YTHz" from a brand string,
+/// following the method from Intel's CPUID application note (AP-485).
+fn parse_brand_frequency(brand: &str) -> Option<u32> {
+    let value = brand.trim_end().strip_suffix("Hz")?;
+    let (value, multiplier) = match value.as_bytes().last()? {
+        b'M' => (&value[..value.len() - 1], 1),
+        b'G' => (&value[..value.len() - 1], 1_000),
+        b'T' => (&value[..value.len() - 1], 1_000_000),
+        _ => return None,
+    };
+
+    // The number is everything after the last space
+    let number = value.rsplit(' ').next()?;
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if whole.is_empty() || !is_digits(whole) || !is_digits(fraction) {
+        return None;
+    }
+
+    let mut mhz = whole.parse::<u32>().ok()?.checked_mul(multiplier)?;
+    let mut scale = multiplier;
+    for digit in fraction.bytes() {
+        scale /= 10;
+        mhz = mhz.checked_add(u32::from(digit - b'0') * scale)?;
+    }
+
+    Some(mhz).filter(|&mhz| mhz > 0)
+}
+
+fn is_vendor(v: &str) -> bool {
+    vendor_str() == v
+}
+
+/// Returns true if the CPU is from AMD.
+#[must_use]
+pub fn is_amd() -> bool {
+    is_vendor(VENDOR_AMD)
+}
+
+/// Returns true if the CPU is from Hygon, whose Dhyana is a licensed Zen.
+#[must_use]
+pub fn is_hygon() -> bool {
+    is_vendor(VENDOR_HYGON)
+}
+
+/// Returns true if the CPU is from AMD or Hygon, which share AMD's
+/// vendor-specific feature flags.
+#[must_use]
+pub fn is_amd_or_hygon() -> bool {
+    is_amd() || is_hygon()
+}
+
+/// Returns true if the CPU is from Centaur (IDT/VIA/Zhaoxin), including
+/// VIA CPUs that report the VIA vendor string.
+#[must_use]
+pub fn is_centaur() -> bool {
+    is_vendor(VENDOR_CENTAUR) || is_vendor(VENDOR_VIA)
+}
+
+/// Returns true if the CPU is from Cyrix.
+#[must_use]
+pub fn is_cyrix() -> bool {
+    is_vendor(VENDOR_CYRIX) || is_vendor(VENDOR_NSC)
+}
+
+/// Is the CPU a Vortex86 or very similar RDC chip?
+#[must_use]
+pub fn is_vortex() -> bool {
+    is_vendor(VENDOR_DMP) || is_vendor(VENDOR_RDC)
+}
+
+/// Returns true if the CPU is from Intel.
+#[must_use]
+pub fn is_intel() -> bool {
+    is_vendor(VENDOR_INTEL)
+}
+
+#[must_use]
+pub fn is_umc() -> bool {
+    is_vendor(VENDOR_UMC)
+}
+
+/// Returns true if the CPU is from Zhaoxin.
+#[must_use]
+pub fn is_zhaoxin() -> bool {
+    is_vendor(VENDOR_ZHAOXIN)
+}
+
+/// Returns true if the CPU is an Intel Overdrive processor.
+///
+/// Checks the Overdrive bit (EAX bit 12) in CPUID leaf 1.
+#[must_use]
+pub fn is_overdrive() -> bool {
+    (x86_cpuid(LEAF_1).eax & (1 << 12)) != 0
+}
+
+/// Returns the number of logical cores.
+#[must_use]
+pub fn amd_logical_cores() -> u32 {
+    // Since AMD has a handy flag for getting logical cores,
+    // try that first
+    if is_amd() || is_hygon() {
+        if let Some(layout) = amd_apic_id_layout()
+            && layout.threads > 1
+        {
+            return layout.threads;
+        }
+
+        if max_leaf() > 0 {
+            let res = x86_cpuid(1);
+            let count = (res.ebx >> 16) & 0xFF;
+            if count != 0 {
+                return count;
+            }
+        }
+    }
+
+    1
+}
+
+/// AMD thread count and APIC ID layout, from extended leaf 0x80000008 ECX.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct AmdApicIdLayout {
+    /// Logical processors per package (NC + 1)
+    pub threads: u32,
+    /// Number of low APIC ID bits that identify a thread within the package
+    pub apic_id_size: u32,
+}
+
+impl AmdApicIdLayout {
+    /// Splits an APIC ID into its package number and the thread's index
+    /// within the package.
+    #[must_use]
+    pub fn split_apic_id(&self, apic_id: u32) -> (u32, u32) {
+        let mask = (1u32 << self.apic_id_size) - 1;
+
+        (apic_id >> self.apic_id_size, apic_id & mask)
+    }
+}
+
+/// Returns the APIC ID layout of AMD CPUs with extended leaf 0x80000008.
+///
+/// CPUs from before ApicIdSize was defined report 0 for it, so the size
+/// is then the fewest bits that fit the thread count.
+#[must_use]
+pub fn amd_apic_id_layout() -> Option<AmdApicIdLayout> {
+    if !(is_amd() || is_hygon()) || !is_valid_leaf(EXT_LEAF_8) {
+        return None;
+    }
+
+    let ecx = x86_cpuid(EXT_LEAF_8).ecx;
+    let threads = (ecx & 0xFF) + 1;
+    let apic_id_size = match (ecx >> 12) & 0xF {
+        0 => u32::BITS - (threads - 1).leading_zeros(),
+        size => size,
+    };
+
+    Some(AmdApicIdLayout {
+        threads,
+        apic_id_size,
+    })
+}
+
+#[must_use]
+pub fn amd_threads_per_core() -> u32 {
+    if (is_amd() || is_hygon()) && is_valid_leaf(EXT_LEAF_1E) {
+        let res = x86_cpuid(EXT_LEAF_1E);
+        let count = (res.ebx >> 8) & 0xFF;
+
+        count + 1
+    } else {
+        1u32
+    }
+}
+
+/// Returns the AMD package (socket) type, from extended leaf 0x80000001 EBX
+/// bits 31:28 (PkgType).
+///
+/// The meaning of each value depends on the family and model, so this is
+/// `None` for models and values that aren't known, as well as for other
+/// vendors and for K8 parts before revision F.
+#[must_use]
+pub fn amd_package_type() -> Option<&'static str> {
+    if !is_amd() || !is_valid_leaf(EXT_LEAF_1) {
+        return None;
+    }
+
+    let pkg_type = x86_cpuid(EXT_LEAF_1).ebx >> 28;
+    let sig = CpuSignature::detect();
+
+    let name = match (sig.display_family, sig.display_model, pkg_type) {
+        // K8, revision F and later
+        (0x0F, 0x40.., 0) => "S1g1",
+        (0x0F, 0x40.., 1) => "F (1207)",
+        (0x0F, 0x40.., 3) => "AM2",
+
+        // K10
+        (0x10, _, 0) => "F (1207)",
+        (0x10, _, 1) => "AM2r2/AM3",
+        (0x10, _, 2) => "S1g3/S1g4",
+        (0x10, _, 3) => "G34",
+        (0x10, _, 4) => "ASB2",
+        (0x10, _, 5) => "C32",
+        (0x11, _, 2) => "S1g2",
+        (0x12, _, 1) => "FS1",
+        (0x12, _, 2) => "FM1",
+
+        // Bobcat
+        (0x14, _, 0) => "FT1",
+
+        // Bulldozer
+        (0x15, 0x00..=0x0F, 1) => "AM3+",
+        (0x15, 0x00..=0x0F, 3) => "G34",
+        (0x15, 0x00..=0x0F, 5) => "C32",
+        (0x15, 0x10..=0x1F, 0) => "FP2",
+        (0x15, 0x10..=0x1F, 1) => "FS1r2",
+        (0x15, 0x10..=0x1F, 2) => "FM2",
+        (0x15, 0x30..=0x3F, 0) => "FP3",
+        (0x15, 0x30..=0x3F, 1) => "FM2+",
+        (0x15, 0x60..=0x7F, 0) => "FP4",
+        (0x15, 0x60..=0x7F, 2) => "AM4",
+        (0x15, 0x60..=0x6F, 3) => "FM2+",
+        (0x15, 0x70..=0x7F, 4) => "FT4",
+
+        // Jaguar/Puma
+        (0x16, 0x00..=0x0F, 0) => "FT3",
+        (0x16, 0x00..=0x0F, 1) => "FS1b",
+        (0x16, 0x30..=0x3F, 0) => "FT3b",
+        (0x16, 0x30..=0x3F, 3) => "FP4",
+
+        // Zen/Zen+/Zen 2
+        (0x17, 0x00..=0x0F, 2) => "AM4",
+        (0x17, 0x00..=0x0F, 4) => "SP3",
+        (0x17, 0x10..=0x2F, 0) => "FP5",
+        (0x17, 0x10..=0x2F, 2) => "AM4",
+        (0x17, 0x30..=0x3F, 4) => "SP3",
+        (0x17, 0x30..=0x3F, 7) => "sTRX4",
+        (0x17, 0x60..=0x6F, 0) => "FP6",
+        (0x17, 0x60..=0x6F, 2) => "AM4",
+        (0x17, 0x70..=0x7F, 2) => "AM4",
+
+        // Zen 3/Zen 4
+        (0x19, 0x00..=0x0F, 4) => "SP3",
+        (0x19, 0x00..=0x0F, 7) => "sWRX8",
+        (0x19, 0x10..=0x1F, 4) => "SP5",
+        (0x19, 0x20..=0x2F, 2) => "AM4",
+        (0x19, 0x50..=0x5F, 0) => "FP6",
+        (0x19, 0x50..=0x5F, 2) => "AM4",
+        (0x19, 0x60..=0x6F, 0) => "AM5",
+        (0x19, 0xA0..=0xAF, 4) => "SP5",
+
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+/// Logical and physical core counts for one processor package.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct CoreCounts {
+    /// Logical processors (threads)
+    pub logical: u32,
+    /// Physical cores
+    pub physical: u32,
+}
+
+/// Returns the per-package core counts, read from the leaves the CPU vendor
+/// documents for them:
+///
+/// - AMD and Hygon: extended leaf 0x80000008 ECX, with threads per core from
+///   0x8000001E
+/// - Intel: leaf 0xB, falling back to leaf 4 EAX for the core count. Hybrid
+///   CPUs also read the core type of each logical processor, since their
+///   E-cores have one thread each
+/// - Others: leaf 1 EBX, without SMT
+#[must_use]
+pub fn core_counts() -> CoreCounts {
+    match CpuBrand::detect() {
+        // Hygon's Dhyana is a Zen, with the same leaves
+        CpuBrand::AMD | CpuBrand::Hygon => {
+            let logical = amd_logical_cores();
+
+            CoreCounts {
+                logical,
+                physical: (logical / amd_threads_per_core()).max(1),
+            }
+        }
+        CpuBrand::Intel => intel_core_counts(),
+        _ => {
+            let logical = legacy_logical_cores();
+
+            CoreCounts {
+                logical,
+                physical: logical,
+            }
+        }
+    }
+}
+
+fn intel_core_counts() -> CoreCounts {
+    if is_valid_leaf(LEAF_0B) {
+        let threads_per_core = x86_cpuid_count(LEAF_0B, 0).ebx & 0xFFFF;
+        let logical = x86_cpuid_count(LEAF_0B, 1).ebx & 0xFFFF;
+
+        #[cfg(not(dos))]
+        if logical > 0
+            && super::has_hybrid()
+            && let Some(physical) = hybrid_physical_cores(logical, threads_per_core)
+        {
+            return CoreCounts { logical, physical };
+        }
+
+        if logical > 0 {
+            return CoreCounts {
+                logical,
+                physical: logical / threads_per_core.max(1),
+            };
+        }
+    }
+
+    let (physical, logical) = intel_legacy_core_counts();
+
+    CoreCounts { logical, physical }
+}
+
+/// Counts the cores of a hybrid Intel CPU from the core type of each logical
+/// processor.
+///
+/// Leaf 0xB's threads per core is the SMT width of the P-cores, but the
+/// E-cores don't have Hyper-Threading, so only the P-core threads are divided
+/// by it. Returns `None` if fewer than `logical` processors can be
+/// enumerated, such as when the process is limited to some of them. Then,
+/// and on the DOS build, the core count is the thread count divided by the
+/// threads per core, which undercounts the E-cores.
+#[cfg(not(dos))]
+fn hybrid_physical_cores(logical: u32, threads_per_core: u32) -> Option<u32> {
+    let core_types = super::provider::on_each_logical_processor(|_| core_type_from_cpuid());
+    if core_types.len() != logical as usize {
+        return None;
+    }
+
+    let p_threads = core_types
+        .iter()
+        .filter(|&&core_type| core_type == CoreType::Performance)
+        .count() as u32;
+    let e_cores = logical - p_threads;
+
+    Some(p_threads.div_ceil(threads_per_core.max(1)) + e_cores)
+}
+
+/// Returns the (cores, threads) per package for Intel CPUs without leaf 0xB,
+/// such as Core 2 and early Nehalem.
+///
+/// The cores come from leaf 4 sub-leaf 0 EAX[31:26] + 1, or 1 without leaf
+/// 4, and the threads from leaf 1 EBX[23:16], the maximum number of
+/// addressable logical processor IDs. Their ratio is the threads per core.
+#[must_use]
+pub fn intel_legacy_core_counts() -> (u32, u32) {
+    let cores = if is_valid_leaf(LEAF_4) {
+        ((x86_cpuid_count(LEAF_4, 0).eax >> 26) & 0x3F) + 1
+    } else {
+        1
+    };
+
+    // A Pentium 4 has no leaf 0xB, and a Prescott's leaf 4 says one core.
+    // HTT is set on every Hyper-Threading capable part, so the thread
+    // count comes from leaf 1 EBX[23:16]. That's the maximum number of
+    // addressable logical processor IDs in the package, not the enabled
+    // count, so it can stay 2 with HT off in the BIOS.
+    (cores, legacy_logical_cores().max(cores))
+}
+
+/// Maximum number of addressable logical processor IDs from leaf 1
+/// EBX[23:16], only valid with HTT set
+fn legacy_logical_cores() -> u32 {
+    if !has_ht() {
+        return 1;
+    }
+
+    ((x86_cpuid(LEAF_1).ebx >> 16) & 0xFF).max(1)
+}
+
+/// Is LFENCE dispatch-serializing, i.e. does it wait for all earlier
+/// instructions to complete before later ones start?
+///
+/// This matters for RDTSC-based timing, where LFENCE is used to keep the
+/// timestamp read from being reordered. It can't be fully determined from
+/// CPUID, so this is a vendor and family based heuristic:
+///
+/// - Intel: always serializing
+/// - AMD: always serializing if 8000_0021h EAX bit 2 says so, or on families
+///   0Fh and 11h. On other families it is controlled by MSR C001_1029h, so
+///   the answer depends on what the OS has set.
+///
+/// Returns `None` when unknown, or when LFENCE (SSE2) is not supported.
+#[must_use]
+pub fn lfence_serializing() -> Option<bool> {
+    if !has_sse2() {
+        return None;
+    }
+
+    match &*vendor_str() {
+        VENDOR_INTEL => Some(true),
+        VENDOR_AMD => {
+            if is_valid_leaf(EXT_LEAF_21) && (x86_cpuid(EXT_LEAF_21).eax & (1 << 2)) != 0 {
+                return Some(true);
+            }
+
+            match CpuSignature::detect().display_family {
+                0x0F | 0x11 => Some(true),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+// ------------------------------------------------------------------------
+// ! Leaf 0000_0007h
+// ------------------------------------------------------------------------
+
+/// Intel speculation control and related features, from the EDX register
+/// of leaf 0x07.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct IntelSpecCtrl {
+    /// Indirect Branch Restricted Speculation and Indirect Branch
+    /// Prediction Barrier, which share a flag
+    pub ibrs_ibpb: bool,
+    /// Single Thread Indirect Branch Predictors
+    pub stibp: bool,
+    /// `IA32_FLUSH_CMD`, to flush the L1 data cache
+    pub l1d_flush: bool,
+    /// `IA32_ARCH_CAPABILITIES`, which lists the speculative execution
+    /// issues the CPU isn't affected by
+    pub arch_capabilities: bool,
+    /// `IA32_CORE_CAPABILITIES`, for model-specific core features
+    pub core_capabilities: bool,
+    /// Speculative Store Bypass Disable
+    pub ssbd: bool,
+    /// VERW clears the CPU buffers, for MDS mitigation
+    pub md_clear: bool,
+}
+
+/// Returns the Intel speculation control features from leaf 0x07.
+///
+/// Other vendors that report these flags, such as Zhaoxin, use the same
+/// layout. AMD reports its own in extended leaf 0x80000008 instead,
+/// see [`amd_security_features`].
+#[must_use]
+pub fn intel_spec_ctrl() -> IntelSpecCtrl {
+    if !is_valid_leaf(LEAF_7) {
+        return IntelSpecCtrl::default();
+    }
+
+    let edx = x86_cpuid(LEAF_7).edx;
+    let bit = |n: u32| edx & (1 << n) != 0;
+
+    IntelSpecCtrl {
+        ibrs_ibpb: bit(26),
+        stibp: bit(27),
+        l1d_flush: bit(28),
+        arch_capabilities: bit(29),
+        core_capabilities: bit(30),
+        ssbd: bit(31),
+        md_clear: bit(10),
+    }
+}
+
+// ------------------------------------------------------------------------
+// ! Leaf 8000_0008h
+// ------------------------------------------------------------------------
+
+/// Physical and linear address sizes supported by the CPU.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct AddressBits {
+    /// Maximum physical address width (MAXPHYADDR)
+    pub physical: u32,
+    /// Maximum linear address width
+    pub linear: u32,
+    /// 5-level paging is supported, so the OS can enable 57-bit linear
+    /// addresses. Whether it has is not visible from CPUID.
+    pub la57: bool,
+    /// Running under a hypervisor, which may clamp or otherwise virtualize
+    /// the reported values. This is advisory only.
+    pub virtualized: bool,
+}
+
+/// Returns the physical and linear address sizes from extended leaf 0x80000008.
+///
+/// If that leaf is not available, MAXPHYADDR is 36 bits for CPUs with
+/// PAE or PSE-36, and 32 bits otherwise.
+#[must_use]
+pub fn address_bits() -> AddressBits {
+    let virtualized = is_hypervisor_guest();
+    let la57 = has_la57();
+
+    if is_valid_leaf(EXT_LEAF_8) {
+        let res = x86_cpuid(EXT_LEAF_8);
+
+        return AddressBits {
+            physical: res.eax & 0xFF,
+            linear: (res.eax >> 8) & 0xFF,
+            la57,
+            virtualized,
+        };
+    }
+
+    AddressBits {
+        physical: if has_pae() || has_pse36() { 36 } else { 32 },
+        linear: 32,
+        la57,
+        virtualized,
+    }
+}
+
+/// AMD speculation control and related features, from the EBX register
+/// of extended leaf 0x80000008.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct AmdSecFeatures {
+    /// CLZERO instruction, to zero a cache line
+    pub clzero: bool,
+    /// Indirect Branch Prediction Barrier
+    pub ibpb: bool,
+    /// Indirect Branch Restricted Speculation
+    pub ibrs: bool,
+    /// Single Thread Indirect Branch Predictor
+    pub stibp: bool,
+    /// IBRS can be left on, and doesn't need to be set on every kernel entry
+    pub ibrs_always_on: bool,
+    /// STIBP can be left on
+    pub stibp_always_on: bool,
+    /// IBRS is preferred over software mitigations, like retpolines
+    pub ibrs_preferred: bool,
+    /// Speculative Store Bypass Disable, through `SPEC_CTRL`
+    pub ssbd: bool,
+    /// Speculative Store Bypass Disable, through `VIRT_SPEC_CTRL`
+    pub virt_ssbd: bool,
+    /// The CPU is not affected by Speculative Store Bypass
+    pub ssbd_not_required: bool,
+    /// Predictive Store Forwarding Disable
+    pub psfd: bool,
+    /// The CPU is not affected by Branch Type Confusion
+    pub btc_no: bool,
+    /// IBPB also clears the return address predictor
+    pub ibpb_ret: bool,
+}
+
+/// Returns the AMD speculation control features from extended leaf 0x80000008.
+///
+/// Returns the default, with nothing supported, for vendors other than AMD
+/// and Hygon, or if the leaf is not available. Intel reports these in leaf 7
+/// instead.
+#[must_use]
+pub fn amd_security_features() -> AmdSecFeatures {
+    if !is_amd_or_hygon() || !is_valid_leaf(EXT_LEAF_8) {
+        return AmdSecFeatures::default();
+    }
+
+    let ebx = x86_cpuid(EXT_LEAF_8).ebx;
+    let bit = |n: u32| ebx & (1 << n) != 0;
+
+    AmdSecFeatures {
+        clzero: bit(0),
+        ibpb: bit(12),
+        ibrs: bit(14),
+        stibp: bit(15),
+        ibrs_always_on: bit(16),
+        stibp_always_on: bit(17),
+        ibrs_preferred: bit(18),
+        ssbd: bit(24),
+        virt_ssbd: bit(25),
+        ssbd_not_required: bit(26),
+        psfd: bit(28),
+        btc_no: bit(29),
+        ibpb_ret: bit(30),
+    }
+}
+
+// ------------------------------------------------------------------------
+// ! Leaf 8000_001Fh
+// ------------------------------------------------------------------------
+
+/// AMD memory encryption support, for confidential computing.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct SevInfo {
+    /// Secure Memory Encryption, of the host's own memory
+    pub sme: bool,
+    /// Secure Encrypted Virtualization, of guest memory
+    pub sev: bool,
+    /// SEV with Encrypted State, covering guest registers too
+    pub sev_es: bool,
+    /// SEV with Secure Nested Paging, adding integrity protection
+    pub sev_snp: bool,
+    /// The page table bit that marks a page as encrypted
+    pub c_bit: u32,
+    /// How many encrypted guests can run at once
+    pub encrypted_guests: u32,
+}
+
+impl SevInfo {
+    /// Are any of the encryption features supported?
+    #[must_use]
+    pub fn is_supported(&self) -> bool {
+        self.sme || self.sev
+    }
+}
+
+/// Returns the AMD memory encryption support from extended leaf 0x8000001F.
+///
+/// Returns the default, with nothing supported, for vendors other than AMD
+/// and Hygon, or if the leaf is not available.
+#[must_use]
+pub fn amd_sev() -> SevInfo {
+    if !is_amd_or_hygon() || !is_valid_leaf(EXT_LEAF_1F) {
+        return SevInfo::default();
+    }
+
+    let res = x86_cpuid(EXT_LEAF_1F);
+
+    SevInfo {
+        sme: res.eax & 1 != 0,
+        sev: res.eax & (1 << 1) != 0,
+        sev_es: res.eax & (1 << 3) != 0,
+        sev_snp: res.eax & (1 << 4) != 0,
+        c_bit: res.ebx & 0x3F,
+        encrypted_guests: res.ecx,
+    }
+}
+
+// ------------------------------------------------------------------------
+// ! Leaf 0000_000Fh
+// ------------------------------------------------------------------------
+
+/// L3 cache monitoring details.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct L3Monitoring {
+    /// Highest RMID usable for L3 monitoring
+    pub max_rmid: u32,
+    /// Factor to convert monitoring counter values to bytes
+    pub conversion_factor: u32,
+    /// L3 occupancy monitoring
+    pub occupancy: bool,
+    /// Total memory bandwidth monitoring
+    pub total_bandwidth: bool,
+    /// Local memory bandwidth monitoring
+    pub local_bandwidth: bool,
+}
+
+/// Resource Director Technology monitoring support, from leaf 0x0F.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct RdtMonitoring {
+    /// Highest RMID of any resource type
+    pub max_rmid: u32,
+    /// L3 cache monitoring
+    pub l3: Option<L3Monitoring>,
+}
+
+/// Returns the supported Resource Director Technology monitoring features,
+/// or `None` if the CPU doesn't report RDT-M.
+#[must_use]
+pub fn rdt_monitoring() -> Option<RdtMonitoring> {
+    if !super::has_rdt_m() || !is_valid_leaf(LEAF_0F) {
+        return None;
+    }
+
+    let res = x86_cpuid_count(LEAF_0F, 0);
+
+    let l3 = if res.edx & (1 << 1) != 0 {
+        let l3 = x86_cpuid_count(LEAF_0F, 1);
+
+        Some(L3Monitoring {
+            max_rmid: l3.ecx,
+            conversion_factor: l3.ebx,
+            occupancy: l3.edx & (1 << 0) != 0,
+            total_bandwidth: l3.edx & (1 << 1) != 0,
+            local_bandwidth: l3.edx & (1 << 2) != 0,
+        })
+    } else {
+        None
+    };
+
+    Some(RdtMonitoring {
+        max_rmid: res.ebx,
+        l3,
+    })
+}
+
+// ------------------------------------------------------------------------
+// ! Leaf 0000_0010h
+// ------------------------------------------------------------------------
+
+/// Cache Allocation Technology details for one cache level.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct CacheAllocation {
+    /// Length of the capacity bitmask, in bits
+    pub cbm_length: u32,
+    /// Highest class of service number
+    pub max_cos: u32,
+}
+
+/// Memory Bandwidth Allocation details.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct BandwidthAllocation {
+    /// Maximum throttling value
+    pub max_throttle: u32,
+    /// Highest class of service number
+    pub max_cos: u32,
+    /// The delay values are linear
+    pub linear: bool,
+}
+
+/// Resource Director Technology allocation support, from leaf 0x10.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct RdtAllocation {
+    /// L3 Cache Allocation Technology
+    pub l3_cat: Option<CacheAllocation>,
+    /// L2 Cache Allocation Technology
+    pub l2_cat: Option<CacheAllocation>,
+    /// Memory Bandwidth Allocation
+    pub mba: Option<BandwidthAllocation>,
+}
+
+/// Returns the supported Resource Director Technology allocation features,
+/// or `None` if the CPU doesn't report RDT-A.
+#[must_use]
+pub fn rdt_allocation() -> Option<RdtAllocation> {
+    if !super::has_rdt_a() || !is_valid_leaf(LEAF_10) {
+        return None;
+    }
+
+    let supported = x86_cpuid_count(LEAF_10, 0).ebx;
+
+    let cat = |sub_leaf: u32| -> Option<CacheAllocation> {
+        if supported & (1 << sub_leaf) == 0 {
+            return None;
+        }
+
+        let res = x86_cpuid_count(LEAF_10, sub_leaf);
+
+        Some(CacheAllocation {
+            cbm_length: (res.eax & 0x1F) + 1,
+            max_cos: res.edx & 0xFFFF,
+        })
+    };
+
+    let mba = if supported & (1 << 3) != 0 {
+        let res = x86_cpuid_count(LEAF_10, 3);
+
+        Some(BandwidthAllocation {
+            max_throttle: (res.eax & 0xFFF) + 1,
+            max_cos: res.edx & 0xFFFF,
+            linear: res.ecx & (1 << 2) != 0,
+        })
+    } else {
+        None
+    };
+
+    Some(RdtAllocation {
+        l3_cat: cat(1),
+        l2_cat: cat(2),
+        mba,
+    })
+}
+
+// ------------------------------------------------------------------------
+// ! Leaf 0000_0006h
+// ------------------------------------------------------------------------
+
+/// Intel Thread Director support, from leaf 0x06.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct ThreadDirector {
+    /// Number of classes the hardware sorts threads into
+    pub classes: u32,
+    /// Hardware Feedback Interface, the per-core performance and efficiency
+    /// table the classes index into
+    pub hfi: bool,
+    /// HRESET can reset the classification history
+    pub hreset: bool,
+}
+
+/// Returns the Intel Thread Director support of a hybrid CPU, or `None`
+/// if the CPU isn't hybrid or doesn't have Thread Director.
+#[must_use]
+pub fn thread_director() -> Option<ThreadDirector> {
+    if !super::has_hybrid() || !is_valid_leaf(LEAF_6) {
+        return None;
+    }
+
+    let res = x86_cpuid(LEAF_6);
+    if res.eax & (1 << 23) == 0 {
+        return None;
+    }
+
+    Some(ThreadDirector {
+        classes: (res.ecx >> 8) & 0xFF,
+        hfi: res.eax & (1 << 19) != 0,
+        hreset: history_reset().is_some_and(|h| h.thread_director()),
+    })
+}
+
+// ------------------------------------------------------------------------
+// ! Leaf 0000_0014h
+// ------------------------------------------------------------------------
+
+/// Intel Processor Trace capabilities, from leaf 0x14.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct ProcessorTrace {
+    /// Tracing can be filtered by CR3, to follow one process
+    pub cr3_filter: bool,
+    /// Configurable PSB frequency, and cycle-accurate mode
+    pub cycle_accurate: bool,
+    /// Tracing can be filtered by IP range
+    pub ip_filter: bool,
+    /// Mini time counter packets
+    pub mtc: bool,
+    /// The PTWRITE instruction
+    pub ptwrite: bool,
+    /// Power event trace packets
+    pub power_event_trace: bool,
+    /// Output to a Table of Physical Addresses
+    pub topa: bool,
+    /// A ToPA table can have more than one output entry
+    pub topa_multi_entry: bool,
+    /// Output to a single contiguous memory range
+    pub single_range: bool,
+    /// Output to a trace transport subsystem
+    pub trace_transport: bool,
+    /// IP payloads are linear addresses, rather than effective addresses
+    pub lip: bool,
+    /// Number of configurable address ranges, for IP filtering
+    pub address_ranges: u32,
+}
+
+/// Returns the Intel Processor Trace capabilities.
+///
+/// Returns the default, with nothing supported, if the CPU doesn't have
+/// Processor Trace.
+#[must_use]
+pub fn processor_trace() -> ProcessorTrace {
+    if !super::has_pt() || !is_valid_leaf(LEAF_14) {
+        return ProcessorTrace::default();
+    }
+
+    let res = x86_cpuid_count(LEAF_14, 0);
+    let address_ranges = if res.eax >= 1 {
+        x86_cpuid_count(LEAF_14, 1).eax & 0x7
+    } else {
+        0
+    };
+
+    ProcessorTrace {
+        cr3_filter: res.ebx & 1 != 0,
+        cycle_accurate: res.ebx & (1 << 1) != 0,
+        ip_filter: res.ebx & (1 << 2) != 0,
+        mtc: res.ebx & (1 << 3) != 0,
+        ptwrite: res.ebx & (1 << 4) != 0,
+        power_event_trace: res.ebx & (1 << 5) != 0,
+        topa: res.ecx & 1 != 0,
+        topa_multi_entry: res.ecx & (1 << 1) != 0,
+        single_range: res.ecx & (1 << 2) != 0,
+        trace_transport: res.ecx & (1 << 3) != 0,
+        lip: res.ecx & (1 << 31) != 0,
+        address_ranges,
+    }
+}
+
+// ------------------------------------------------------------------------
+// ! Leaf 0000_001Fh
+// ------------------------------------------------------------------------
+
+/// Returns each level of the V2 extended topology leaf, 0x1F, as its level
+/// type and x2APIC ID shift, from the lowest level up.
+///
+/// The types are 1 for SMT, 2 for core, 3 for module, 4 for tile and 5 for
+/// die. The shift is how far to move the x2APIC ID right to get the ID of
+/// the next level up. Sub-leaves are read until the type is 0, for at most 8
+/// levels, in case a hypervisor never reports the end. Returns an empty
+/// list if the CPU doesn't have leaf 0x1F.
+#[must_use]
+pub fn leaf1f_levels() -> Vec<(u8, u8)> {
+    let mut levels = Vec::new();
+
+    if !is_valid_leaf(LEAF_1F) {
+        return levels;
+    }
+
+    for sub_leaf in 0..8 {
+        let res = x86_cpuid_count(LEAF_1F, sub_leaf);
+        let level_type = ((res.ecx >> 8) & 0xFF) as u8;
+
+        if level_type == 0 {
+            break;
+        }
+
+        levels.push((level_type, (res.eax & 0x1F) as u8));
+    }
+
+    levels
+}
+
+// ------------------------------------------------------------------------
+// ! Leaf 0000_0020h
+// ------------------------------------------------------------------------
+
+/// Processor history reset support, from leaf 0x20.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct HistoryReset {
+    /// Bitmap of the history that HRESET can reset, as enabled by
+    /// `IA32_HRESET_ENABLE`
+    pub capabilities: u32,
+}
+
+impl HistoryReset {
+    /// Can HRESET reset the Intel Thread Director history?
+    #[must_use]
+    pub fn thread_director(&self) -> bool {
+        self.capabilities & 1 != 0
+    }
+}
+
+/// Returns what the HRESET instruction can reset, or `None` if the CPU
+/// doesn't support HRESET.
+#[must_use]
+pub fn history_reset() -> Option<HistoryReset> {
+    if !super::has_hreset() || !is_valid_leaf(LEAF_20) {
+        return None;
+    }
+
+    Some(HistoryReset {
+        capabilities: x86_cpuid_count(LEAF_20, 0).ebx,
+    })
+}
+
+// ------------------------------------------------------------------------
+// ! Leaf 0000_0024h
+// ------------------------------------------------------------------------
+
+/// AVX10 support, from leaf 0x24.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Avx10Info {
+    /// AVX10 version, such as 1 for AVX10.1
+    pub version: u32,
+    /// Widest supported vector length, in bits: 128, 256 or 512
+    pub max_vl: u32,
+}
+
+/// Returns the AVX10 version and widest vector length, or `None` if the CPU
+/// doesn't support AVX10.
+///
+/// Leaf 0x24 EBX has the version in bits 7:0, and a bit for each vector
+/// length from bit 16 (128-bit) to 18 (512-bit).
+#[must_use]
+pub fn avx10() -> Option<Avx10Info> {
+    if !super::has_avx10() || !is_valid_leaf(LEAF_24) {
+        return None;
+    }
+
+    let res = x86_cpuid_count(LEAF_24, 0);
+    let version = res.ebx & 0xFF;
+    let max_vl = [(18, 512), (17, 256), (16, 128)]
+        .into_iter()
+        .find(|&(bit, _)| res.ebx & (1 << bit) != 0)
+        .map_or(0, |(_, vl)| vl);
+
+    if version == 0 {
+        return None;
+    }
+
+    Some(Avx10Info { version, max_vl })
+}
+
+// ------------------------------------------------------------------------
+// ! MSR 0xCE
+// ------------------------------------------------------------------------
+
+/// Intel bus clock for Sandy Bridge and later, in MHz
+const INTEL_BUS_CLOCK_MHZ: u32 = 100;
+
+/// Clock ratios decoded from Intel's `MSR_PLATFORM_INFO` (0xCE).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PlatformInfo {
+    /// Maximum non-turbo ratio (bits 15:8)
+    pub max_non_turbo_ratio: u32,
+    /// Maximum efficiency (minimum) ratio (bits 47:40)
+    pub min_ratio: u32,
+}
+
+impl PlatformInfo {
+    /// Decodes the raw value of `MSR_PLATFORM_INFO`.
+    #[must_use]
+    pub fn from_msr(value: u64) -> Self {
+        Self {
+            max_non_turbo_ratio: ((value >> 8) & 0xFF) as u32,
+            min_ratio: ((value >> 40) & 0xFF) as u32,
+        }
+    }
+
+    /// Base (maximum non-turbo) clock, in MHz
+    #[must_use]
+    pub fn base_clock_mhz(&self) -> u32 {
+        self.max_non_turbo_ratio * INTEL_BUS_CLOCK_MHZ
+    }
+
+    /// Minimum clock, in MHz
+    #[must_use]
+    pub fn min_clock_mhz(&self) -> u32 {
+        self.min_ratio * INTEL_BUS_CLOCK_MHZ
+    }
+}
+
+/// Reads the base and minimum clock ratios from `MSR_PLATFORM_INFO`.
+///
+/// Only attempted on Intel family 6 CPUs from Sandy Bridge (model 0x2A) on,
+/// which have the MSR and a 100MHz bus clock, when running in real mode.
+/// Silvermont and Airmont Atoms have a bus clock of 80 to 133MHz instead,
+/// given by `MSR_FSB_FREQ`, so they're skipped. Returns `None` otherwise.
+#[cfg(dos)]
+#[must_use]
+pub fn intel_platform_info() -> Option<PlatformInfo> {
+    if vendor_str() != VENDOR_INTEL
+        || !super::has_msr()
+        || detection_mode() == DetectionMode::CpuidOnly
+        || !is_privileged()
+    {
+        return None;
+    }
+
+    let sig = CpuSignature::detect();
+    if sig.display_family != 6 || sig.display_model < 0x2A {
+        return None;
+    }
+
+    // Silvermont: Bay Trail, Merrifield, Avoton, Moorefield and SoFIA.
+    // Airmont: Cherry Trail/Braswell and Lightning Mountain
+    if matches!(
+        sig.display_model,
+        0x37 | 0x4A | 0x4C | 0x4D | 0x5A | 0x5D | 0x75
+    ) {
+        return None;
+    }
+
+    // Safety: real mode is ring 0, and the CPU check above ensures the MSR
+    // exists
+    let info = PlatformInfo::from_msr(unsafe { read_msr(MSR_PLATFORM_INFO) });
+
+    if info.max_non_turbo_ratio == 0 {
+        None
+    } else {
+        Some(info)
+    }
+}
+
+// ------------------------------------------------------------------------
+// ! MSR 0x10A
+// ------------------------------------------------------------------------
+
+/// Reads `IA32_ARCH_CAPABILITIES`, whose bits say which speculative
+/// execution issues the CPU isn't affected by, or has mitigations for.
+///
+/// Returns `None` if the CPU doesn't have the MSR, or when not running in
+/// real mode, where it can't be read.
+#[cfg(dos)]
+#[must_use]
+pub fn intel_arch_capabilities() -> Option<u64> {
+    if !intel_spec_ctrl().arch_capabilities
+        || !super::has_msr()
+        || detection_mode() == DetectionMode::CpuidOnly
+        || !is_privileged()
+    {
+        return None;
+    }
+
+    // Safety: real mode is ring 0, and the CPUID flag means the MSR exists
+    Some(unsafe { read_msr(MSR_ARCH_CAPABILITIES) })
+}
+
+// ------------------------------------------------------------------------
+// ! MSR 0x981 and 0x982
+// ------------------------------------------------------------------------
+
+/// Intel Total Memory Encryption state, from `IA32_TME_CAPABILITY` and
+/// `IA32_TME_ACTIVATE`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct TmeInfo {
+    /// TME is turned on, encrypting all memory with one key
+    pub enabled: bool,
+    /// The settings are locked until reset
+    pub locked: bool,
+    /// Physical address bits given to multi-key (MKTME) key IDs. Zero if
+    /// only the single TME key is in use.
+    pub key_id_bits: u32,
+    /// The most MKTME keys the CPU supports
+    pub max_keys: u32,
+}
+
+impl TmeInfo {
+    /// Decodes the raw values of `IA32_TME_CAPABILITY` and `IA32_TME_ACTIVATE`.
+    #[must_use]
+    pub fn from_msrs(capability: u64, activate: u64) -> Self {
+        Self {
+            enabled: activate & (1 << 1) != 0,
+            locked: activate & 1 != 0,
+            key_id_bits: ((activate >> 32) & 0xF) as u32,
+            max_keys: ((capability >> 36) & 0x7FFF) as u32,
+        }
+    }
+
+    /// How many MKTME key IDs are usable, besides the TME key
+    #[must_use]
+    pub fn key_ids(&self) -> u32 {
+        if self.key_id_bits == 0 {
+            return 0;
+        }
+
+        ((1 << self.key_id_bits) - 1).min(self.max_keys)
+    }
+}
+
+/// Reads the Total Memory Encryption state from the TME MSRs.
+///
+/// Returns `None` if the CPU doesn't have TME, or when not running in real
+/// mode, where the MSRs can't be read.
+#[cfg(dos)]
+#[must_use]
+pub fn intel_tme() -> Option<TmeInfo> {
+    if !super::has_tme()
+        || !super::has_msr()
+        || detection_mode() == DetectionMode::CpuidOnly
+        || !is_privileged()
+    {
+        return None;
+    }
+
+    // Safety: real mode is ring 0, and the TME CPUID flag means both MSRs
+    // exist
+    let (capability, activate) =
+        unsafe { (read_msr(MSR_TME_CAPABILITY), read_msr(MSR_TME_ACTIVATE)) };
+
+    Some(TmeInfo::from_msrs(capability, activate))
+}
+
+// ------------------------------------------------------------------------
+// ! MSR 0xE1
+// ------------------------------------------------------------------------
+
+/// Returns the longest time UMWAIT and TPAUSE may wait, in TSC ticks, from
+/// `IA32_UMWAIT_CONTROL`.
+///
+/// The OS sets this limit, so that a user-mode wait can't hold the core
+/// forever. Zero means there is no limit. The MSR is only read on the
+/// bare-metal (DOS) build when running in real mode, so this returns `None`
+/// elsewhere, and when the CPU doesn't have WAITPKG.
+#[must_use]
+pub fn umwait_max_time() -> Option<u32> {
+    if !super::has_waitpkg() {
+        return None;
+    }
+
+    #[cfg(dos)]
+    {
+        if !super::has_msr() || detection_mode() == DetectionMode::CpuidOnly || !is_privileged() {
+            return None;
+        }
+
+        // Safety: real mode is ring 0, and the WAITPKG flag means the MSR
+        // exists
+        let control = unsafe { read_msr(MSR_UMWAIT_CONTROL) };
+
+        // Bit 0 disables the C0.2 state, bits 31:2 are the time limit
+        return Some(control as u32 & !0b11);
+    }
+
+    #[cfg(not(dos))]
+    None
+}
+
+// ------------------------------------------------------------------------
+// ! MSR 0xE7 and 0xE8
+// ------------------------------------------------------------------------
+
+/// Returns the ratio of the current core's actual clock to its base clock.
+///
+/// `IA32_MPERF` counts at the base clock and `IA32_APERF` at the actual
+/// clock, so the ratio of their increase over one BIOS timer tick (about
+/// 55ms) is the average over that time, including any turbo boost. Returns
+/// `None` if the CPU doesn't have the counters, or when not running in real
+/// mode, where they can't be read.
+#[cfg(dos)]
+#[must_use]
+pub fn current_frequency_ratio() -> Option<f32> {
+    use super::dos::peek_u16;
+
+    if !super::has_msr()
+        || !super::has_aperf_mperf()
+        || detection_mode() == DetectionMode::CpuidOnly
+        || !is_privileged()
+    {
+        return None;
+    }
+
+    // Start on a fresh tick, for a full interval
+    let start = peek_u16(0x0040, 0x006C);
+    while peek_u16(0x0040, 0x006C) == start {}
+
+    // Safety: real mode is ring 0, and CPUID leaf 6 says the counters exist
+    let (mperf, aperf) = unsafe { (read_msr(MSR_MPERF), read_msr(MSR_APERF)) };
+
+    let tick = peek_u16(0x0040, 0x006C);
+    while peek_u16(0x0040, 0x006C) == tick {}
+
+    let (mperf_end, aperf_end) = unsafe { (read_msr(MSR_MPERF), read_msr(MSR_APERF)) };
+
+    let mperf_delta = mperf_end.wrapping_sub(mperf);
+    let aperf_delta = aperf_end.wrapping_sub(aperf);
+    if mperf_delta == 0 {
+        return None;
+    }
+
+    Some(aperf_delta as f32 / mperf_delta as f32)
+}
+
+/// Returns the current core's actual clock, in MHz.
+///
+/// The ratio from [`current_frequency_ratio`] times the base clock, from
+/// `MSR_PLATFORM_INFO` or CPUID leaf 16h.
+#[cfg(dos)]
+#[must_use]
+pub fn current_frequency_mhz() -> Option<u32> {
+    let ratio = current_frequency_ratio()?;
+
+    let base = match intel_platform_info() {
+        Some(info) => info.base_clock_mhz(),
+        None if is_valid_leaf(LEAF_16) => x86_cpuid(LEAF_16).eax,
+        None => 0,
+    };
+    if base == 0 {
+        return None;
+    }
+
+    Some((base as f32 * ratio + 0.5) as u32)
+}
+
+/// Returns the name of each known flag in the given leaf and sub-leaf, as
+/// (register, bit, name), sorted by register and bit.
+///
+/// Names come from [`FEATURE_SPECS`](super::FEATURE_SPECS), then
+/// [`UNLISTED_FEATURE_SPECS`](super::UNLISTED_FEATURE_SPECS). Vendor-specific
+/// flags are only included for the current vendor, so the result matches the
+/// active CPUID provider. AVX-512 and AMX flags keep their short names from
+/// the feature list, such as "F" for AVX-512 Foundation.
+#[cfg(not(dos))]
+#[must_use]
+pub fn describe_leaf(leaf: u32, sub_leaf: u32) -> Vec<(Reg, u32, &'static str)> {
+    let mut bits: Vec<(Reg, u32, &'static str)> = super::FEATURE_SPECS
+        .iter()
+        .chain(super::UNLISTED_FEATURE_SPECS.iter())
+        .filter(|spec| spec.leaf == leaf && spec.sub_leaf == sub_leaf)
+        .filter(|spec| spec.vendor.is_none_or(|check| check()))
+        .map(|spec| (spec.reg, spec.bit, spec.name))
+        .collect();
+
+    bits.sort_by_key(|&(reg, bit, _)| (reg, bit));
+
+    bits
+}
+
+// ------------------------------------------------------------------------
+// ! Leaf 0000_0001h
+// ------------------------------------------------------------------------
+
+/// Returns the CPU brand ID from basic leaf 0x00000001.
+///
+/// The brand ID is a value that identifies the specific CPU brand variant.
+#[must_use]
+pub fn get_brand_id() -> u32 {
+    let res = x86_cpuid(LEAF_1);
+    res.ebx & 0xFF
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpuid::provider::CpuDump;
+    use crate::cpuid::{has_popcnt, vendor_str};
+
+    #[test]
+    fn test_current_mode() {
+        #[cfg(target_arch = "x86_64")]
+        assert_eq!(current_mode(), CpuMode::Long);
+
+        let mode: &str = current_mode().into();
+        assert!(["Real", "Protected", "Long"].contains(&mode));
+    }
+
+    #[test]
+    fn test_xcr0() {
+        // x87 state is always enabled
+        if crate::cpuid::has_osxsave() && probes_enabled() {
+            assert!(xcr0().is_some_and(|xcr0| xcr0 & 1 == 1));
+        } else {
+            assert_eq!(xcr0(), None);
+        }
+    }
+
+    #[test]
+    fn test_detection_mode() {
+        assert_eq!(detection_mode(), DetectionMode::Full);
+
+        with_detection_mode(DetectionMode::CpuidOnly, || {
+            assert_eq!(detection_mode(), DetectionMode::CpuidOnly);
+            assert!(!probes_enabled());
+            assert_eq!(xcr0(), None);
+            assert!(!crate::common::Speed::detect().measured);
+        });
+
+        assert_eq!(detection_mode(), DetectionMode::Full);
+    }
+
+    #[test]
+    fn test_describe_leaf() {
+        let bits = describe_leaf(LEAF_1, 0);
+        assert!(bits.contains(&(Reg::Ecx, 28, "AVX")));
+        assert!(bits.contains(&(Reg::Edx, 5, "MSR")));
+        assert!(bits.is_sorted_by_key(|&(reg, bit, _)| (reg, bit)));
+
+        // No two names for the same bit
+        assert!(
+            bits.windows(2)
+                .all(|w| (w[0].0, w[0].1) != (w[1].0, w[1].1))
+        );
+
+        assert!(describe_leaf(LEAF_2, 0).is_empty());
+    }
+
+    #[test]
+    fn test_parse_brand_frequency() {
+        let brand = parse_brand_frequency;
+        assert_eq!(brand("Intel(R) Core(TM) i7-3770 CPU @ 3.40GHz"), Some(3400));
+        assert_eq!(
+            brand("Intel(R) Core(TM) i7 CPU         920  @ 2.67GHz"),
+            Some(2670)
+        );
+        assert_eq!(brand("Intel(R) Pentium(R) 4 CPU 1500MHz"), Some(1500));
+        assert_eq!(
+            brand("Intel(R) Xeon(R) CPU E5-2680 0 @ 2.70GHz  "),
+            Some(2700)
+        );
+        assert_eq!(brand("Imaginary CPU @ 1.5THz"), Some(1_500_000));
+
+        assert_eq!(brand("AMD Ryzen 9 5900XT 16-Core Processor"), None);
+        assert_eq!(brand("12th Gen Intel(R) Core(TM) i7-12700H"), None);
+        assert_eq!(brand("Broken CPU @ 1.2.3GHz"), None);
+        assert_eq!(brand("Broken CPU @ GHz"), None);
+        assert_eq!(brand("Broken CPU @ 0.00GHz"), None);
+        assert_eq!(brand(""), None);
+    }
+
+    #[test]
+    fn test_serialize() {
+        // Either path should just return
+        serialize();
+        serialize();
+    }
+
+    #[test]
+    fn test_popcnt_u64() {
+        let values = [
+            0,
+            1,
+            0x8000_0000_0000_0000,
+            0x5555_5555_5555_5555,
+            0x0123_4567_89AB_CDEF,
+            u64::from(u32::MAX),
+            u64::MAX,
+        ];
+
+        for x in values {
+            assert_eq!(popcnt_sw(x), x.count_ones());
+            assert_eq!(popcnt_u64(x), x.count_ones());
+            if has_popcnt() && probes_enabled() {
+                assert_eq!(unsafe { popcnt_hw(x) }, popcnt_sw(x));
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_cpuid_result_for_cpu_info() {
         let cpuid_result = CpuidResult {
             eax: 10,
             ebx: 20,
@@ -391,6 +2346,60 @@ mod tests {
         assert_eq!(cpu_info.edx, 40);
     }
 
+    #[test]
+    fn test_cpuid_unsupported() {
+        let dump = CpuDump::parse(
+            "0x00000000 0x00: eax=0x00000020 ebx=0x756E6547 ecx=0x6C65746E edx=0x49656E69\n\
+             0x00000001 0x00: eax=0x000906A3 ebx=0x00800800 ecx=0x7FFAFBBF edx=0xBFEBFBFF\n",
+        )
+        .expect("Failed to parse dump");
+        let cpuid = |leaf, sub_leaf| dump.get(leaf, sub_leaf);
+
+        assert_eq!(
+            checked_cpuid_count(false, LEAF_0, 0, cpuid),
+            Cpuid::default()
+        );
+        assert_eq!(
+            checked_cpuid_count(false, LEAF_1, 0, cpuid),
+            Cpuid::default()
+        );
+        assert_eq!(
+            checked_cpuid_count(true, LEAF_0, 0, cpuid),
+            Cpuid {
+                eax: 0x20,
+                ebx: 0x756E_6547,
+                ecx: 0x6C65_746E,
+                edx: 0x4965_6E69,
+            }
+        );
+        assert_eq!(checked_cpuid_count(true, LEAF_1, 0, cpuid).eax, 0x0009_06A3);
+    }
+
+    #[test]
+    fn test_platform_info_from_msr() {
+        // i7-2600: 34x non-turbo, 16x minimum
+        let info = PlatformInfo::from_msr(0x0000_1000_0000_2200);
+        assert_eq!(info.max_non_turbo_ratio, 34);
+        assert_eq!(info.min_ratio, 16);
+        assert_eq!(info.base_clock_mhz(), 3400);
+        assert_eq!(info.min_clock_mhz(), 1600);
+    }
+
+    #[test]
+    fn test_tme_info_from_msrs() {
+        // TME and MKTME on, with 6 key ID bits, and up to 63 keys
+        let info = TmeInfo::from_msrs(0x0000_03F6_0000_0001, 0x0000_0006_0000_0003);
+        assert!(info.enabled);
+        assert!(info.locked);
+        assert_eq!(info.key_id_bits, 6);
+        assert_eq!(info.max_keys, 63);
+        assert_eq!(info.key_ids(), 63);
+
+        // TME on, without MKTME
+        let info = TmeInfo::from_msrs(0x0000_03F6_0000_0001, 0x3);
+        assert_eq!(info.key_ids(), 0);
+    }
+
     #[test]
     fn test_vendor_str() {
         let vendor = vendor_str();
@@ -0,0 +1,110 @@
+//! `rep movsb` memory copies, used for the bare-metal `memcpy`/`memmove`.
+//!
+//! `rep movsb` is available on every x86 CPU, and is the fast path on CPUs
+//! with ERMS (see [`has_erms`](super::has_erms)). Even without ERMS, it beats
+//! a byte-at-a-time loop.
+
+/// Copies `n` bytes from `src` to `dst`, front to back.
+///
+/// # Safety
+///
+/// Same requirements as [`core::ptr::copy_nonoverlapping`]. On the DOS
+/// build, both pointers must be in the data segment (ES = DS).
+#[inline]
+pub unsafe fn copy_forward(dst: *mut u8, src: *const u8, n: usize) {
+    unsafe {
+        core::arch::asm!(
+        "rep movsb",
+        inout("ecx") n => _,
+        inout("edi") dst => _,
+        inout("esi") src => _,
+        options(nostack, preserves_flags),
+        );
+    }
+}
+
+/// Copies `n` bytes from `src` to `dst`, back to front.
+///
+/// # Safety
+///
+/// Same requirements as [`copy_forward`], except that the ranges may overlap.
+#[inline]
+pub unsafe fn copy_backward(dst: *mut u8, src: *const u8, n: usize) {
+    if n == 0 {
+        return;
+    }
+
+    unsafe {
+        core::arch::asm!(
+        "std",
+        "rep movsb",
+        "cld",
+        inout("ecx") n => _,
+        inout("edi") dst.add(n - 1) => _,
+        inout("esi") src.add(n - 1) => _,
+        options(nostack),
+        );
+    }
+}
+
+/// Copies `n` bytes from `src` to `dst`, where the ranges may overlap.
+///
+/// # Safety
+///
+/// Same requirements as [`core::ptr::copy`]. On the DOS build, both pointers
+/// must be in the data segment (ES = DS).
+#[inline]
+pub unsafe fn copy(dst: *mut u8, src: *const u8, n: usize) {
+    // A forward copy is only a problem if the destination starts inside the source
+    if (dst as usize).wrapping_sub(src as usize) >= n {
+        unsafe { copy_forward(dst, src, n) };
+    } else {
+        unsafe { copy_backward(dst, src, n) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_forward() {
+        let src = *b"rep movsb";
+        let mut dst = [0u8; 9];
+        unsafe { copy_forward(dst.as_mut_ptr(), src.as_ptr(), src.len()) };
+        assert_eq!(dst, src);
+    }
+
+    #[test]
+    fn test_copy_zero_length() {
+        let src = [1u8; 4];
+        let mut dst = [0u8; 4];
+        unsafe {
+            copy_forward(dst.as_mut_ptr(), src.as_ptr(), 0);
+            copy_backward(dst.as_mut_ptr(), src.as_ptr(), 0);
+            copy(dst.as_mut_ptr(), src.as_ptr(), 0);
+        }
+        assert_eq!(dst, [0; 4]);
+    }
+
+    #[test]
+    fn test_copy_overlapping() {
+        // Destination after source
+        let mut buf = *b"0123456789";
+        let p = buf.as_mut_ptr();
+        unsafe { copy(p.add(2), p, 6) };
+        assert_eq!(&buf, b"0101234589");
+
+        // Destination before source
+        let mut buf = *b"0123456789";
+        let p = buf.as_mut_ptr();
+        unsafe { copy(p, p.add(2), 6) };
+        assert_eq!(&buf, b"2345676789");
+
+        // Same place
+        let mut buf = *b"0123456789";
+        let p = buf.as_mut_ptr();
+        unsafe { copy(p, p, 10) };
+        assert_eq!(&buf, b"0123456789");
+    }
+}
@@ -6,10 +6,14 @@
 use super::constants::*;
 use super::vendor::TMicroArch;
 use super::vendor::*;
-use super::{CpuBrand, CpuSignature, is_centaur, is_zhaoxin};
+use super::{
+    CpuBrand, CpuSignature, FeatureNameStyle, FeatureSet, FeatureSpec, is_centaur, is_zhaoxin,
+};
 #[cfg(test)]
 use crate::common::DataSource;
+use alloc::format;
 use alloc::string::String;
+use alloc::vec::Vec;
 
 /// CPU Microarchitecture enumeration.
 ///
@@ -319,6 +323,281 @@ impl MicroArch {
             MicroArch::U5D => "U5D",
         }
     }
+
+    /// Features that every CPU of this microarchitecture has.
+    ///
+    /// Comparing these with the detected features shows what has been
+    /// disabled, by firmware or a hypervisor. Product segmentation also
+    /// removes features: Intel's Pentium and Celeron models lack AVX before
+    /// Ice Lake, and some Ice Lake client parts lack AVX-512.
+    #[must_use]
+    pub fn baseline_features(self) -> FeatureSet {
+        feature_set(self.baseline())
+    }
+
+    /// Features from [`baseline_features`](Self::baseline_features) that
+    /// the current CPU doesn't report.
+    #[must_use]
+    pub fn missing_baseline_features(self) -> FeatureSet {
+        self.baseline_features().difference(FeatureSet::detect())
+    }
+
+    /// Feature bits in `features` that contradict this microarchitecture.
+    ///
+    /// Flags baseline features from before AVX that aren't reported, since
+    /// product segmentation only removes AVX and later, along with tracked
+    /// features that no CPU of this microarchitecture has, such as AVX2 on
+    /// Sandy Bridge. Either points to a misdetected model, or an emulator
+    /// reporting a mismatched set of features. Microarchitectures where the
+    /// feature set varies too much, like Skylake (client vs server), are
+    /// only checked against the baseline.
+    #[must_use]
+    pub fn feature_contradictions(self, features: FeatureSet) -> Vec<String> {
+        let name = self.as_str();
+        let feature = |spec: &FeatureSpec| spec.name_styled(FeatureNameStyle::Canonical);
+
+        let mut contradictions: Vec<String> = self
+            .baseline_features()
+            .difference(feature_set(&[AVX, AVX2, AVX512]))
+            .difference(features)
+            .iter()
+            .map(|spec| {
+                format!(
+                    "{} not reported, but every {name} CPU has it",
+                    feature(spec)
+                )
+            })
+            .collect();
+
+        if let Some(ceiling) = self.ceiling() {
+            contradictions.extend(
+                feature_set(X86_64_V4)
+                    .intersection(features)
+                    .difference(feature_set(ceiling))
+                    .iter()
+                    .map(|spec| format!("{} reported, but no {name} CPU has it", feature(spec))),
+            );
+        }
+
+        contradictions
+    }
+
+    fn baseline(self) -> &'static [Baseline] {
+        match self {
+            // AMD
+            MicroArch::K5 => &[PENTIUM],
+            MicroArch::K6 => &[PENTIUM, MMX],
+            MicroArch::K7 => &[PENTIUM, CMOV, MMX],
+            MicroArch::K8 => P6_SSE2,
+            MicroArch::K10 => P6_SSE3,
+            MicroArch::Bobcat => P6_SSSE3,
+            MicroArch::Bulldozer
+            | MicroArch::Piledriver
+            | MicroArch::Steamroller
+            | MicroArch::Jaguar
+            | MicroArch::Puma2014 => X86_AVX,
+            MicroArch::Excavator
+            | MicroArch::Zen
+            | MicroArch::ZenPlus
+            | MicroArch::Zen2
+            | MicroArch::Zen3
+            | MicroArch::Zen3Plus => X86_64_V3,
+            MicroArch::Zen4 | MicroArch::Zen4C | MicroArch::Zen5 | MicroArch::Zen5C => X86_64_V4,
+
+            // Intel
+            MicroArch::P5 => &[PENTIUM],
+            MicroArch::PentiumPro => I686,
+            MicroArch::PentiumII => &[PENTIUM, CMOV, MMX],
+            MicroArch::PentiumIII => &[PENTIUM, CMOV, MMX, SSE],
+            MicroArch::Willamette | MicroArch::Northwood | MicroArch::Dothan => P6_SSE2,
+            MicroArch::Prescott | MicroArch::CedarMill | MicroArch::Yonah => P6_SSE3,
+            MicroArch::Core | MicroArch::Merom | MicroArch::Bonnel | MicroArch::Saltwell => {
+                P6_SSSE3
+            }
+            MicroArch::Penryn | MicroArch::Dunnington => {
+                &[PENTIUM, CMOV, MMX, SSE, SSE2, SSE3, SSSE3, SSE41]
+            }
+            MicroArch::Nehalem
+            | MicroArch::Westmere
+            | MicroArch::Silvermont
+            | MicroArch::Airmont
+            | MicroArch::Goldmont
+            | MicroArch::GoldmontPlus
+            | MicroArch::Tremont => X86_64_V2,
+            MicroArch::SandyBridge | MicroArch::IvyBridge => X86_AVX,
+            MicroArch::Haswell
+            | MicroArch::Broadwell
+            | MicroArch::Skylake
+            | MicroArch::KabyLake
+            | MicroArch::AmberLake
+            | MicroArch::CoffeeLake
+            | MicroArch::WhiskyLake
+            | MicroArch::CometLake
+            | MicroArch::AlderLake
+            | MicroArch::GoldenCove
+            | MicroArch::Gracemont
+            | MicroArch::RaptorLake
+            | MicroArch::RaptorCove
+            | MicroArch::MeteorLake
+            | MicroArch::RedwoodCove
+            | MicroArch::Crestmont
+            | MicroArch::ArrowLake
+            | MicroArch::LionCove
+            | MicroArch::Skymont
+            | MicroArch::LunarLake
+            | MicroArch::PantherLake
+            | MicroArch::CougarCove
+            | MicroArch::Darkmont => X86_64_V3,
+            MicroArch::CascadeLake
+            | MicroArch::PalmCove
+            | MicroArch::IcyLake
+            | MicroArch::SunnyCove
+            | MicroArch::TigerLake
+            | MicroArch::SapphireRapids => X86_64_V4,
+
+            _ => &[],
+        }
+    }
+
+    /// The tracked features that any CPU of this microarchitecture can have,
+    /// when they're consistent across the whole microarchitecture
+    fn ceiling(self) -> Option<&'static [Baseline]> {
+        let ceiling: &[Baseline] = match self {
+            // AMD
+            MicroArch::K5 => &[PENTIUM],
+            MicroArch::K6 => &[PENTIUM, MMX],
+            MicroArch::K7 => &[PENTIUM, CMOV, MMX, SSE],
+            MicroArch::K8 => P6_SSE3,
+            MicroArch::K10 => &[PENTIUM, CMOV, MMX, SSE, SSE2, SSE3, POPCNT],
+            MicroArch::Bobcat => &[PENTIUM, CMOV, MMX, SSE, SSE2, SSE3, SSSE3, POPCNT],
+            MicroArch::Bulldozer
+            | MicroArch::Piledriver
+            | MicroArch::Steamroller
+            | MicroArch::Jaguar
+            | MicroArch::Puma2014 => &[
+                PENTIUM, CMOV, MMX, SSE, SSE2, SSE3, SSSE3, SSE41, SSE42, AVX, FMA_BMI1,
+            ],
+            MicroArch::Excavator
+            | MicroArch::Zen
+            | MicroArch::ZenPlus
+            | MicroArch::Zen2
+            | MicroArch::Zen3
+            | MicroArch::Zen3Plus => X86_64_V3,
+
+            // Intel
+            MicroArch::P5 => &[PENTIUM, MMX],
+            MicroArch::PentiumPro => I686,
+            MicroArch::PentiumII => &[PENTIUM, CMOV, MMX],
+            MicroArch::PentiumIII => &[PENTIUM, CMOV, MMX, SSE],
+            MicroArch::Willamette | MicroArch::Northwood | MicroArch::Dothan => P6_SSE2,
+            MicroArch::Prescott | MicroArch::CedarMill | MicroArch::Yonah => P6_SSE3,
+            MicroArch::Bonnel | MicroArch::Saltwell => P6_SSSE3,
+            // Penryn models are also detected as Core
+            MicroArch::Core | MicroArch::Merom | MicroArch::Penryn | MicroArch::Dunnington => {
+                &[PENTIUM, CMOV, MMX, SSE, SSE2, SSE3, SSSE3, SSE41]
+            }
+            MicroArch::Nehalem
+            | MicroArch::Westmere
+            | MicroArch::Silvermont
+            | MicroArch::Airmont
+            | MicroArch::Goldmont
+            | MicroArch::GoldmontPlus
+            | MicroArch::Tremont => X86_64_V2,
+            MicroArch::SandyBridge | MicroArch::IvyBridge => X86_AVX,
+            MicroArch::Haswell
+            | MicroArch::Broadwell
+            | MicroArch::KabyLake
+            | MicroArch::AmberLake
+            | MicroArch::CoffeeLake
+            | MicroArch::WhiskyLake
+            | MicroArch::CometLake
+            | MicroArch::Gracemont
+            | MicroArch::Crestmont
+            | MicroArch::Skymont => X86_64_V3,
+
+            _ => return None,
+        };
+
+        Some(ceiling)
+    }
+
+    /// The LLVM `-C target-cpu` name for this microarchitecture, if LLVM has one
+    pub fn llvm_target_cpu(self) -> Option<&'static str> {
+        let name = match self {
+            // AMD
+            MicroArch::K6 => "k6",
+            MicroArch::K7 => "athlon",
+            MicroArch::K8 => "k8",
+            MicroArch::K10 => "amdfam10",
+            MicroArch::Bobcat => "btver1",
+            MicroArch::Jaguar | MicroArch::Puma2014 => "btver2",
+            MicroArch::Bulldozer => "bdver1",
+            MicroArch::Piledriver => "bdver2",
+            MicroArch::Steamroller => "bdver3",
+            MicroArch::Excavator => "bdver4",
+            MicroArch::Zen | MicroArch::ZenPlus => "znver1",
+            MicroArch::Zen2 => "znver2",
+            MicroArch::Zen3 | MicroArch::Zen3Plus => "znver3",
+            MicroArch::Zen4 | MicroArch::Zen4C => "znver4",
+            MicroArch::Zen5 | MicroArch::Zen5C => "znver5",
+
+            // Centaur
+            MicroArch::Winchip => "winchip-c6",
+            MicroArch::Winchip2 | MicroArch::Winchip2A | MicroArch::Winchip2B => "winchip2",
+            MicroArch::Samuel2 | MicroArch::Ezra | MicroArch::EzraT => "c3",
+            MicroArch::Nehemiah | MicroArch::NehemiahP => "c3-2",
+
+            // Cyrix/NatSemi
+            MicroArch::Geode => "geode",
+
+            // Intel
+            MicroArch::I486 => "i486",
+            MicroArch::P5 => "pentium",
+            MicroArch::PentiumPro => "pentiumpro",
+            MicroArch::PentiumII => "pentium2",
+            MicroArch::PentiumIII => "pentium3",
+            MicroArch::Willamette | MicroArch::Northwood => "pentium4",
+            MicroArch::Prescott | MicroArch::CedarMill => "prescott",
+            MicroArch::Dothan => "pentium-m",
+            MicroArch::Yonah => "yonah",
+            MicroArch::Core | MicroArch::Merom => "core2",
+            MicroArch::Penryn | MicroArch::Dunnington => "penryn",
+            MicroArch::Nehalem => "nehalem",
+            MicroArch::Westmere => "westmere",
+            MicroArch::SandyBridge => "sandybridge",
+            MicroArch::IvyBridge => "ivybridge",
+            MicroArch::Haswell => "haswell",
+            MicroArch::Broadwell => "broadwell",
+            MicroArch::Skylake
+            | MicroArch::KabyLake
+            | MicroArch::AmberLake
+            | MicroArch::CoffeeLake
+            | MicroArch::WhiskyLake
+            | MicroArch::CometLake => "skylake",
+            MicroArch::CascadeLake => "cascadelake",
+            MicroArch::PalmCove => "cannonlake",
+            MicroArch::IcyLake | MicroArch::SunnyCove => "icelake-client",
+            MicroArch::TigerLake => "tigerlake",
+            MicroArch::SapphireRapids => "sapphirerapids",
+            MicroArch::AlderLake | MicroArch::GoldenCove | MicroArch::Gracemont => "alderlake",
+            MicroArch::RaptorLake | MicroArch::RaptorCove => "raptorlake",
+            MicroArch::MeteorLake | MicroArch::RedwoodCove | MicroArch::Crestmont => "meteorlake",
+            MicroArch::ArrowLake | MicroArch::LionCove | MicroArch::Skymont => "arrowlake",
+            MicroArch::LunarLake => "lunarlake",
+            MicroArch::PantherLake | MicroArch::CougarCove | MicroArch::Darkmont => "pantherlake",
+            MicroArch::Bonnel | MicroArch::Saltwell => "bonnell",
+            MicroArch::Silvermont | MicroArch::Airmont => "silvermont",
+            MicroArch::Goldmont => "goldmont",
+            MicroArch::GoldmontPlus => "goldmont-plus",
+            MicroArch::Tremont => "tremont",
+            MicroArch::KnightsLanding => "knl",
+            MicroArch::Lakemont => "lakemont",
+
+            _ => return None,
+        };
+
+        Some(name)
+    }
 }
 
 impl From<MicroArch> for String {
@@ -327,6 +606,49 @@ impl From<MicroArch> for String {
     }
 }
 
+// ----------------------------------------------------------------------------
+// ! Feature baselines
+// ----------------------------------------------------------------------------
+
+/// A group of features introduced together, by their names in [`FEATURE_SPECS`](super::FEATURE_SPECS)
+type Baseline = &'static [&'static str];
+
+const PENTIUM: Baseline = &["FPU", "TSC", "CX8"];
+const CMOV: Baseline = &["CMOV"];
+const MMX: Baseline = &["MMX"];
+const SSE: Baseline = &["SSE"];
+const SSE2: Baseline = &["SSE2"];
+const SSE3: Baseline = &["SSE3"];
+const SSSE3: Baseline = &["SSSE3"];
+const SSE41: Baseline = &["SSE4.1"];
+const SSE42: Baseline = &["SSE4.2", "POPCNT"];
+const AVX: Baseline = &["AVX"];
+const POPCNT: Baseline = &["POPCNT"];
+const FMA_BMI1: Baseline = &["FMA", "BMI1"];
+const AVX2: Baseline = &["AVX2", "FMA", "BMI1", "BMI2"];
+const AVX512: Baseline = &["F", "DQ", "CD", "BW", "VL"];
+
+const I686: &[Baseline] = &[PENTIUM, CMOV];
+const P6_SSE2: &[Baseline] = &[PENTIUM, CMOV, MMX, SSE, SSE2];
+const P6_SSE3: &[Baseline] = &[PENTIUM, CMOV, MMX, SSE, SSE2, SSE3];
+const P6_SSSE3: &[Baseline] = &[PENTIUM, CMOV, MMX, SSE, SSE2, SSE3, SSSE3];
+const X86_64_V2: &[Baseline] = &[PENTIUM, CMOV, MMX, SSE, SSE2, SSE3, SSSE3, SSE41, SSE42];
+const X86_AVX: &[Baseline] = &[
+    PENTIUM, CMOV, MMX, SSE, SSE2, SSE3, SSSE3, SSE41, SSE42, AVX,
+];
+const X86_64_V3: &[Baseline] = &[
+    PENTIUM, CMOV, MMX, SSE, SSE2, SSE3, SSSE3, SSE41, SSE42, AVX, AVX2,
+];
+const X86_64_V4: &[Baseline] = &[
+    PENTIUM, CMOV, MMX, SSE, SSE2, SSE3, SSSE3, SSE41, SSE42, AVX, AVX2, AVX512,
+];
+
+fn feature_set(groups: &[Baseline]) -> FeatureSet {
+    groups.iter().fold(FeatureSet::default(), |set, group| {
+        set.union(FeatureSet::from_names(group))
+    })
+}
+
 /// Complete CPU architecture information.
 #[derive(Debug, Clone, PartialEq)]
 pub struct CpuArch {
@@ -373,11 +695,29 @@ impl CpuArch {
         }
     }
 
+    /// Is this an AMD APU, with integrated Radeon graphics?
+    ///
+    /// Based on the code name, so this is only as good as the lookup table.
+    #[must_use]
+    pub fn is_apu(&self) -> bool {
+        self.vendor_string == VENDOR_AMD && Amd::is_apu(self.code_name)
+    }
+
     /// Finds and returns the CPU architecture based on model string, signature, and vendor.
     ///
     /// Uses CPUID information to determine the microarchitecture and code name.
+    /// The vendor string is kept as given, even when the brand is unknown.
     #[must_use]
     pub fn find(model: &str, s: CpuSignature, vendor_string: &str) -> Self {
+        // The vendor lookups fill in their usual vendor string, such as
+        // CentaurHauls for a Zhaoxin CPU, so put back the one reported
+        Self {
+            vendor_string: String::from(vendor_string),
+            ..Self::find_by_brand(model, s, vendor_string)
+        }
+    }
+
+    fn find_by_brand(model: &str, s: CpuSignature, vendor_string: &str) -> Self {
         let arch = |ma: MicroArch,
                     code_name: &'static str,
                     brand_name: &'static str,
@@ -400,6 +740,13 @@ impl CpuArch {
         }
 
         match brand {
+            // Family 18h is only used by Hygon, but some Hygon BIOSes
+            // report the AMD vendor string
+            CpuBrand::AMD | CpuBrand::Hygon if s.display_family == 0x18 => match s.display_model {
+                0x00..=0x0F => arch(MicroArch::Zen, "Dhyana", "Hygon", Some(N14)),
+                _ => arch(MicroArch::Unknown, UNK, "Hygon", None),
+            },
+
             CpuBrand::AMD => Amd::micro_arch(model, s),
 
             CpuBrand::Intel => Intel::micro_arch(model, s),
@@ -465,6 +812,70 @@ impl CpuArch {
 pub(crate) mod tests {
     use super::*;
 
+    #[test]
+    fn test_baseline_features() {
+        let haswell = MicroArch::Haswell.baseline_features();
+        assert!(haswell.contains("AVX2"));
+        assert!(haswell.contains("SSE4.2"));
+        assert!(!haswell.contains("F"));
+
+        // The K6 has MMX, but no CMOV
+        assert_eq!(
+            MicroArch::K6.baseline_features(),
+            FeatureSet::from_names(&["FPU", "TSC", "CX8", "MMX"])
+        );
+
+        assert!(MicroArch::Unknown.baseline_features().is_empty());
+        assert!(MicroArch::Unknown.missing_baseline_features().is_empty());
+    }
+
+    #[test]
+    fn test_ceiling_covers_baseline() {
+        let arches = [
+            MicroArch::K6,
+            MicroArch::K10,
+            MicroArch::Piledriver,
+            MicroArch::Zen3,
+            MicroArch::P5,
+            MicroArch::PentiumIII,
+            MicroArch::Merom,
+            MicroArch::Goldmont,
+            MicroArch::IvyBridge,
+            MicroArch::Haswell,
+        ];
+
+        for arch in arches {
+            let ceiling = feature_set(arch.ceiling().expect("Missing feature ceiling"));
+            assert!(arch.baseline_features().is_subset(ceiling), "{arch:?}");
+        }
+
+        // Server parts have AVX-512, client parts don't
+        assert!(MicroArch::Skylake.ceiling().is_none());
+        assert!(
+            MicroArch::Unknown
+                .feature_contradictions(FeatureSet::detect())
+                .is_empty()
+        );
+
+        let haswell = MicroArch::Haswell.baseline_features();
+        assert!(
+            MicroArch::Haswell
+                .feature_contradictions(haswell)
+                .is_empty()
+        );
+        assert_eq!(
+            MicroArch::Haswell.feature_contradictions(
+                haswell
+                    .difference(FeatureSet::from_names(&["SSE2"]))
+                    .union(FeatureSet::from_names(&["F"]))
+            ),
+            [
+                "SSE2 not reported, but every Haswell CPU has it",
+                "AVX512F reported, but no Haswell CPU has it",
+            ]
+        );
+    }
+
     #[test]
     fn test_micro_arch_from_string() {
         assert_eq!(String::from(MicroArch::Winchip), "Winchip");
@@ -590,5 +1001,6 @@ pub(crate) mod tests {
         assert_eq!(arch.micro_arch, MicroArch::Unknown);
         assert_eq!(arch.code_name, UNK);
         assert_eq!(arch.brand_name, UNK);
+        assert_eq!(arch.vendor_string, vendor_str);
     }
 }
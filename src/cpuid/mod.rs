@@ -21,6 +21,7 @@ pub mod dos;
 pub mod dump;
 pub mod features;
 pub mod fns;
+pub mod mem;
 pub mod micro_arch;
 pub mod mp;
 
@@ -31,6 +32,7 @@ pub mod topology;
 pub mod vendor;
 
 pub mod quirks;
+pub mod serial;
 
 // ----------------------------------------------------------------------------
 
@@ -1,4 +1,5 @@
 use super::{Cpuid, real_x86_cpuid_count};
+use core::fmt;
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::fs;
@@ -57,6 +58,19 @@ pub fn reset_cpuid_provider() {
     });
 }
 
+/// Runs `f` with a custom CPUID provider for the current thread, then puts
+/// back the thread's previous provider.
+pub fn with_cpuid_provider<P, R>(provider: P, f: impl FnOnce() -> R) -> R
+where
+    P: CpuidProvider + Send + Sync + 'static,
+{
+    let previous = THREAD_PROVIDER.with(|p| p.borrow_mut().replace(Box::new(provider)));
+    let result = f();
+    THREAD_PROVIDER.with(|p| *p.borrow_mut() = previous);
+
+    result
+}
+
 /// Sets a custom global CPUID provider.
 pub fn set_global_cpuid_provider<P: CpuidProvider + Send + Sync + 'static>(provider: P) {
     let mut p = PROVIDER
@@ -116,6 +130,49 @@ pub fn dump_cpu_count() -> usize {
     DUMP_CPU_COUNT.with(|c| c.get())
 }
 
+/// Calls `f` with the index of each logical processor, while CPUID reads
+/// come from that processor.
+///
+/// For a dump file, each CPU in the dump is selected in turn, and the
+/// previously selected CPU is put back afterwards. Otherwise `f` runs on a
+/// scoped thread that is pinned to each processor in turn, so the caller's
+/// own affinity is left alone. That thread only sees the global CPUID
+/// provider, not one set for the calling thread. Returns an empty list if
+/// the processors can't be enumerated.
+pub(crate) fn on_each_logical_processor<T: Send>(mut f: impl FnMut(usize) -> T + Send) -> Vec<T> {
+    if info_source() == CpuidInfoSource::DumpFile {
+        let previous = CURRENT_DUMP_CPU.get();
+        let results = (0..dump_cpu_count())
+            .map(|idx| {
+                set_dump_cpu(idx);
+                f(idx)
+            })
+            .collect();
+        set_dump_cpu(previous);
+
+        return results;
+    }
+
+    let Some(core_ids) = core_affinity::get_core_ids() else {
+        return Vec::new();
+    };
+
+    std::thread::scope(|scope| {
+        scope
+            .spawn(move || {
+                core_ids
+                    .into_iter()
+                    .map(|core_id| {
+                        core_affinity::set_for_current(core_id);
+                        f(core_id.id)
+                    })
+                    .collect()
+            })
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
 #[derive(Debug, Clone)]
 pub struct CpuDump {
     pub cpus: Vec<HashMap<(u32, u32), Cpuid>>,
@@ -129,17 +186,48 @@ impl Default for CpuDump {
     }
 }
 
+/// Errors from parsing a CPUID dump.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A register value on this line (counting from 1) isn't valid hex
+    InvalidRegister(usize),
+    /// There are no CPUID leaves in the dump
+    Empty,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidRegister(line) => write!(f, "Invalid register value on line {line}"),
+            ParseError::Empty => write!(f, "No CPUID leaves found"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses a hex number, with or without the `0x` prefix
+fn parse_hex(s: &str) -> Option<u32> {
+    u32::from_str_radix(s.trim_start_matches("0x"), 16).ok()
+}
+
 impl CpuDump {
     pub fn parse_file<P: AsRef<Path>>(path: P) -> Self {
         let contents = fs::read_to_string(path).expect("Failed to read dump file");
+        Self::parse(&contents).expect("Failed to parse dump file")
+    }
+
+    /// Parses the text output of the dump command.
+    ///
+    /// Each line is `leaf sub_leaf: eax=.. ebx=.. ecx=.. edx=..` in hex.
+    /// `CPU n:` lines start the leaves of another logical CPU; without any,
+    /// the whole dump is one CPU. Other lines are skipped.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
         let mut cpus: Vec<HashMap<(u32, u32), Cpuid>> = Vec::new();
         let mut current: Option<HashMap<(u32, u32), Cpuid>> = None;
 
-        for line in contents.lines() {
+        for (line_no, line) in text.lines().enumerate() {
             let line = line.trim();
-            if line.is_empty() {
-                continue;
-            }
             if line.starts_with("CPU ") {
                 if let Some(map) = current.take() {
                     cpus.push(map);
@@ -153,86 +241,43 @@ impl CpuDump {
                 continue;
             }
 
-            let leaf_str = parts[0].trim_start_matches("0x");
-            let subleaf_str = parts[1].trim_end_matches(':').trim_start_matches("0x");
-
-            let Ok(leaf) = u32::from_str_radix(leaf_str, 16) else {
+            let Some(leaf) = parse_hex(parts[0]) else {
                 continue;
             };
-            let Ok(sub_leaf) = u32::from_str_radix(subleaf_str, 16) else {
+            let Some(sub_leaf) = parse_hex(parts[1].trim_end_matches(':')) else {
                 continue;
             };
 
-            let mut eax = 0u32;
-            let mut ebx = 0u32;
-            let mut ecx = 0u32;
-            let mut edx = 0u32;
-
+            let mut res = Cpuid::default();
             for part in &parts[2..] {
-                let reg_val = part.trim_end_matches(',');
-                if let Some(val) = reg_val.strip_prefix("eax=") {
-                    eax = u32::from_str_radix(val.trim_start_matches("0x"), 16).unwrap_or(0);
-                } else if let Some(val) = reg_val.strip_prefix("ebx=") {
-                    ebx = u32::from_str_radix(val.trim_start_matches("0x"), 16).unwrap_or(0);
-                } else if let Some(val) = reg_val.strip_prefix("ecx=") {
-                    ecx = u32::from_str_radix(val.trim_start_matches("0x"), 16).unwrap_or(0);
-                } else if let Some(val) = reg_val.strip_prefix("edx=") {
-                    edx = u32::from_str_radix(val.trim_start_matches("0x"), 16).unwrap_or(0);
+                let Some((reg, val)) = part.trim_end_matches(',').split_once('=') else {
+                    continue;
+                };
+                let val = parse_hex(val).ok_or(ParseError::InvalidRegister(line_no + 1))?;
+                match reg {
+                    "eax" => res.eax = val,
+                    "ebx" => res.ebx = val,
+                    "ecx" => res.ecx = val,
+                    "edx" => res.edx = val,
+                    _ => (),
                 }
             }
 
-            if let Some(ref mut map) = current {
-                map.insert((leaf, sub_leaf), Cpuid { eax, ebx, ecx, edx });
-            }
+            current
+                .get_or_insert_with(HashMap::new)
+                .insert((leaf, sub_leaf), res);
         }
 
         if let Some(map) = current {
             cpus.push(map);
         }
 
-        // If no CPU sections were found, treat entire file as a single context
-        if cpus.is_empty() {
-            let mut map = HashMap::new();
-            for line in contents.lines() {
-                let line = line.trim();
-                if line.is_empty() {
-                    continue;
-                }
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() < 2 {
-                    continue;
-                }
-                let leaf_str = parts[0].trim_start_matches("0x");
-                let subleaf_str = parts[1].trim_end_matches(':').trim_start_matches("0x");
-                let Ok(leaf) = u32::from_str_radix(leaf_str, 16) else {
-                    continue;
-                };
-                let Ok(sub_leaf) = u32::from_str_radix(subleaf_str, 16) else {
-                    continue;
-                };
-                let mut eax = 0u32;
-                let mut ebx = 0u32;
-                let mut ecx = 0u32;
-                let mut edx = 0u32;
-                for part in &parts[2..] {
-                    let reg_val = part.trim_end_matches(',');
-                    if let Some(val) = reg_val.strip_prefix("eax=") {
-                        eax = u32::from_str_radix(val.trim_start_matches("0x"), 16).unwrap_or(0);
-                    } else if let Some(val) = reg_val.strip_prefix("ebx=") {
-                        ebx = u32::from_str_radix(val.trim_start_matches("0x"), 16).unwrap_or(0);
-                    } else if let Some(val) = reg_val.strip_prefix("ecx=") {
-                        ecx = u32::from_str_radix(val.trim_start_matches("0x"), 16).unwrap_or(0);
-                    } else if let Some(val) = reg_val.strip_prefix("edx=") {
-                        edx = u32::from_str_radix(val.trim_start_matches("0x"), 16).unwrap_or(0);
-                    }
-                }
-                map.insert((leaf, sub_leaf), Cpuid { eax, ebx, ecx, edx });
-            }
-            cpus.push(map);
+        if cpus.iter().all(HashMap::is_empty) {
+            return Err(ParseError::Empty);
         }
 
         DUMP_CPU_COUNT.with(|c| c.set(cpus.len()));
-        CpuDump { cpus }
+        Ok(CpuDump { cpus })
     }
 
     #[must_use]
@@ -255,3 +300,50 @@ impl CpuidProvider for CpuDump {
         CpuidInfoSource::DumpFile
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dump() {
+        let dump = CpuDump::parse(
+            "CPU 0:\n    0x00000000 0x00: eax=0x00000001 ebx=0x756E6547 ecx=0x6C65746E edx=0x49656E69\n\
+             CPU 1:\n    0x00000001 0x02: eax=0x1 ebx=0x2 ecx=0x3 edx=0x4\n",
+        )
+        .expect("Failed to parse dump");
+
+        assert_eq!(dump.cpus.len(), 2);
+        assert_eq!(dump.cpus[0][&(0, 0)].ebx, 0x756E_6547);
+        assert_eq!(
+            dump.cpus[1][&(1, 2)],
+            Cpuid {
+                eax: 1,
+                ebx: 2,
+                ecx: 3,
+                edx: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_dump_without_cpu_sections() {
+        let dump = CpuDump::parse("0x00000000 0x00: eax=0x00000001 ebx=0 ecx=0 edx=0")
+            .expect("Failed to parse dump");
+        assert_eq!(dump.cpus.len(), 1);
+        assert_eq!(dump.cpus[0][&(0, 0)].eax, 1);
+    }
+
+    #[test]
+    fn test_parse_dump_errors() {
+        assert_eq!(CpuDump::parse("").err(), Some(ParseError::Empty));
+        assert_eq!(
+            CpuDump::parse("CPU 0:\nnot a dump").err(),
+            Some(ParseError::Empty)
+        );
+        assert_eq!(
+            CpuDump::parse("CPU 0:\n0x00000000 0x00: eax=0xZZ").err(),
+            Some(ParseError::InvalidRegister(2))
+        );
+    }
+}
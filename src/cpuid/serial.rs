@@ -0,0 +1,416 @@
+//! A compact binary format for detected CPU information.
+//!
+//! This is meant for moving results between machines, such as the DOS build
+//! writing a file that is read back on a modern host. The layout, with
+//! multi-byte values in little-endian order:
+//!
+//! | Bytes | Value |
+//! |-------|-------|
+//! | 4     | Magic, `RSID` |
+//! | 1     | Format version, currently 2 |
+//! | 1     | Flags: bit 0 has CPUID, bit 1 Overdrive, bit 2 measured speed |
+//! | 5     | Extended family, family, extended model, model, stepping |
+//! | 1     | Brand id |
+//! | 1     | Core type: 0 none, 1 super, 2 performance, 3 efficiency |
+//! | 2 × 5 | Sockets, cores, threads, base MHz, boost MHz |
+//! | 1 + n | Vendor string, as a length then the bytes |
+//! | 1 + n | Model string |
+//! | 1 + n | Hypervisor vendor string, empty for none |
+//! | 1 + n | Easter egg string, empty for none |
+//! | 4 × 9 | Feature flags, as the registers in [`FEATURE_REGISTERS`] |
+//!
+//! Features are stored as their CPUID bits, so the format doesn't change when
+//! [`FEATURE_SPECS`] gains a row for a bit in one of those registers. Features
+//! that aren't in that table, such as the Centaur group, are left out, as are
+//! caches, per-core-type details and data sources.
+
+use super::constants::{EXT_LEAF_1, EXT_LEAF_8, LEAF_1, LEAF_7};
+use super::cpu::Cpu;
+use super::features::{FEATURE_SPECS, Reg};
+use crate::common::CoreType;
+use alloc::vec::Vec;
+use core::fmt;
+
+#[cfg(not(dos))]
+use super::micro_arch::CpuArch;
+#[cfg(not(dos))]
+use super::{CpuSignature, Cpuid, EXT_LEAF_0, LEAF_0, get_feature_list};
+#[cfg(not(dos))]
+use crate::common::{DataSource, Speed, TopologyTier};
+#[cfg(not(dos))]
+use alloc::string::String;
+
+const MAGIC: &[u8; 4] = b"RSID";
+const VERSION: u8 = 2;
+
+/// The feature flag registers, as (leaf, sub-leaf, register), in the order
+/// they're stored. Only ever add to the end, with a new format version.
+pub const FEATURE_REGISTERS: [(u32, u32, Reg); 9] = [
+    (LEAF_1, 0, Reg::Edx),
+    (LEAF_1, 0, Reg::Ecx),
+    (LEAF_7, 0, Reg::Ebx),
+    (LEAF_7, 0, Reg::Ecx),
+    (LEAF_7, 0, Reg::Edx),
+    (LEAF_7, 1, Reg::Eax),
+    (EXT_LEAF_1, 0, Reg::Edx),
+    (EXT_LEAF_1, 0, Reg::Ecx),
+    (EXT_LEAF_8, 0, Reg::Ebx),
+];
+
+/// Errors from decoding [`Cpu::to_bytes`] output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data doesn't start with the magic bytes
+    Magic,
+    /// The data is from an unsupported version of the format
+    Version(u8),
+    /// The data ends partway through a value
+    Truncated,
+    /// A string isn't valid UTF-8
+    InvalidString,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Magic => write!(f, "Not rustid CPU data"),
+            DecodeError::Version(v) => write!(f, "Unsupported format version {v}"),
+            DecodeError::Truncated => write!(f, "Data ends unexpectedly"),
+            DecodeError::InvalidString => write!(f, "Invalid UTF-8 string"),
+        }
+    }
+}
+
+#[cfg(not(dos))]
+impl std::error::Error for DecodeError {}
+
+/// Appends a string, as a one byte length then the bytes.
+///
+/// CPUID strings are at most 48 bytes, so anything past 255 is cut off.
+fn push_str(out: &mut Vec<u8>, s: &str) {
+    let bytes = &s.as_bytes()[..s.len().min(u8::MAX as usize)];
+    out.push(bytes.len() as u8);
+    out.extend_from_slice(bytes);
+}
+
+fn push_u16(out: &mut Vec<u8>, n: u32) {
+    let n = n.min(u32::from(u16::MAX)) as u16;
+    out.extend_from_slice(&n.to_le_bytes());
+}
+
+/// Reads values from the front of a byte slice
+#[cfg(not(dos))]
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+#[cfg(not(dos))]
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < n {
+            return Err(DecodeError::Truncated);
+        }
+
+        let (head, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u32, DecodeError> {
+        let b = self.take(2)?;
+        Ok(u32::from(u16::from_le_bytes([b[0], b[1]])))
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn short_str(&mut self) -> Result<String, DecodeError> {
+        let len = self.u8()? as usize;
+        core::str::from_utf8(self.take(len)?)
+            .map(String::from)
+            .map_err(|_| DecodeError::InvalidString)
+    }
+
+    fn optional_str(&mut self) -> Result<Option<String>, DecodeError> {
+        let s = self.short_str()?;
+        Ok(if s.is_empty() { None } else { Some(s) })
+    }
+}
+
+/// Packs a signature back into the leaf 1 EAX layout
+#[cfg(not(dos))]
+fn leaf_1_eax(sig: &[u8], overdrive: bool) -> u32 {
+    let [ext_family, family, ext_model, model, stepping] =
+        [0, 1, 2, 3, 4].map(|i| u32::from(sig[i]));
+
+    (ext_family << 20)
+        | (ext_model << 16)
+        | (u32::from(overdrive) << 12)
+        | ((family & 0xF) << 8)
+        | ((model & 0xF) << 4)
+        | (stepping & 0xF)
+}
+
+impl Cpu {
+    /// Sets the CPUID bit of each feature in the feature list, for
+    /// [`FEATURE_REGISTERS`].
+    ///
+    /// Vendor-specific flags are set for every vendor here, since decoding
+    /// checks the vendor.
+    fn feature_registers(&self) -> [u32; FEATURE_REGISTERS.len()] {
+        let mut registers = [0u32; FEATURE_REGISTERS.len()];
+
+        let names = self.features.values().flat_map(|list| list.split(' '));
+
+        for name in names {
+            for spec in FEATURE_SPECS.iter().filter(|spec| spec.name == name) {
+                if let Some(i) = FEATURE_REGISTERS
+                    .iter()
+                    .position(|&r| r == (spec.leaf, spec.sub_leaf, spec.reg))
+                {
+                    registers[i] |= 1 << spec.bit;
+                }
+            }
+        }
+
+        registers
+    }
+
+    /// Encodes the detected information in the compact binary format
+    /// described in the [`serial`](self) module.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let sig = &self.signature;
+        let topo = &self.topology;
+        let mut out = Vec::with_capacity(256);
+
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        out.push(
+            u8::from(self.has_cpuid)
+                | (u8::from(sig.is_overdrive) << 1)
+                | (u8::from(topo.speed.measured) << 2),
+        );
+        for n in [
+            sig.extended_family,
+            sig.family,
+            sig.extended_model,
+            sig.model,
+            sig.stepping,
+            self.brand_id,
+        ] {
+            out.push(n as u8);
+        }
+        out.push(match self.core_type {
+            None => 0,
+            Some(CoreType::Super) => 1,
+            Some(CoreType::Performance) => 2,
+            Some(CoreType::Efficiency) => 3,
+        });
+        for n in [
+            topo.sockets.count,
+            topo.cores.count,
+            topo.threads.count,
+            topo.speed.base,
+            topo.speed.boost,
+        ] {
+            push_u16(&mut out, n);
+        }
+
+        push_str(&mut out, &self.arch.vendor_string);
+        push_str(&mut out, &self.arch.model);
+        push_str(&mut out, self.hyp_vendor_str.as_deref().unwrap_or(""));
+        push_str(&mut out, self.easter_egg.as_deref().unwrap_or(""));
+
+        for flags in self.feature_registers() {
+            out.extend_from_slice(&flags.to_le_bytes());
+        }
+
+        out
+    }
+
+    /// Decodes data from [`Cpu::to_bytes`].
+    ///
+    /// The architecture details are looked up again from the vendor, model
+    /// string and signature, so they reflect this version of the tables.
+    /// Not available on the DOS build, as the lookups go through the CPUID
+    /// provider.
+    #[cfg(not(dos))]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Cpu, DecodeError> {
+        use super::provider::{CpuDump, set_dump_cpu, with_cpuid_provider};
+        use std::collections::HashMap;
+
+        let mut r = Reader { bytes };
+
+        if r.take(MAGIC.len()).map_err(|_| DecodeError::Magic)? != MAGIC {
+            return Err(DecodeError::Magic);
+        }
+        match r.u8()? {
+            VERSION => {}
+            v => return Err(DecodeError::Version(v)),
+        }
+
+        let flags = r.u8()?;
+        let sig = r.take(5)?;
+        let brand_id = u32::from(r.u8()?);
+        let core_type = match r.u8()? {
+            1 => Some(CoreType::Super),
+            2 => Some(CoreType::Performance),
+            3 => Some(CoreType::Efficiency),
+            _ => None,
+        };
+        let [sockets, cores, threads, base, boost] =
+            [r.u16()?, r.u16()?, r.u16()?, r.u16()?, r.u16()?];
+
+        let vendor = r.short_str()?;
+        let model = r.short_str()?;
+        let hyp_vendor_str = r.optional_str()?;
+        let easter_egg = r.optional_str()?;
+
+        let mut registers = [0u32; FEATURE_REGISTERS.len()];
+        for flags in &mut registers {
+            *flags = r.u32()?;
+        }
+
+        // The architecture and feature lookups go through CPUID, so answer
+        // those from the decoded values instead of this machine
+        let vendor_bytes: [u8; 12] =
+            core::array::from_fn(|i| *vendor.as_bytes().get(i).unwrap_or(&b' '));
+        let vendor_reg = |i: usize| {
+            u32::from_le_bytes([
+                vendor_bytes[i],
+                vendor_bytes[i + 1],
+                vendor_bytes[i + 2],
+                vendor_bytes[i + 3],
+            ])
+        };
+        let mut leaves = HashMap::new();
+        leaves.insert(
+            (LEAF_0, 0),
+            Cpuid {
+                eax: LEAF_7,
+                ebx: vendor_reg(0),
+                ecx: vendor_reg(8),
+                edx: vendor_reg(4),
+            },
+        );
+        leaves.insert(
+            (LEAF_1, 0),
+            Cpuid {
+                eax: leaf_1_eax(sig, flags & 2 != 0),
+                ebx: brand_id,
+                ..Cpuid::default()
+            },
+        );
+        leaves.insert(
+            (LEAF_7, 0),
+            Cpuid {
+                eax: 1,
+                ..Cpuid::default()
+            },
+        );
+        leaves.insert(
+            (EXT_LEAF_0, 0),
+            Cpuid {
+                eax: EXT_LEAF_8,
+                ..Cpuid::default()
+            },
+        );
+        for (&(leaf, sub_leaf, reg), flags) in FEATURE_REGISTERS.iter().zip(registers) {
+            let res: &mut Cpuid = leaves.entry((leaf, sub_leaf)).or_default();
+            let value = match reg {
+                Reg::Eax => &mut res.eax,
+                Reg::Ebx => &mut res.ebx,
+                Reg::Ecx => &mut res.ecx,
+                Reg::Edx => &mut res.edx,
+            };
+            *value |= flags;
+        }
+
+        let dump = CpuDump { cpus: vec![leaves] };
+        // An earlier dump may have left another CPU selected
+        set_dump_cpu(0);
+        let (signature, arch, features) = with_cpuid_provider(dump, || {
+            let [ext_family, family, ext_model, model_id, stepping] =
+                [0, 1, 2, 3, 4].map(|i| u32::from(sig[i]));
+            let signature = CpuSignature::new(
+                ext_family,
+                family,
+                ext_model,
+                model_id,
+                stepping,
+                DataSource::CpuidDump,
+            );
+
+            (
+                signature,
+                CpuArch::find(&model, signature, &vendor),
+                get_feature_list(),
+            )
+        });
+
+        let mut cpu = Cpu {
+            has_cpuid: flags & 1 != 0,
+            arch,
+            hyp_vendor_str,
+            easter_egg,
+            brand_id,
+            signature,
+            features,
+            core_type,
+            ..Cpu::default()
+        };
+
+        let tier = |count| TopologyTier::new(count, DataSource::CpuidDump);
+        cpu.topology.sockets = tier(sockets);
+        cpu.topology.cores = tier(cores);
+        cpu.topology.threads = tier(threads);
+        cpu.topology.speed = Speed {
+            base,
+            boost,
+            measured: flags & 4 != 0,
+        };
+
+        Ok(cpu)
+    }
+}
+
+#[cfg(all(test, not(dos)))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_registers_cover_specs() {
+        for spec in &FEATURE_SPECS {
+            assert!(
+                FEATURE_REGISTERS.contains(&(spec.leaf, spec.sub_leaf, spec.reg)),
+                "{} isn't in a stored register",
+                spec.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_errors() {
+        assert_eq!(Cpu::from_bytes(b"").err(), Some(DecodeError::Magic));
+        assert_eq!(Cpu::from_bytes(b"NOPE\x01").err(), Some(DecodeError::Magic));
+        assert_eq!(
+            Cpu::from_bytes(b"RSID\x03").err(),
+            Some(DecodeError::Version(3))
+        );
+
+        let bytes = Cpu::default().to_bytes();
+        assert!(Cpu::from_bytes(&bytes).is_ok());
+        assert_eq!(
+            Cpu::from_bytes(&bytes[..bytes.len() - 1]).err(),
+            Some(DecodeError::Truncated)
+        );
+    }
+}
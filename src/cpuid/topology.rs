@@ -55,6 +55,10 @@ impl Speed {
     }
 
     fn measure() -> Self {
+        if super::detection_mode() == super::DetectionMode::CpuidOnly {
+            return Speed::default();
+        }
+
         #[cfg(not(dos))]
         if info_source() == CpuidInfoSource::DumpFile || !super::has_tsc() {
             return Speed::default();
@@ -234,7 +238,13 @@ impl Topology {
 
         let t_per_core = threads_per_core.max(1);
         let t_per_pkg = threads_per_package.max(1);
-        let c_per_pkg = t_per_pkg / t_per_core;
+        // The E-cores of a hybrid CPU have one thread each, so dividing by
+        // the P-cores' threads per core would undercount them
+        let c_per_pkg = if super::has_hybrid() && cores.count > 0 {
+            cores.count
+        } else {
+            t_per_pkg / t_per_core
+        };
 
         (
             sockets,
@@ -27,21 +27,18 @@ pub unsafe extern "C" fn _start() -> ! {
 #[cfg(dos)]
 #[unsafe(no_mangle)]
 pub extern "C" fn rust_main() -> ! {
-    use rustid::common::{CliFlags, TCpuDisplay, TDetect};
+    use rustid::common::CliFlags;
     use rustid::cpuid::dos::{exit, init_heap};
-    use rustid::{Cpu, cyrix_cpuid_check, version};
+    use rustid::{cyrix_cpuid_check, run, version};
 
     unsafe { init_heap() };
 
     cyrix_cpuid_check();
 
-    let cpu = Cpu::detect();
-    let flags = CliFlags::default();
-
     version();
-    cpu.display_table(flags);
+    let code = run(CliFlags::default());
 
-    exit(0);
+    exit(code as u8);
 }
 
 #[cfg(not(dos))]
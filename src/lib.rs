@@ -60,6 +60,9 @@ pub use arm::Cpu;
 #[cfg(dos)]
 pub use cpuid::dos::*;
 
+// `crate::print!`/`crate::println!` are the only output macros used in the
+// library: the DOS versions are exported from `cpuid::dos`, otherwise they
+// are the standard library's.
 #[cfg(not(dos))]
 pub use std::{print, println};
 
@@ -70,6 +73,45 @@ pub fn version() {
     );
 }
 
+/// Exit code from [`run`] when the CPU doesn't support CPUID
+pub const EXIT_NO_CPUID: i32 = 1;
+
+/// Exit code from [`run`] when the CPUID data has inconsistencies
+pub const EXIT_INCONSISTENT: i32 = 2;
+
+/// Detects the CPU and prints its table, returning the [`exit_code`] for
+/// the program.
+pub fn run(flags: common::CliFlags) -> i32 {
+    use common::{TCpuDisplay, TDetect};
+
+    let cpu = Cpu::detect();
+    cpu.display_table(flags);
+
+    exit_code(&cpu)
+}
+
+/// The program's exit code for a detected CPU.
+///
+/// The code is 0 normally. On x86 it is [`EXIT_NO_CPUID`] for a CPU without
+/// CPUID, or [`EXIT_INCONSISTENT`] when [`Cpu::validate`] finds problems, so
+/// scripts can tell the output is incomplete or untrustworthy.
+#[allow(unused_variables)]
+#[must_use]
+pub fn exit_code(cpu: &Cpu) -> i32 {
+    #[cfg(x86_cpu)]
+    {
+        if !cpu.has_cpuid {
+            return EXIT_NO_CPUID;
+        }
+
+        if !cpu.validate().is_empty() {
+            return EXIT_INCONSISTENT;
+        }
+    }
+
+    0
+}
+
 #[cfg(not(dos))]
 #[cfg(x86_cpu)]
 pub fn file_version() {
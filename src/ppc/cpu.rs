@@ -1,8 +1,8 @@
 //! Contains the Cpu struct for PowerPC.
 
 use crate::common::cache::{Cache, CacheLevel, CacheType, Level1Cache};
-use crate::common::{CliFlags, CpuDisplay, DataSource, TCpuDisplay, TDetect};
-use crate::ppc::micro_arch::CpuArch;
+use crate::common::{CliFlags, ConsoleWriter, CpuDisplay, DataSource, TCpuDisplay, TDetect};
+use crate::ppc::micro_arch::{CpuArch, SocVersion};
 use std::fs;
 use std::path::Path;
 
@@ -12,6 +12,8 @@ pub struct Cpu {
     pub version: u16,
     pub revision: u16,
     pub cpu_arch: CpuArch,
+    /// The System Version Register, on Freescale/NXP embedded cores
+    pub svr: Option<u32>,
     pub cache: Option<Cache>,
     pub clock_speed: Option<u64>,
     pub clock_speed_source: DataSource,
@@ -257,6 +259,7 @@ impl TDetect for Cpu {
         let version = (pvr >> 16) as u16;
         let revision = (pvr & 0xFFFF) as u16;
         let cpu_arch = CpuArch::find(pvr);
+        let svr = super::get_svr();
         let (cache, _) = Self::detect_cache(pvr);
         let (clock_speed, clock_speed_source) = Self::detect_clock_speed();
 
@@ -265,6 +268,7 @@ impl TDetect for Cpu {
             version,
             revision,
             cpu_arch,
+            svr,
             cache,
             clock_speed,
             clock_speed_source,
@@ -280,11 +284,15 @@ impl TCpuDisplay for Cpu {
     fn display_table(&self, flags: CliFlags) {
         println!();
 
-        let cpu = CpuDisplay { flags };
+        let mut out = ConsoleWriter;
+        let cpu = CpuDisplay::new(flags, &mut out);
 
         cpu.simple_line("Model", self.cpu_arch.marketing_name);
         cpu.simple_line("MicroArch", self.cpu_arch.micro_arch.into());
         cpu.simple_line("Code Name", self.cpu_arch.code_name);
+        if let Some(soc) = self.svr.and_then(SocVersion::find) {
+            cpu.simple_line("SoC", &soc.to_string());
+        }
         if let Some(tech) = self.cpu_arch.technology {
             cpu.simple_line("Process", tech);
         }
@@ -295,7 +303,7 @@ impl TCpuDisplay for Cpu {
                 cpu.label("Frequency"),
                 CpuDisplay::format_frequency(clock_mhz)
             );
-            CpuDisplay::newline();
+            cpu.newline();
         }
 
         // TODO handle multiple cores/sockets
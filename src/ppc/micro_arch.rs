@@ -28,6 +28,13 @@ pub enum MicroArch {
 
     // IBM G5
     Ppc970fx,
+
+    // Freescale/NXP embedded (Book E)
+    E500v1,
+    E500v2,
+    E500mc,
+    E5500,
+    E6500,
 }
 
 impl From<MicroArch> for &'static str {
@@ -53,6 +60,11 @@ impl From<MicroArch> for &'static str {
             MicroArch::Ppc7460 => "PowerPC 7460 (G4)",
             MicroArch::Ppc970 => "PowerPC 970 (G5)",
             MicroArch::Ppc970fx => "PowerPC 970FX (G5)",
+            MicroArch::E500v1 => "e500v1",
+            MicroArch::E500v2 => "e500v2",
+            MicroArch::E500mc => "e500mc",
+            MicroArch::E5500 => "e5500",
+            MicroArch::E6500 => "e6500",
         }
     }
 }
@@ -200,9 +212,126 @@ impl CpuArch {
                 Some(N90),
             ),
 
+            // Freescale/NXP embedded cores
+            0x8020 => Self::new(
+                "PowerQUICC III",
+                MicroArch::E500v1,
+                "e500",
+                0x8020,
+                Some(N130),
+            ),
+            0x8021 => Self::new(
+                "PowerQUICC III",
+                MicroArch::E500v2,
+                "e500",
+                0x8021,
+                Some(N90),
+            ),
+            0x8023 => Self::new("QorIQ", MicroArch::E500mc, "e500mc", 0x8023, Some(N45)),
+            0x8024 => Self::new("QorIQ", MicroArch::E5500, "e5500", 0x8024, Some(N45)),
+            0x8040 => Self::new("QorIQ", MicroArch::E6500, "e6500", 0x8040, Some(N28)),
+
             _ => Self::default(),
         }
     }
+
+    /// Whether the core has a System Version Register (SPR 1023).
+    ///
+    /// Reading SPR 1023 on a core without one is an illegal instruction.
+    pub fn has_svr(pvr: u32) -> bool {
+        matches!(pvr >> 16, 0x8020..=0x8024 | 0x8040)
+    }
+}
+
+/// A Freescale/NXP SoC, decoded from the System Version Register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SocVersion {
+    pub part: &'static str,
+    /// The 'E' parts include the security engine
+    pub security: bool,
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl SocVersion {
+    /// Decodes the SVR, with part numbers from Linux's `asm/mpc85xx.h`.
+    pub fn find(svr: u32) -> Option<Self> {
+        // Bits 31:8 are the SoC version, without the security engine bit
+        let part = match (svr >> 8) & 0xFF_F7FF {
+            0x80_3000 => "MPC8540",
+            0x80_3100 => "MPC8548",
+            0x80_3101 => "MPC8547",
+            0x80_3102 => "MPC8545",
+            0x80_3200 => "MPC8543",
+            0x80_3400 => "MPC8533",
+            0x80_3401 => "MPC8544",
+            0x80_3700 => "MPC8536",
+            0x80_3701 => "MPC8535",
+            0x80_7000 => "MPC8560",
+            0x80_7100 => "MPC8555",
+            0x80_7200 => "MPC8541",
+            0x80_7500 => "MPC8568",
+            0x80_7501 => "MPC8567",
+            0x80_8000 => "MPC8569",
+            0x80_E000 => "MPC8572",
+            0x80_E200 => "P2020",
+            0x80_E300 => "P2010",
+            0x80_E400 => "P1020",
+            0x80_E401 => "P1021",
+            0x80_E402 => "P1024",
+            0x80_E403 => "P1025",
+            0x80_E500 => "P1011",
+            0x80_E501 => "P1012",
+            0x80_E600 => "P1022",
+            0x80_E700 => "P1013",
+            0x80_F100 => "P1010",
+            0x80_F101 => "P1014",
+            0x80_F600 => "P1023",
+            0x80_F700 => "P1017",
+            0x82_0000 => "P4080",
+            0x82_0100 => "P4040",
+            0x82_0400 => "P5040",
+            0x82_0500 => "P5021",
+            0x82_1000 => "P2040",
+            0x82_1001 => "P2041",
+            0x82_1103 => "P3041",
+            0x82_2000 => "P5020",
+            0x82_2100 => "P5010",
+            0x82_4000 => "T4240",
+            0x82_4001 => "T4120",
+            0x82_4100 => "T4160",
+            0x82_4102 => "T4080",
+            0x85_2000 => "T1040",
+            0x85_2001 => "T1041",
+            0x85_2002 => "T1042",
+            0x85_2100 => "T1020",
+            0x85_2101 => "T1021",
+            0x85_2102 => "T1022",
+            0x85_3000 => "T2080",
+            0x85_3100 => "T2081",
+            0x85_4000 => "T1024",
+            0x85_4100 => "T1023",
+            0x86_8000 => "B4860",
+            0x86_8100 => "B4440",
+            0x86_8102 => "B4420",
+            0x86_8103 => "B4220",
+            _ => return None,
+        };
+
+        Some(Self {
+            part,
+            security: svr & 0x0008_0000 != 0,
+            major: ((svr >> 4) & 0xF) as u8,
+            minor: (svr & 0xF) as u8,
+        })
+    }
+}
+
+impl core::fmt::Display for SocVersion {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let e = if self.security { "E" } else { "" };
+        write!(f, "{}{} (Rev {}.{})", self.part, e, self.major, self.minor)
+    }
 }
 
 #[cfg(test)]
@@ -237,4 +366,28 @@ mod tests {
         assert_eq!(cpu.marketing_name, "PowerPC 970FX");
         assert_eq!(cpu.micro_arch, MicroArch::Ppc970fx);
     }
+
+    #[test]
+    fn test_e500v2_lookup() {
+        let pvr = 0x8021_1051;
+        let cpu = CpuArch::find(pvr);
+        assert_eq!(cpu.micro_arch, MicroArch::E500v2);
+        assert!(CpuArch::has_svr(pvr));
+        assert!(!CpuArch::has_svr(0x0008_0202));
+    }
+
+    #[test]
+    fn test_svr_lookup() {
+        // P2020E, rev 2.1
+        let soc = SocVersion::find(0x80EA_0021).unwrap();
+        assert_eq!(soc.part, "P2020");
+        assert!(soc.security);
+        assert_eq!(soc.to_string(), "P2020E (Rev 2.1)");
+
+        // T4240, rev 2.0, without the security engine
+        let soc = SocVersion::find(0x8240_0020).unwrap();
+        assert_eq!(soc.to_string(), "T4240 (Rev 2.0)");
+
+        assert_eq!(SocVersion::find(0x1234_5678), None);
+    }
 }
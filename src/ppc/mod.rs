@@ -20,3 +20,33 @@ pub fn get_pvr() -> u32 {
     }
     pvr
 }
+
+/// Gets the System Version Register (SVR), which identifies the SoC
+/// around a Freescale/NXP e500 or later embedded core.
+///
+/// The SVR is SPR 1023, and only readable in supervisor mode. Linux
+/// doesn't emulate the read as it does for the PVR, but lists the value
+/// in `/proc/cpuinfo`, so that is used instead. Returns `None` for cores
+/// without an SVR, and on other OSes, where user code can't read it.
+pub fn get_svr() -> Option<u32> {
+    if !micro_arch::CpuArch::has_svr(get_pvr()) {
+        return None;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+        cpuinfo
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(key, _)| key.trim() == "SVR")
+            .and_then(|(_, value)| {
+                let value = value.trim();
+                let value = value.strip_prefix("0x").unwrap_or(value);
+                u32::from_str_radix(value, 16).ok()
+            })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    None
+}
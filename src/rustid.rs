@@ -18,6 +18,10 @@ fn help() {
     println!("  r, dump          Dump raw CPUID values");
     #[cfg(x86_cpu)]
     println!("  f, file <file>   Load CPUID dump from file and display CPU information");
+    #[cfg(x86_cpu)]
+    println!("  t, rustflags     Suggest RUSTFLAGS for building for this CPU");
+    #[cfg(x86_cpu)]
+    println!("  z, cpuz          Display CPU information in a CPU-Z like layout");
     println!("  V, version       Display version info");
     println!("  h, help          Show this help message");
     println!();
@@ -37,10 +41,8 @@ fn main() {
     #[cfg(target_arch = "x86")]
     cyrix_cpuid_check();
 
-    let mut flags = CliFlags {
-        color: true,
-        ..Default::default()
-    };
+    let mut flags = CliFlags::default();
+    flags.color = true;
 
     let mut action = "default";
     #[cfg(x86_cpu)]
@@ -59,6 +61,10 @@ fn main() {
             "e" | "everything" => action = "everything",
             "r" | "dump" => action = "dump",
             #[cfg(x86_cpu)]
+            "t" | "rustflags" => action = "rustflags",
+            #[cfg(x86_cpu)]
+            "z" | "cpuz" => action = "cpuz",
+            #[cfg(x86_cpu)]
             "f" | "file" => {
                 file_path = args.next();
                 if file_path.is_none() {
@@ -78,6 +84,10 @@ fn main() {
                         'e' => action = "everything",
                         'r' => action = "dump",
                         #[cfg(x86_cpu)]
+                        't' => action = "rustflags",
+                        #[cfg(x86_cpu)]
+                        'z' => action = "cpuz",
+                        #[cfg(x86_cpu)]
                         'f' => {
                             file_path = args.next();
                             if file_path.is_none() {
@@ -115,7 +125,7 @@ fn main() {
     }
 
     // Display the version header
-    if action != "dump" {
+    if action != "dump" && action != "rustflags" {
         #[cfg(x86_cpu)]
         if file_path.is_none() {
             version();
@@ -125,6 +135,8 @@ fn main() {
         version();
     }
 
+    let mut code = 0;
+
     match action {
         "debug" => {
             Cpu::detect().debug();
@@ -150,10 +162,18 @@ fn main() {
 
             print!("{output}");
         }
+        #[cfg(x86_cpu)]
+        "rustflags" => {
+            println!("{}", Cpu::detect().suggested_rustflags());
+        }
+        #[cfg(x86_cpu)]
+        "cpuz" => {
+            Cpu::detect().display_cpuz(flags);
+        }
         "help" => help(),
         "version" => {}
         "default" => {
-            Cpu::detect().display_table(flags);
+            code = rustid::run(flags);
         }
         _ => unreachable!(),
     }
@@ -163,4 +183,8 @@ fn main() {
         use rustid::cpuid::provider;
         provider::reset_cpuid_provider();
     }
+
+    if code != 0 {
+        std::process::exit(code);
+    }
 }
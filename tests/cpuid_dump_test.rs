@@ -2,9 +2,11 @@
 
 use rustid::common::TDetect;
 use rustid::common::*;
+use rustid::cpuid::micro_arch::MicroArch;
 use rustid::cpuid::provider::*;
 use rustid::cpuid::*;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 
 // ----------------------------------------------------------------------------
 // ! Test Setup
@@ -34,12 +36,61 @@ fn raw_path(segment: &str) -> PathBuf {
     path
 }
 
+/// Applies each `(from, to)` patch to the dump text, panicking if a
+/// pattern isn't found, so a test can't silently run against the unpatched CPU
+fn patch_dump(text: &str, patches: &[(&str, &str)]) -> String {
+    let mut text = text.to_string();
+    for (from, to) in patches {
+        assert!(text.contains(from), "Patch pattern not found: {from}");
+        text = text.replace(from, to);
+    }
+
+    text
+}
+
+/// Loads a dump file with [`patch_dump`] applied
+fn patched_dump(segment: &str, patches: &[(&str, &str)]) -> CpuDump {
+    let text = std::fs::read_to_string(raw_path(segment)).expect("Missing dump");
+    CpuDump::parse(&patch_dump(&text, patches)).expect("Failed to parse dump")
+}
+
 fn set_file_cpuid_provider(path: &str) {
     let path = raw_path(path);
     let cpu = CpuDump::parse_file(path);
     set_cpuid_provider(MockCpuidProvider { cpu: cpu.clone() });
 }
 
+/// Wraps a dump file, remembering which leaves were read
+struct RecordingCpuidProvider {
+    cpu: CpuDump,
+    leaves: Arc<Mutex<Vec<u32>>>,
+}
+
+impl CpuidProvider for RecordingCpuidProvider {
+    fn cpuid_count(&self, leaf: u32, sub_leaf: u32) -> Cpuid {
+        self.leaves.lock().expect("Poisoned leaf log").push(leaf);
+        self.cpu.get(leaf, sub_leaf)
+    }
+
+    fn info_source(&self) -> CpuidInfoSource {
+        CpuidInfoSource::DumpFile
+    }
+}
+
+/// Runs `test` against the given dump, returning the leaves it read
+fn record_leaves(path: &str, test: impl FnOnce()) -> Vec<u32> {
+    let leaves = Arc::new(Mutex::new(Vec::new()));
+    set_cpuid_provider(RecordingCpuidProvider {
+        cpu: CpuDump::parse_file(raw_path(path)),
+        leaves: Arc::clone(&leaves),
+    });
+
+    test();
+
+    set_file_cpuid_provider(path);
+    leaves.lock().expect("Poisoned leaf log").clone()
+}
+
 // ----------------------------------------------------------------------------
 // ! Test Helpers
 // ----------------------------------------------------------------------------
@@ -136,6 +187,61 @@ mod ppro {
         test();
     }
 
+    #[test]
+    fn test_intel_threads_per_core() {
+        with_mock_cpu(|| {
+            assert_eq!(Cpu::threads_per_core(), 1);
+        });
+
+        // The same CPU, with HTT and two logical processors, like a
+        // Pentium 4 with Hyper-Threading
+        let dump = patched_dump(
+            "dump/p6x2.txt",
+            &[(
+                "ebx=0x00000000 ecx=0x00000000 edx=0x0000fbff",
+                "ebx=0x00020000 ecx=0x00000000 edx=0x1000fbff",
+            )],
+        );
+        with_cpuid_provider(dump, || {
+            assert_eq!(Cpu::threads_per_core(), 2);
+        });
+    }
+
+    #[test]
+    fn test_intel_no_lzcnt() {
+        with_mock_cpu(|| {
+            assert!(!has_lzcnt());
+            assert!(!lzcnt_is_real());
+        });
+    }
+
+    #[test]
+    fn test_baseline_features() {
+        with_mock_cpu(|| {
+            let cpu = Cpu::detect();
+            let arch = cpu.arch.micro_arch;
+            assert_eq!(arch, rustid::cpuid::micro_arch::MicroArch::PentiumPro);
+            assert!(arch.baseline_features().contains("CMOV"));
+            assert!(arch.missing_baseline_features().is_empty());
+            assert!(arch.feature_contradictions(cpu.feature_set()).is_empty());
+        });
+    }
+
+    #[test]
+    fn test_no_raw_brand_bytes() {
+        with_mock_cpu(|| {
+            assert_eq!(raw_brand_bytes(), [0; 48]);
+        });
+    }
+
+    #[test]
+    fn test_cpuid_maxval_not_known() {
+        // A 32-bit CPU with a genuinely low max leaf
+        with_mock_cpu(|| {
+            assert_eq!(cpuid_maxval_limited(), None);
+        });
+    }
+
     #[test]
     fn test_vendor_detection() {
         with_mock_cpu(|| {
@@ -169,6 +275,451 @@ mod ppro {
     }
 }
 
+/// A Prescott Pentium 4 with Hyper-Threading, which has one core, and
+/// reports HTT whether or not HT is enabled in the BIOS
+mod prescott {
+    use super::*;
+
+    const DUMP: &str = "\
+        0x00000000 0x00: eax=0x00000005 ebx=0x756E6547 ecx=0x6C65746E edx=0x49656E69
+        0x00000001 0x00: eax=0x00000F41 ebx=0x00020800 ecx=0x0000649D edx=0xBFEBFBFF
+        0x00000004 0x00: eax=0x00004121 ebx=0x01C0003F ecx=0x0000001F edx=0x00000000
+        0x00000004 0x01: eax=0x00004143 ebx=0x01C0003F ecx=0x000007FF edx=0x00000000
+        0x00000004 0x02: eax=0x00000000 ebx=0x00000000 ecx=0x00000000 edx=0x00000000";
+
+    #[test]
+    fn test_ht_enabled() {
+        let dump = CpuDump::parse(DUMP).expect("Failed to parse dump");
+        with_cpuid_provider(dump, || {
+            assert!(has_ht());
+            assert_eq!(
+                core_counts(),
+                CoreCounts {
+                    logical: 2,
+                    physical: 1
+                }
+            );
+            assert_eq!(Cpu::threads_per_core(), 2);
+        });
+    }
+
+    #[test]
+    fn test_ht_disabled() {
+        // HT off in the BIOS: HTT is still set, but only one logical
+        // processor is reported
+        let text = patch_dump(DUMP, &[("ebx=0x00020800", "ebx=0x00010800")]);
+        let dump = CpuDump::parse(&text).expect("Failed to parse dump");
+        with_cpuid_provider(dump, || {
+            assert!(has_ht());
+            assert_eq!(
+                core_counts(),
+                CoreCounts {
+                    logical: 1,
+                    physical: 1
+                }
+            );
+            assert_eq!(Cpu::threads_per_core(), 1);
+        });
+    }
+}
+
+/// A Core 2 Quad (Kentsfield), from before leaf 0xB
+mod core2_quad {
+    use super::*;
+
+    const DUMP: &str = "\
+        0x00000000 0x00: eax=0x0000000A ebx=0x756E6547 ecx=0x6C65746E edx=0x49656E69
+        0x00000001 0x00: eax=0x000006FB ebx=0x04040800 ecx=0x0000E3BD edx=0xBFEBFBFF
+        0x00000004 0x00: eax=0x0C004121 ebx=0x01C0003F ecx=0x0000003F edx=0x00000001
+        0x00000004 0x01: eax=0x0C004122 ebx=0x01C0003F ecx=0x0000003F edx=0x00000001
+        0x00000004 0x02: eax=0x0C004143 ebx=0x03C0003F ecx=0x00000FFF edx=0x00000001
+        0x00000004 0x03: eax=0x00000000 ebx=0x00000000 ecx=0x00000000 edx=0x00000000";
+
+    #[test]
+    fn test_legacy_core_counts() {
+        let dump = CpuDump::parse(DUMP).expect("Failed to parse dump");
+        with_cpuid_provider(dump, || {
+            assert!(!is_valid_leaf(LEAF_0B));
+            assert_eq!(intel_legacy_core_counts(), (4, 4));
+            assert_eq!(
+                core_counts(),
+                CoreCounts {
+                    logical: 4,
+                    physical: 4
+                }
+            );
+            assert_eq!(Cpu::threads_per_core(), 1);
+        });
+    }
+}
+
+/// A Geode GX1, which has no brand string
+mod geode_gx1 {
+    use super::*;
+
+    const DUMP: &str = "\
+        0x00000000 0x00: eax=0x00000002 ebx=0x646F6547 ecx=0x43534E20 edx=0x79622065
+        0x00000001 0x00: eax=0x00000540 ebx=0x00000000 ecx=0x00000000 edx=0x00808131";
+
+    #[test]
+    fn test_model_string() {
+        let dump = CpuDump::parse(DUMP).expect("Failed to parse dump");
+        with_cpuid_provider(dump, || {
+            let cpu = Cpu::detect();
+            assert_eq!(Cpu::raw_model_string(), UNK);
+            assert_eq!(
+                cpu.display_model_string(),
+                "National Semiconductor Geode GX1"
+            );
+        });
+    }
+}
+
+/// Whether `name` is in one of the feature list categories of `cpu`
+fn lists_feature(cpu: &Cpu, name: &str) -> bool {
+    cpu.features
+        .values()
+        .any(|list| list.split(' ').any(|feature| feature == name))
+}
+
+/// Runs `Cpu::from_dump` over the recorded dumps, checking what each
+/// one is detected as
+mod recorded {
+    use super::*;
+
+    struct Expected {
+        file: &'static str,
+        vendor: &'static str,
+        micro_arch: MicroArch,
+        code_name: &'static str,
+        features: &'static [&'static str],
+    }
+
+    const EXPECTED: &[Expected] = &[
+        Expected {
+            file: "dump/5900XT.txt",
+            vendor: VENDOR_AMD,
+            micro_arch: MicroArch::Zen3,
+            code_name: "Vermeer",
+            features: &["SSE4A", "AVX2", "VPCLMULQDQ"],
+        },
+        Expected {
+            file: "dump/7950x3d.txt",
+            vendor: VENDOR_AMD,
+            micro_arch: MicroArch::Zen4,
+            code_name: "Raphael",
+            features: &["AVX2", "VNNI"],
+        },
+        Expected {
+            file: "dump/2700U.txt",
+            vendor: VENDOR_AMD,
+            micro_arch: MicroArch::Zen,
+            code_name: "Raven Ridge",
+            features: &["SSE4A", "AVX2"],
+        },
+        Expected {
+            file: "dump/12700H.txt",
+            vendor: VENDOR_INTEL,
+            micro_arch: MicroArch::AlderLake,
+            code_name: "Alder Lake-H",
+            features: &["AVX2", "VPCLMULQDQ"],
+        },
+        Expected {
+            file: "dump/m3-8100y.txt",
+            vendor: VENDOR_INTEL,
+            micro_arch: MicroArch::AmberLake,
+            code_name: "Amber Lake-Y",
+            features: &["SSE4.2", "AVX2"],
+        },
+        Expected {
+            file: "dump/eeepc.txt",
+            vendor: VENDOR_INTEL,
+            micro_arch: MicroArch::Dothan,
+            code_name: "Dothan",
+            features: &["SSE2"],
+        },
+        Expected {
+            file: "dump/p6x2.txt",
+            vendor: VENDOR_INTEL,
+            micro_arch: MicroArch::PentiumPro,
+            code_name: "P6",
+            features: &["CMOV"],
+        },
+    ];
+
+    #[test]
+    fn test_recorded_dumps() {
+        for expected in EXPECTED {
+            let text = std::fs::read_to_string(raw_path(expected.file)).expect("Missing dump");
+            let cpu = Cpu::from_dump(&text).expect("Failed to parse dump");
+
+            assert_eq!(cpu.arch.vendor_string, expected.vendor, "{}", expected.file);
+            assert_eq!(
+                cpu.arch.micro_arch, expected.micro_arch,
+                "{}",
+                expected.file
+            );
+            assert_eq!(cpu.arch.code_name, expected.code_name, "{}", expected.file);
+            for feature in expected.features {
+                assert!(lists_feature(&cpu, feature), "{}: {feature}", expected.file);
+            }
+
+            // Checked against the dump, not the CPU running the test
+            assert_eq!(cpu.validate(), Vec::<String>::new(), "{}", expected.file);
+            let decoded = Cpu::from_bytes(&cpu.to_bytes()).expect("Failed to decode");
+            assert_eq!(
+                decoded.validate(),
+                Vec::<String>::new(),
+                "{}",
+                expected.file
+            );
+        }
+    }
+}
+
+/// Hand-made dumps for CPUs there are no captures of, built from documented
+/// values. They only have the leaves the vendor, model and feature checks
+/// need, so they don't cover the cache or topology paths.
+mod synthetic {
+    use super::*;
+
+    /// A Core i7-4770
+    mod haswell {
+        use super::*;
+
+        const DUMP: &str = "\
+            0x00000000 0x00: eax=0x0000000D ebx=0x756E6547 ecx=0x6C65746E edx=0x49656E69
+            0x00000001 0x00: eax=0x000306C3 ebx=0x00100800 ecx=0x7FFAFBFF edx=0xBFEBFBFF
+            0x00000007 0x00: eax=0x00000000 ebx=0x000027AB ecx=0x00000000 edx=0x00000000
+            0x80000000 0x00: eax=0x80000008 ebx=0x00000000 ecx=0x00000000 edx=0x00000000
+            0x80000001 0x00: eax=0x00000000 ebx=0x00000000 ecx=0x00000021 edx=0x2C100800
+            0x80000002 0x00: eax=0x65746E49 ebx=0x2952286C ecx=0x726F4320 edx=0x4D542865
+            0x80000003 0x00: eax=0x37692029 ebx=0x3737342D ecx=0x50432030 edx=0x20402055
+            0x80000004 0x00: eax=0x30342E33 ebx=0x007A4847 ecx=0x00000000 edx=0x00000000
+            0x80000008 0x00: eax=0x00003027 ebx=0x00000000 ecx=0x00000000 edx=0x00000000";
+
+        #[test]
+        fn test_from_dump() {
+            let cpu = Cpu::from_dump(DUMP).expect("Failed to parse dump");
+
+            assert_eq!(cpu.arch.vendor_string, VENDOR_INTEL);
+            assert_eq!(cpu.arch.micro_arch, MicroArch::Haswell);
+            assert_eq!(cpu.arch.code_name, "Haswell");
+            assert_eq!(cpu.arch.model, "Intel(R) Core(TM) i7-4770 CPU @ 3.40GHz");
+            for feature in ["AVX2", "FMA", "BMI2", "FSGSBASE"] {
+                assert!(lists_feature(&cpu, feature), "{feature}");
+            }
+            assert!(!lists_feature(&cpu, "AVX512F"));
+        }
+    }
+
+    /// A Pentium III (Coppermine)
+    mod pentium_iii {
+        use super::*;
+
+        const DUMP: &str = "\
+            0x00000000 0x00: eax=0x00000002 ebx=0x756E6547 ecx=0x6C65746E edx=0x49656E69
+            0x00000001 0x00: eax=0x00000683 ebx=0x00000002 ecx=0x00000000 edx=0x0383FBFF
+            0x00000002 0x00: eax=0x03020101 ebx=0x00000000 ecx=0x00000000 edx=0x0C040882";
+
+        #[test]
+        fn test_from_dump() {
+            let cpu = Cpu::from_dump(DUMP).expect("Failed to parse dump");
+
+            assert_eq!(cpu.arch.vendor_string, VENDOR_INTEL);
+            assert_eq!(cpu.arch.micro_arch, MicroArch::PentiumIII);
+            assert_eq!(cpu.arch.code_name, "Coppermine");
+            assert!(lists_feature(&cpu, "SSE"));
+            assert!(!lists_feature(&cpu, "SSE2"));
+        }
+    }
+
+    /// An AMD K6-2
+    mod k6_2 {
+        use super::*;
+
+        const DUMP: &str = "\
+            0x00000000 0x00: eax=0x00000001 ebx=0x68747541 ecx=0x444D4163 edx=0x69746E65
+            0x00000001 0x00: eax=0x0000058C ebx=0x00000000 ecx=0x00000000 edx=0x008021BF
+            0x80000000 0x00: eax=0x80000005 ebx=0x00000000 ecx=0x00000000 edx=0x00000000
+            0x80000001 0x00: eax=0x0000058C ebx=0x00000000 ecx=0x00000000 edx=0x808029BF
+            0x80000002 0x00: eax=0x2D444D41 ebx=0x7428364B ecx=0x3320296D edx=0x72702044
+            0x80000003 0x00: eax=0x7365636F ebx=0x00726F73 ecx=0x00000000 edx=0x00000000
+            0x80000004 0x00: eax=0x00000000 ebx=0x00000000 ecx=0x00000000 edx=0x00000000
+            0x80000005 0x00: eax=0x00000000 ebx=0x02800140 ecx=0x20020220 edx=0x20020220";
+
+        #[test]
+        fn test_from_dump() {
+            let cpu = Cpu::from_dump(DUMP).expect("Failed to parse dump");
+
+            assert_eq!(cpu.arch.vendor_string, VENDOR_AMD);
+            assert_eq!(cpu.arch.micro_arch, MicroArch::K6);
+            assert_eq!(cpu.arch.code_name, "Chompers/CXT");
+            assert!(lists_feature(&cpu, "MMX"));
+            assert!(lists_feature(&cpu, "3DNow!"));
+            assert!(!lists_feature(&cpu, "CMOV"));
+        }
+
+        #[test]
+        fn test_simd_extensions() {
+            let dump = CpuDump::parse(DUMP).expect("Failed to parse dump");
+            with_cpuid_provider(dump, || {
+                assert!(has_mmx());
+                assert!(has_3dnow());
+                assert!(!has_mmx_plus());
+                assert!(!has_3dnow_plus());
+            });
+
+            // With the extensions a K7 adds
+            let text = patch_dump(DUMP, &[("edx=0x808029BF", "edx=0xC0C029BF")]);
+            let dump = CpuDump::parse(&text).expect("Failed to parse dump");
+            with_cpuid_provider(dump, || {
+                assert!(has_mmx_plus());
+                assert!(has_3dnow_plus());
+            });
+        }
+    }
+
+    /// An Atom N270 (Diamondville)
+    mod atom_n270 {
+        use super::*;
+
+        const DUMP: &str = "\
+            0x00000000 0x00: eax=0x0000000A ebx=0x756E6547 ecx=0x6C65746E edx=0x49656E69
+            0x00000001 0x00: eax=0x000106C2 ebx=0x01020800 ecx=0x0040C39D edx=0xBFE9FBFF
+            0x80000000 0x00: eax=0x80000008 ebx=0x00000000 ecx=0x00000000 edx=0x00000000
+            0x80000001 0x00: eax=0x00000000 ebx=0x00000000 ecx=0x00000001 edx=0x00100000
+            0x80000002 0x00: eax=0x65746E49 ebx=0x2952286C ecx=0x6F744120 edx=0x4D54286D
+            0x80000003 0x00: eax=0x50432029 ebx=0x324E2055 ecx=0x20203037 edx=0x31204020
+            0x80000004 0x00: eax=0x4730362E ebx=0x00007A48 ecx=0x00000000 edx=0x00000000
+            0x80000008 0x00: eax=0x00002020 ebx=0x00000000 ecx=0x00000000 edx=0x00000000";
+
+        #[test]
+        fn test_from_dump() {
+            let cpu = Cpu::from_dump(DUMP).expect("Failed to parse dump");
+
+            assert_eq!(cpu.arch.vendor_string, VENDOR_INTEL);
+            assert_eq!(cpu.arch.micro_arch, MicroArch::Bonnel);
+            assert_eq!(cpu.arch.code_name, "Silverthorne/Diamondville");
+            for feature in ["SSSE3", "HT"] {
+                assert!(lists_feature(&cpu, feature), "{feature}");
+            }
+            assert!(!lists_feature(&cpu, "SSE4.1"));
+        }
+    }
+}
+
+/// A Hygon Dhyana (family 18h), built from documented values
+mod hygon_dhyana {
+    use super::*;
+
+    const DUMP: &str = "\
+        0x00000000 0x00: eax=0x0000000D ebx=0x6F677948 ecx=0x656E6975 edx=0x6E65476E
+        0x00000001 0x00: eax=0x00900F01 ebx=0x00100800 ecx=0x7ED8320B edx=0x178BFBFF";
+
+    #[test]
+    fn test_from_dump() {
+        let cpu = Cpu::from_dump(DUMP).expect("Failed to parse dump");
+
+        assert_eq!(cpu.arch.vendor_string, VENDOR_HYGON);
+        assert_eq!(cpu.arch.brand_name, "Hygon");
+        assert_eq!(cpu.arch.micro_arch, MicroArch::Zen);
+        assert_eq!(cpu.arch.code_name, "Dhyana");
+    }
+
+    #[test]
+    fn test_amd_vendor_string() {
+        // Some BIOSes have the CPU report AuthenticAMD
+        let text = patch_dump(
+            DUMP,
+            &[(
+                "ebx=0x6F677948 ecx=0x656E6975 edx=0x6E65476E",
+                "ebx=0x68747541 ecx=0x444D4163 edx=0x69746E65",
+            )],
+        );
+        let cpu = Cpu::from_dump(&text).expect("Failed to parse dump");
+
+        assert_eq!(cpu.arch.vendor_string, VENDOR_AMD);
+        assert_eq!(cpu.arch.brand_name, "Hygon");
+        assert_eq!(cpu.arch.micro_arch, MicroArch::Zen);
+        assert_eq!(cpu.arch.code_name, "Dhyana");
+    }
+
+    #[test]
+    fn test_core_counts() {
+        // Dhyana is a Zen, so a Zen dump with the Hygon vendor string
+        // has the same counts
+        let dump = patched_dump(
+            "dump/2700U.txt",
+            &[(
+                "ebx=0x68747541 ecx=0x444D4163 edx=0x69746E65",
+                "ebx=0x6F677948 ecx=0x656E6975 edx=0x6E65476E",
+            )],
+        );
+        with_cpuid_provider(dump, || {
+            assert!(is_hygon());
+            assert_eq!(
+                core_counts(),
+                CoreCounts {
+                    logical: 8,
+                    physical: 4
+                }
+            );
+            assert_eq!(amd_threads_per_core(), 2);
+            assert_eq!(Cpu::threads_per_core(), 2);
+        });
+    }
+
+    #[test]
+    fn test_amd_feature_flags() {
+        // Hygon sets the same vendor-specific flags as AMD
+        let dump = patched_dump(
+            "dump/2700U.txt",
+            &[(
+                "ebx=0x68747541 ecx=0x444D4163 edx=0x69746E65",
+                "ebx=0x6F677948 ecx=0x656E6975 edx=0x6E65476E",
+            )],
+        );
+        with_cpuid_provider(dump, || {
+            assert!(has_mmx_plus());
+            assert!(has_sse4a());
+            assert!(amd_security_features().clzero);
+            assert!(amd_sev().sev);
+
+            let features = get_feature_list();
+            let base = features.get("Base").expect("Missing Base features");
+            assert!(base.split(' ').any(|name| name == "MMX+"));
+        });
+    }
+}
+
+/// An emulator that reports a vendor string, but no leaf 1
+mod leaf0_only {
+    use super::*;
+
+    fn with_mock_cpu(test: impl FnOnce()) {
+        set_file_cpuid_provider("dump/leaf0-only.txt");
+        test();
+    }
+
+    #[test]
+    fn test_no_leaf_1_signature() {
+        with_mock_cpu(|| {
+            assert_eq!(max_leaf(), 0);
+            assert_eq!(&*vendor_str(), VENDOR_INTEL);
+            assert_eq!(CpuSignature::detect(), CpuSignature::default());
+        });
+    }
+
+    #[test]
+    fn test_no_leaf_1_model_string() {
+        with_mock_cpu(|| {
+            let cpu = Cpu::detect();
+            assert!(cpu.display_model_string().starts_with("'Classic' "));
+        });
+    }
+}
+
 mod m3_8100y {
     use super::*;
 
@@ -185,6 +736,144 @@ mod m3_8100y {
         });
     }
 
+    #[test]
+    fn test_intel_threads_per_core() {
+        with_mock_cpu(|| {
+            assert_eq!(Cpu::threads_per_core(), 2);
+        });
+    }
+
+    #[test]
+    fn test_intel_processor_trace() {
+        // Amber Lake has Processor Trace, but not PTWRITE
+        with_mock_cpu(|| {
+            assert!(has_pt());
+            assert!(!has_ptwrite());
+
+            let pt = processor_trace();
+            assert!(pt.ip_filter);
+            assert!(!pt.ptwrite);
+            assert_eq!(pt.address_ranges, 2);
+        });
+    }
+
+    #[test]
+    fn test_intel_brand_frequency() {
+        with_mock_cpu(|| {
+            assert_eq!(brand_frequency_mhz(), Some(1100));
+        });
+    }
+
+    #[test]
+    fn test_unknown_vendor_string() {
+        // The same CPU, with a vendor string rustid doesn't know
+        let dump = patched_dump(
+            "dump/m3-8100y.txt",
+            &[(
+                "ebx=0x756e6547 ecx=0x6c65746e edx=0x49656e69",
+                "ebx=0x5453694d ecx=0x3638344f edx=0x41207265",
+            )],
+        );
+        with_cpuid_provider(dump, || {
+            let cpu = Cpu::detect();
+            assert_eq!(cpu.arch.vendor_string, "MiSTer AO486");
+            assert_eq!(cpu.arch.brand_name, UNK);
+            assert_eq!(CpuBrand::detect(), CpuBrand::Other(*b"MiSTer AO486"));
+
+            let vendor = cpu
+                .as_pairs()
+                .into_iter()
+                .find(|(key, _)| *key == "Vendor")
+                .map(|(_, value)| value);
+            assert_eq!(vendor.as_deref(), Some("MiSTer AO486 (Unknown)"));
+
+            let mut out = String::new();
+            cpu.emit(CliFlags::default(), &mut out);
+            assert!(out.contains("MiSTer AO486 (Unknown)"));
+        });
+    }
+
+    #[test]
+    fn test_intel_tsx() {
+        // TSX disabled by microcode
+        with_mock_cpu(|| {
+            assert!(!has_rtm());
+            assert!(!has_hle());
+            assert!(has_rtm_always_abort());
+            assert!(!has_tsx());
+        });
+
+        // The same CPU, still advertising RTM and HLE
+        let dump = patched_dump("dump/m3-8100y.txt", &[("ebx=0x029c67af", "ebx=0x029c6fbf")]);
+        with_cpuid_provider(dump, || {
+            assert!(has_rtm());
+            assert!(has_hle());
+            assert!(!has_tsx());
+
+            let features = get_feature_list();
+            let other = features.get("Other").expect("Missing Other features");
+            assert!(!other.contains("TSX"));
+        });
+
+        // ...and with TSX left enabled
+        let dump = patched_dump(
+            "dump/m3-8100y.txt",
+            &[
+                ("ebx=0x029c67af", "ebx=0x029c6fbf"),
+                ("edx=0xbc002e00", "edx=0xbc002600"),
+            ],
+        );
+        with_cpuid_provider(dump, || {
+            assert!(has_tsx());
+
+            let features = get_feature_list();
+            let other = features.get("Other").expect("Missing Other features");
+            assert!(other.contains("TSX"));
+        });
+    }
+
+    #[test]
+    fn test_intel_mpx() {
+        with_mock_cpu(|| {
+            assert!(has_mpx());
+
+            let features = get_feature_list();
+            let security = features.get("Security").expect("Missing Security features");
+            assert!(security.contains("MPX"));
+        });
+    }
+
+    #[test]
+    fn test_intel_core_counts() {
+        let leaves = record_leaves("dump/m3-8100y.txt", || {
+            assert_eq!(
+                core_counts(),
+                CoreCounts {
+                    logical: 4,
+                    physical: 2
+                }
+            );
+        });
+
+        assert!(leaves.contains(&LEAF_0B));
+        assert!(!leaves.contains(&EXT_LEAF_8));
+    }
+
+    #[test]
+    fn test_intel_quick_vendor() {
+        with_mock_cpu(|| {
+            assert_eq!(quick_vendor(), CpuBrand::Intel);
+            assert_eq!(quick_vendor(), CpuBrand::detect());
+        });
+    }
+
+    #[test]
+    fn test_intel_lfence_serializing() {
+        with_mock_cpu(|| {
+            assert_eq!(lfence_serializing(), Some(true));
+        });
+    }
+
     #[test]
     fn test_intel_brand_string() {
         with_mock_cpu(|| {
@@ -245,6 +934,28 @@ mod m3_8100y {
         });
     }
 
+    #[test]
+    fn test_intel_address_bits() {
+        with_mock_cpu(|| {
+            let bits = address_bits();
+            assert_eq!(bits.physical, 39);
+            assert_eq!(bits.linear, 48);
+            assert!(!bits.la57);
+            assert!(!bits.virtualized);
+
+            let cpu = Cpu::detect();
+            assert_eq!(cpu.address_bits, bits);
+            assert!(cpu.phys_addr_trustworthy());
+        });
+    }
+
+    #[test]
+    fn test_intel_no_hybrid_core_type() {
+        with_mock_cpu(|| {
+            assert_eq!(core_type(), None);
+        });
+    }
+
     #[test]
     fn test_intel_feature_class() {
         with_mock_cpu(|| {
@@ -332,129 +1043,923 @@ mod m3_8100y {
         });
     }
 
-    #[test]
-    fn test_intel_avx2_support() {
-        with_mock_cpu(|| {
-            assert!(has_avx2());
-        });
+    #[test]
+    fn test_intel_avx2_support() {
+        with_mock_cpu(|| {
+            assert!(has_avx2());
+        });
+    }
+
+    #[test]
+    fn test_intel_aes_support() {
+        with_mock_cpu(|| {
+            assert!(has_aes());
+        });
+    }
+
+    #[test]
+    fn test_intel_fpu_support() {
+        with_mock_cpu(|| {
+            assert!(has_fpu());
+        });
+    }
+
+    #[test]
+    fn test_intel_tsc_support() {
+        with_mock_cpu(|| {
+            assert!(has_tsc());
+        });
+    }
+
+    #[test]
+    fn test_intel_mmx_support() {
+        with_mock_cpu(|| {
+            assert!(has_mmx());
+        });
+    }
+
+    #[test]
+    fn test_intel_ssse3_support() {
+        with_mock_cpu(|| {
+            assert!(has_ssse3());
+        });
+    }
+
+    #[test]
+    fn test_intel_fma_support() {
+        with_mock_cpu(|| {
+            assert!(has_fma());
+        });
+    }
+
+    #[test]
+    fn test_intel_cx16_support() {
+        with_mock_cpu(|| {
+            assert!(has_cx16());
+        });
+    }
+
+    #[test]
+    fn test_intel_rdrand_support() {
+        with_mock_cpu(|| {
+            assert!(has_rdrand());
+        });
+    }
+
+    #[test]
+    fn test_intel_bmi1_support() {
+        with_mock_cpu(|| {
+            assert!(has_bmi1());
+        });
+    }
+
+    #[test]
+    fn test_intel_bmi2_support() {
+        with_mock_cpu(|| {
+            assert!(has_bmi2());
+        });
+    }
+
+    #[test]
+    fn test_intel_f16c_support() {
+        with_mock_cpu(|| {
+            assert!(has_f16c());
+        });
+    }
+}
+
+mod intel_12700h {
+    use super::*;
+
+    fn with_mock_cpu(test: impl FnOnce()) {
+        set_file_cpuid_provider("dump/12700H.txt");
+        test();
+    }
+
+    #[test]
+    fn test_brand_signature_consistent() {
+        with_mock_cpu(|| {
+            let cpu = Cpu::detect();
+            assert!(cpu.brand_signature_consistent());
+        });
+    }
+
+    #[test]
+    fn test_hybrid_core_counts() {
+        // 6 P-cores with two threads each, and 8 E-cores with one
+        with_mock_cpu(|| {
+            assert_eq!(
+                core_counts(),
+                CoreCounts {
+                    logical: 20,
+                    physical: 14
+                }
+            );
+
+            let cpu = Cpu::detect();
+            assert_eq!(cpu.topology.cores.count, 14);
+            assert_eq!(cpu.topology.threads.count, 20);
+        });
+    }
+
+    #[test]
+    fn test_detect_all() {
+        with_mock_cpu(|| {
+            let all = Cpu::detect_all();
+            assert_eq!(all.len(), 2);
+            assert_eq!(all[0].core_type, Some(CoreType::Performance));
+            assert_eq!(all[1].core_type, Some(CoreType::Efficiency));
+
+            for cpu in &all {
+                assert_eq!(cpu.cores.len(), 2);
+                assert_eq!(cpu.signature, all[0].signature);
+            }
+
+            // Detection afterwards still reads the first CPU of the dump
+            assert_eq!(Cpu::detect().core_type, Some(CoreType::Performance));
+        });
+    }
+
+    #[test]
+    fn test_tsc_aux_matches_apic() {
+        // The dump has RDTSCP, but RDTSCP would run on this machine
+        with_mock_cpu(|| {
+            assert!(has_rdtscp());
+            assert_eq!(Cpu::tsc_aux_matches_apic(), None);
+        });
+    }
+
+    #[test]
+    fn test_intel_system_features() {
+        with_mock_cpu(|| {
+            assert!(has_vme() && has_de() && has_pse() && has_msr());
+            assert!(has_apic() && has_x2apic());
+            assert!(has_tsc_deadline());
+            assert!(!has_dca());
+            assert!(Cpu::detect().features["Other"].contains("TSC-Deadline"));
+        });
+    }
+
+    #[test]
+    fn test_intel_processor_trace() {
+        with_mock_cpu(|| {
+            assert!(has_pt());
+            assert!(has_ptwrite());
+            assert!(Cpu::detect().features["Other"].contains("PT"));
+
+            let pt = processor_trace();
+            assert!(pt.cr3_filter && pt.cycle_accurate && pt.ip_filter && pt.mtc);
+            assert!(pt.ptwrite);
+            assert!(!pt.power_event_trace);
+            assert!(pt.topa && pt.topa_multi_entry && pt.single_range);
+            assert!(!pt.trace_transport && !pt.lip);
+            assert_eq!(pt.address_ranges, 2);
+        });
+    }
+
+    #[test]
+    fn test_intel_spec_ctrl() {
+        with_mock_cpu(|| {
+            let spec = intel_spec_ctrl();
+            assert!(spec.ibrs_ibpb && spec.stibp && spec.ssbd);
+            assert!(spec.l1d_flush);
+            assert!(spec.arch_capabilities);
+            assert!(spec.md_clear);
+            assert!(!spec.core_capabilities);
+
+            // AMD's bits are in a different leaf
+            assert_eq!(amd_security_features(), AmdSecFeatures::default());
+        });
+    }
+
+    #[test]
+    fn test_intel_avx10() {
+        with_mock_cpu(|| {
+            assert!(!has_avx10());
+            assert_eq!(avx10(), None);
+        });
+
+        // The same CPU, with AVX10.2 and every vector length
+        let dump = patched_dump(
+            "dump/12700H.txt",
+            &[
+                (
+                    "0x00000000 0x00: eax=0x00000020",
+                    "0x00000000 0x00: eax=0x00000024",
+                ),
+                (
+                    "0x00000007 0x01: eax=0x00400810 ebx=0x00000000 ecx=0x00000000 edx=0x00000000",
+                    "0x00000007 0x01: eax=0x00400810 ebx=0x00000000 ecx=0x00000000 edx=0x00080000\n\
+                     0x00000024 0x00: eax=0x00000000 ebx=0x00070002 ecx=0x00000000 edx=0x00000000",
+                ),
+            ],
+        );
+        with_cpuid_provider(dump, || {
+            assert!(has_avx10());
+            assert_eq!(
+                avx10(),
+                Some(Avx10Info {
+                    version: 2,
+                    max_vl: 512
+                })
+            );
+        });
+    }
+
+    #[test]
+    fn test_intel_validate() {
+        with_mock_cpu(|| {
+            assert!(!leaf_7_1_unreliable());
+            let cpu = Cpu::detect();
+            assert_eq!(cpu.validate(), Vec::<String>::new());
+            assert_eq!(rustid::exit_code(&cpu), 0);
+        });
+
+        // The same CPU, as seen by a VM that drops sub-leaf 1
+        let dump = patched_dump(
+            "dump/12700H.txt",
+            &[(
+                "0x00000007 0x01: eax=0x00400810",
+                "0x00000007 0x01: eax=0x00000000",
+            )],
+        );
+        with_cpuid_provider(dump, || {
+            assert!(leaf_7_1_unreliable());
+            let cpu = Cpu::detect();
+            assert!(
+                cpu.validate()
+                    .iter()
+                    .any(|problem| problem.contains("sub-leaf 1"))
+            );
+            assert_eq!(rustid::exit_code(&cpu), rustid::EXIT_INCONSISTENT);
+        });
+    }
+
+    #[test]
+    fn test_intel_no_avx512() {
+        with_mock_cpu(|| {
+            let cpu = Cpu::detect();
+            assert!(!cpu.has_any_avx512());
+            assert!(cpu.avx512_subfeatures().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_intel_no_brand_frequency() {
+        with_mock_cpu(|| {
+            assert_eq!(brand_frequency_mhz(), None);
+        });
+    }
+
+    #[test]
+    fn test_intel_baseline_features() {
+        with_mock_cpu(|| {
+            let cpu = Cpu::detect();
+            let arch = cpu.arch.micro_arch;
+            assert!(arch.baseline_features().contains("AVX2"));
+            assert!(arch.missing_baseline_features().is_empty());
+            assert!(arch.feature_contradictions(cpu.feature_set()).is_empty());
+        });
+    }
+
+    #[test]
+    fn test_intel_serialize() {
+        with_mock_cpu(|| {
+            assert!(has_serialize());
+            serialize();
+        });
+    }
+
+    #[test]
+    fn test_intel_fast_msr_features() {
+        with_mock_cpu(|| {
+            assert!(!has_wrmsrns());
+            assert!(!has_msrlist());
+        });
+
+        // The same CPU, reporting WRMSRNS and MSRLIST in leaf 7, sub-leaf 1
+        let dump = patched_dump(
+            "dump/12700H.txt",
+            &[(
+                "0x00000007 0x01: eax=0x00400810",
+                "0x00000007 0x01: eax=0x08480810",
+            )],
+        );
+        with_cpuid_provider(dump, || {
+            assert!(has_wrmsrns());
+            assert!(has_msrlist());
+        });
+    }
+
+    #[test]
+    fn test_intel_fsgsbase_pkru_usable() {
+        with_mock_cpu(|| {
+            assert!(has_fsgsbase());
+            // The OS state doesn't apply to a dump
+            assert_eq!(fsgsbase_usable(), None);
+            assert!(!pkru_usable());
+        });
+
+        let pkru = |ecx: &str| {
+            let dump = patched_dump("dump/12700H.txt", &[("ecx=0x184007A4", ecx)]);
+            with_cpuid_provider(dump, pkru_usable)
+        };
+
+        // PKU, without the OS setting CR4.PKE
+        assert!(!pkru("ecx=0x184007AC"));
+        // PKU and OSPKE
+        assert!(pkru("ecx=0x184007BC"));
+    }
+
+    #[test]
+    fn test_intel_leaf1f_levels() {
+        with_mock_cpu(|| {
+            // SMT, then core
+            assert_eq!(leaf1f_levels(), [(1, 1), (2, 7)]);
+        });
+
+        // Sub-leaves that never end, as a broken hypervisor might report
+        let mut text = String::from(
+            "0x00000000 0x00: eax=0x0000001F ebx=0x756E6547 ecx=0x6C65746E edx=0x49656E69\n",
+        );
+        for sub_leaf in 0..16 {
+            text.push_str(&format!(
+                "0x0000001F 0x{sub_leaf:02X}: eax=0x00000001 ebx=0x00000001 ecx=0x00000100 edx=0x00000000\n"
+            ));
+        }
+        let dump = CpuDump::parse(&text).expect("Failed to parse dump");
+        with_cpuid_provider(dump, || {
+            assert_eq!(leaf1f_levels(), [(1, 1); 8]);
+        });
+    }
+
+    #[test]
+    fn test_intel_waitpkg() {
+        with_mock_cpu(|| {
+            assert!(has_waitpkg());
+            // The OS state and the MSR don't apply to a dump
+            assert_eq!(waitpkg_usable(), None);
+            assert_eq!(umwait_max_time(), None);
+        });
+
+        // The same CPU, without WAITPKG
+        let dump = patched_dump("dump/12700H.txt", &[("ecx=0x184007A4", "ecx=0x18400784")]);
+        with_cpuid_provider(dump, || {
+            assert!(!has_waitpkg());
+            assert_eq!(waitpkg_usable(), Some(false));
+        });
+    }
+
+    #[test]
+    fn test_intel_lam() {
+        with_mock_cpu(|| {
+            assert!(!has_lam());
+        });
+
+        // The same CPU, reporting LAM in leaf 7, sub-leaf 1
+        let dump = patched_dump(
+            "dump/12700H.txt",
+            &[(
+                "0x00000007 0x01: eax=0x00400810",
+                "0x00000007 0x01: eax=0x04400810",
+            )],
+        );
+        with_cpuid_provider(dump, || {
+            assert!(has_lam());
+            assert!(get_feature_list().values().any(|v| v.contains("LAM")));
+        });
+    }
+
+    #[test]
+    fn test_intel_thread_director() {
+        with_mock_cpu(|| {
+            assert!(has_hybrid());
+            assert_eq!(
+                thread_director(),
+                Some(ThreadDirector {
+                    classes: 4,
+                    hfi: true,
+                    hreset: true,
+                })
+            );
+        });
+    }
+
+    #[test]
+    fn test_intel_hreset() {
+        with_mock_cpu(|| {
+            assert!(has_hreset());
+            let hreset = history_reset().expect("Missing leaf 0x20");
+            assert!(hreset.thread_director());
+        });
+    }
+
+    #[test]
+    fn test_intel_cpuid_maxval_limited() {
+        with_mock_cpu(|| {
+            assert_eq!(cpuid_maxval_limited(), Some(false));
+        });
+
+        // The same CPU, with the BIOS limiting leaf 0 to a max of 3
+        let dump = patched_dump(
+            "dump/12700H.txt",
+            &[(
+                "0x00000000 0x00: eax=0x00000020",
+                "0x00000000 0x00: eax=0x00000003",
+            )],
+        );
+        let cpu = with_cpuid_provider(dump, || {
+            assert_eq!(max_leaf(), 3);
+            assert_eq!(cpuid_maxval_limited(), Some(true));
+            Cpu::detect()
+        });
+
+        // Shown from the detected values, not the CPU running the test
+        let mut out = String::new();
+        cpu.emit(CliFlags::default(), &mut out);
+        assert!(out.contains("CPUID limited to leaf 3 by the BIOS"));
+    }
+
+    #[test]
+    fn test_intel_vendor_detection() {
+        with_mock_cpu(|| {
+            assert_eq!(&*vendor_str(), VENDOR_INTEL);
+        });
+    }
+
+    #[test]
+    fn test_intel_hybrid_core_type() {
+        with_mock_cpu(|| {
+            set_dump_cpu(0);
+            assert_eq!(core_type(), Some(CoreType::Performance));
+
+            set_dump_cpu(12);
+            assert_eq!(core_type(), Some(CoreType::Efficiency));
+
+            set_dump_cpu(0);
+        });
+    }
+
+    #[test]
+    fn test_intel_detect_on_all_cores() {
+        with_mock_cpu(|| {
+            let cores = Cpu::detect_on_all_cores();
+            assert_eq!(cores.len(), 20);
+
+            assert_eq!(cores[0].cpu, 0);
+            assert_eq!(cores[0].core_type, Some(CoreType::Performance));
+            assert_eq!(cores[12].cpu, 12);
+            assert_eq!(cores[12].core_type, Some(CoreType::Efficiency));
+            assert!(cores.iter().all(|c| c.signature == cores[0].signature));
+
+            // The previously selected CPU is put back afterwards
+            assert_eq!(core_type(), Some(CoreType::Performance));
+        });
+    }
+
+    #[test]
+    fn test_intel_address_bits_virtualized() {
+        with_mock_cpu(|| {
+            let bits = address_bits();
+            assert_eq!(bits.physical, 39);
+            assert_eq!(bits.linear, 48);
+            assert!(!bits.la57);
+            assert!(bits.virtualized);
+            assert!(!Cpu::detect().phys_addr_trustworthy());
+        });
+    }
+
+    #[test]
+    fn test_intel_pmem_capabilities() {
+        with_mock_cpu(|| {
+            let pmem = Cpu::detect().pmem;
+            assert!(pmem.clflush);
+            assert!(pmem.clflushopt);
+            assert!(pmem.clwb);
+            assert!(pmem.fpu_csds_deprecated);
+            assert_eq!(pmem.flush_line_size, 64);
+        });
+    }
+
+    #[test]
+    fn test_intel_leaf_7_subleaf_1_features() {
+        with_mock_cpu(|| {
+            // Alder Lake reports sub-leaf 1, but predates CMPccXADD and AVX-IFMA
+            assert!(x86_cpuid(LEAF_7).eax >= 1);
+            assert!(!has_cmpccxadd());
+            assert!(!has_avx_ifma());
+        });
+    }
+
+    #[test]
+    fn test_intel_no_mpx() {
+        // Dropped after Ice Lake
+        with_mock_cpu(|| {
+            assert!(!has_mpx());
+        });
+    }
+
+    #[test]
+    fn test_intel_cet() {
+        with_mock_cpu(|| {
+            assert!(has_cet_ss());
+            assert!(has_cet_ibt());
+
+            let features = get_feature_list();
+            let security = features.get("Security").expect("Missing Security features");
+            assert!(security.contains("CET-SS"));
+            assert!(security.contains("CET-IBT"));
+        });
+    }
+
+    #[test]
+    fn test_intel_fsgsbase() {
+        with_mock_cpu(|| {
+            assert!(has_fsgsbase());
+
+            let features = get_feature_list();
+            let other = features.get("Other").expect("Missing Other features");
+            assert!(other.contains("FSGSBASE"));
+        });
+    }
+
+    #[test]
+    fn test_intel_no_rdt() {
+        with_mock_cpu(|| {
+            assert!(!has_rdt_a());
+            assert_eq!(rdt_allocation(), None);
+            assert!(!has_rdt_m());
+            assert_eq!(rdt_monitoring(), None);
+        });
+    }
+
+    #[test]
+    fn test_intel_emit_to_string() {
+        with_mock_cpu(|| {
+            let mut out = String::new();
+            Cpu::detect().emit(CliFlags::default(), &mut out);
+
+            assert!(out.contains("GenuineIntel (Intel)"));
+            assert!(out.contains("Alder Lake"));
+            assert!(!out.contains('\x1b'));
+        });
+    }
+
+    #[test]
+    fn test_intel_emit_from_dump() {
+        // Nothing about the CPU running the test should show up
+        let text = std::fs::read_to_string(raw_path("dump/12700H.txt")).expect("Missing dump");
+        let cpu = Cpu::from_dump(&text).expect("Failed to parse dump");
+        assert_eq!(cpu.mode, None);
+
+        let mut flags = CliFlags::default();
+        flags.verbose = true;
+        let mut out = String::new();
+        cpu.emit(flags, &mut out);
+
+        assert!(out.contains("Alder Lake"));
+        assert!(!out.contains("CPU Mode"));
+        assert!(!out.contains("TME"));
+        // The dump doesn't record XCR0
+        assert!(cpu.os_disabled.is_empty());
+        assert!(!out.contains("OS Disabled"));
+
+        // The same CPU, under an OS that hasn't set OSXSAVE
+        let dump = patched_dump("dump/12700H.txt", &[("ecx=0xFFFAF38B", "ecx=0xF7FAF38B")]);
+        let cpu = with_cpuid_provider(dump, Cpu::detect);
+        assert!(cpu.os_disabled.contains("AVX2"));
+
+        let mut out = String::new();
+        cpu.emit(flags, &mut out);
+        assert!(out.contains("OS Disabled"));
+
+        // The same CPU, with TME
+        let dump = patched_dump("dump/12700H.txt", &[("ecx=0x184007A4", "ecx=0x184027A4")]);
+        let cpu = with_cpuid_provider(dump, Cpu::detect);
+        assert!(cpu.has_tme);
+        assert_eq!(cpu.tme, None);
+
+        let mut out = String::new();
+        cpu.emit(flags, &mut out);
+        assert!(out.contains("TME"));
+    }
+
+    #[test]
+    fn test_intel_as_pairs() {
+        with_mock_cpu(|| {
+            let pairs = Cpu::detect().as_pairs();
+            let get = |key: &str| {
+                pairs
+                    .iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| v.as_str())
+            };
+
+            assert_eq!(get("Vendor"), Some("GenuineIntel (Intel)"));
+            assert_eq!(get("MicroArch"), Some("Alder Lake"));
+            assert_eq!(get("Signature"), Some("Family 6h, Model 9Ah, Stepping 3h"));
+            assert!(get("Features").is_some_and(|f| f.contains("AVX2")));
+            assert_eq!(get("Package"), None);
+            assert_eq!(pairs.first().map(|(k, _)| *k), Some("Architecture"));
+        });
+    }
+
+    #[test]
+    fn test_intel_to_markdown() {
+        with_mock_cpu(|| {
+            let cpu = Cpu::detect();
+            let md = cpu.to_markdown();
+            let mut lines = md.lines();
+
+            assert_eq!(lines.next(), Some("| Field | Value |"));
+            assert_eq!(lines.next(), Some("|-------|-------|"));
+            assert!(md.contains("| MicroArch | Alder Lake |\n"));
+            assert_eq!(md.lines().count(), cpu.as_pairs().len() + 2);
+        });
+    }
+
+    #[test]
+    fn test_intel_emit_cpuz() {
+        with_mock_cpu(|| {
+            let mut out = String::new();
+            Cpu::detect().emit_cpuz(CliFlags::default(), &mut out);
+
+            assert!(out.contains("Code Name: Alder Lake-H"));
+            assert!(out.contains("Specification: 12th Gen Intel(R) Core(TM) i7-12700H"));
+            assert!(out.contains("Ext. Model: 9A"));
+            assert!(out.contains("SSE4.2, EM64T, AES, AVX,"));
+            assert!(!out.contains("Package"));
+            assert!(out.lines().all(|line| line == line.trim_end()));
+        });
+
+        // Only the Cpu is used, not whatever CPU is current
+        let dump = CpuDump::parse_file(raw_path("dump/12700H.txt"));
+        let cpu = with_cpuid_provider(dump, Cpu::detect);
+        set_file_cpuid_provider("dump/5900XT.txt");
+
+        let mut out = String::new();
+        cpu.emit_cpuz(CliFlags::default(), &mut out);
+        assert!(out.contains("SSE4.2, EM64T, AES, AVX,"));
+        assert!(!out.contains("Package"));
+    }
+
+    #[test]
+    fn test_intel_fast_rep_movsb() {
+        with_mock_cpu(|| {
+            assert!(has_erms());
+            assert!(has_fsrm());
+
+            let features = get_feature_list();
+            let other = features.get("Other").expect("Missing Other features");
+            assert!(other.contains("ERMS"));
+            assert!(other.contains("FSRM"));
+        });
+    }
+
+    #[test]
+    fn test_intel_no_amx() {
+        with_mock_cpu(|| {
+            assert!(!has_amx_tile());
+            assert!(!has_amx_bf16());
+            assert!(!has_amx_int8());
+            assert!(!get_feature_list().contains_key("AMX"));
+        });
+    }
+}
+
+mod amd_7950x3d {
+    use super::*;
+
+    #[test]
+    fn test_amd_avx512_subfeatures() {
+        // Read from the stored feature list, not the host
+        let text = std::fs::read_to_string(raw_path("dump/7950x3d.txt")).expect("Missing dump");
+        let cpu = Cpu::from_dump(&text).expect("Failed to parse dump");
+        assert!(cpu.has_any_avx512());
+
+        let features = cpu.avx512_subfeatures();
+        assert_eq!(features.iter().next().map(|spec| spec.name), Some("F"));
+        assert!(features.contains("BW"));
+        assert!(features.contains("VL"));
+        assert!(features.contains("VBMI"));
+        assert!(features.contains("VBMI2"));
+        assert!(features.iter().all(|spec| spec.group == "AVX512"));
+
+        // Intel-only
+        assert!(!features.contains("4FMAPS"));
+        assert!(!features.contains("FP16"));
+    }
+
+    #[test]
+    fn test_amd_package_type() {
+        set_file_cpuid_provider("dump/7950x3d.txt");
+        assert_eq!(amd_package_type(), Some("AM5"));
+    }
+
+    #[test]
+    fn test_amd_threads_per_core() {
+        set_file_cpuid_provider("dump/7950x3d.txt");
+        assert_eq!(Cpu::threads_per_core(), 2);
+
+        // The same CPU without the SMT level in leaf 0xB, so from
+        // extended leaf 0x8000001E
+        let dump = patched_dump(
+            "dump/7950x3d.txt",
+            &[(
+                "0x0000000B 0x00: eax=0x00000001 ebx=0x00000002 ecx=0x00000100",
+                "0x0000000B 0x00: eax=0x00000000 ebx=0x00000000 ecx=0x00000000",
+            )],
+        );
+        with_cpuid_provider(dump, || {
+            assert_eq!(Cpu::threads_per_core(), 2);
+        });
+    }
+
+    #[test]
+    fn test_topology() {
+        set_file_cpuid_provider("dump/7950x3d.txt");
+
+        let cpu = Cpu::detect();
+
+        assert_eq!(cpu.topology.dies.count, 2);
+        assert_eq!(cpu.topology.threads.count, 32);
+        assert_eq!(cpu.topology.cores.count, 16);
+        assert_eq!(cpu.topology.sockets.count, 1);
+    }
+}
+
+mod amd_5900xt {
+    use super::*;
+
+    fn with_mock_cpu(test: impl FnOnce()) {
+        set_file_cpuid_provider("dump/5900XT.txt");
+        test();
     }
 
     #[test]
-    fn test_intel_aes_support() {
+    fn test_amd_leaf1f_levels() {
         with_mock_cpu(|| {
-            assert!(has_aes());
+            assert!(!is_valid_leaf(LEAF_1F));
+            assert!(leaf1f_levels().is_empty());
         });
     }
 
     #[test]
-    fn test_intel_fpu_support() {
+    fn test_amd_package_type() {
         with_mock_cpu(|| {
-            assert!(has_fpu());
+            assert_eq!(amd_package_type(), Some("AM4"));
         });
     }
 
     #[test]
-    fn test_intel_tsc_support() {
+    fn test_amd_cpu_family() {
+        use rustid::cpuid::vendor::AMDCpuFamily;
+
         with_mock_cpu(|| {
-            assert!(has_tsc());
+            assert_eq!(AMDCpuFamily::detect(), Some(AMDCpuFamily::Ryzen));
         });
-    }
 
-    #[test]
-    fn test_intel_mmx_support() {
-        with_mock_cpu(|| {
-            assert!(has_mmx());
+        // A Milan EPYC, which shares its die with Threadripper Pro, so needs
+        // the SP3 package to tell them apart
+        let dump = patched_dump(
+            "dump/5900XT.txt",
+            &[
+                (
+                    "0x80000001 0x00: eax=0x00A20F12 ebx=0x20000000",
+                    "0x80000001 0x00: eax=0x00A20F12 ebx=0x40000000",
+                ),
+                ("eax=0x00A20F12", "eax=0x00A00F11"),
+            ],
+        );
+        with_cpuid_provider(dump, || {
+            assert_eq!(amd_package_type(), Some("SP3"));
+            assert_eq!(AMDCpuFamily::from_signature(CpuSignature::detect()), None);
+            assert_eq!(AMDCpuFamily::detect(), Some(AMDCpuFamily::Epyc));
         });
     }
 
     #[test]
-    fn test_intel_ssse3_support() {
+    fn test_amd_system_features() {
+        // Zen 3 doesn't report the TSC-deadline APIC timer mode
         with_mock_cpu(|| {
-            assert!(has_ssse3());
+            assert!(has_vme() && has_de() && has_pse() && has_apic());
+            assert!(!has_tsc_deadline());
+            assert!(!Cpu::detect().features["Other"].contains("TSC-Deadline"));
         });
     }
 
     #[test]
-    fn test_intel_fma_support() {
+    fn test_detect_all() {
+        // Not hybrid, so every core gives the same result
         with_mock_cpu(|| {
-            assert!(has_fma());
+            let all = Cpu::detect_all();
+            assert_eq!(all.len(), 1);
+            assert_eq!(all[0].core_type, None);
+            assert!(all[0].cores.is_empty());
         });
     }
 
     #[test]
-    fn test_intel_cx16_support() {
+    fn test_amd_security_features() {
         with_mock_cpu(|| {
-            assert!(has_cx16());
+            let sec = amd_security_features();
+            assert!(has_clzero());
+            assert!(sec.clzero);
+            assert!(sec.ibpb && sec.ibrs && sec.stibp && sec.ssbd);
+            assert!(sec.stibp_always_on);
+            assert!(sec.ibrs_preferred);
+            assert!(sec.psfd);
+            assert!(!sec.ibrs_always_on);
+            assert!(!sec.virt_ssbd);
+            assert!(!sec.btc_no);
         });
     }
 
     #[test]
-    fn test_intel_rdrand_support() {
+    fn test_amd_vendor_detection() {
         with_mock_cpu(|| {
-            assert!(has_rdrand());
+            let vendor = vendor_str();
+            assert_eq!(&*vendor, VENDOR_AMD);
         });
     }
 
     #[test]
-    fn test_intel_bmi1_support() {
+    fn test_amd_quick_vendor() {
         with_mock_cpu(|| {
-            assert!(has_bmi1());
+            assert_eq!(quick_vendor(), CpuBrand::AMD);
         });
     }
 
     #[test]
-    fn test_intel_bmi2_support() {
+    fn test_amd_lfence_serializing() {
+        // Zen 3 reports LfenceAlwaysSerializing in 8000_0021h
         with_mock_cpu(|| {
-            assert!(has_bmi2());
+            assert_eq!(lfence_serializing(), Some(true));
         });
     }
 
     #[test]
-    fn test_intel_f16c_support() {
+    fn test_amd_brand_string() {
         with_mock_cpu(|| {
-            assert!(has_f16c());
+            let brand = Cpu::detect().display_model_string();
+            assert!(brand.contains("AMD"));
+            assert!(brand.contains("5900"));
         });
     }
-}
-
-mod amd_7950x3d {
-    use super::*;
 
     #[test]
-    fn test_topology() {
-        set_file_cpuid_provider("dump/7950x3d.txt");
-
-        let cpu = Cpu::detect();
-
-        assert_eq!(cpu.topology.dies.count, 2);
-        assert_eq!(cpu.topology.threads.count, 32);
-        assert_eq!(cpu.topology.cores.count, 16);
-        assert_eq!(cpu.topology.sockets.count, 1);
-    }
-}
+    fn test_cpu_from_dump() {
+        let text = std::fs::read_to_string(raw_path("dump/5900XT.txt")).expect("Missing dump");
+        let cpu = Cpu::from_dump(&text).expect("Failed to parse dump");
+        assert_eq!(cpu.arch.code_name, "Vermeer");
+        assert_eq!(cpu.package, Some("AM4"));
+        assert_eq!(
+            cpu.product_line,
+            Some(rustid::cpuid::vendor::AMDCpuFamily::Ryzen)
+        );
 
-mod amd_5900xt {
-    use super::*;
-
-    fn with_mock_cpu(test: impl FnOnce()) {
-        set_file_cpuid_provider("dump/5900XT.txt");
-        test();
-    }
-
-    #[test]
-    fn test_amd_vendor_detection() {
+        // The thread's own provider is put back afterwards
         with_mock_cpu(|| {
-            let vendor = vendor_str();
-            assert_eq!(&*vendor, VENDOR_AMD);
+            assert_eq!(Cpu::from_dump(&text), Ok(Cpu::detect()));
+            assert_eq!(vendor_str().as_str(), VENDOR_AMD);
         });
     }
 
     #[test]
-    fn test_amd_brand_string() {
+    fn test_amd_not_apu() {
         with_mock_cpu(|| {
-            let brand = Cpu::detect().display_model_string();
-            assert!(brand.contains("AMD"));
-            assert!(brand.contains("5900"));
+            let cpu = Cpu::detect();
+            assert!(!cpu.arch.is_apu());
+            assert!(!cpu.display_model_string().contains("Radeon"));
+        });
+    }
+
+    #[test]
+    fn test_amd_apu_without_igpu() {
+        // A Ryzen 5 5500 is a Cezanne die with the GPU fused off
+        let dump = patched_dump(
+            "dump/5900XT.txt",
+            &[
+                ("eax=0x00A20F12", "eax=0x00A50F00"),
+                (
+                    "eax=0x20444D41 ebx=0x657A7952 ecx=0x2039206E edx=0x30303935",
+                    "eax=0x20444D41 ebx=0x657A7952 ecx=0x2035206E edx=0x30303535",
+                ),
+                (
+                    "eax=0x31205458 ebx=0x6F432D36 ecx=0x50206572 edx=0x65636F72",
+                    "eax=0x00000000 ebx=0x00000000 ecx=0x00000000 edx=0x00000000",
+                ),
+                (
+                    "eax=0x726F7373 ebx=0x20202020 ecx=0x20202020 edx=0x00202020",
+                    "eax=0x00000000 ebx=0x00000000 ecx=0x00000000 edx=0x00000000",
+                ),
+            ],
+        );
+        with_cpuid_provider(dump, || {
+            let cpu = Cpu::detect();
+            assert_eq!(cpu.arch.code_name, "Cezanne");
+            assert!(cpu.arch.is_apu());
+            assert_eq!(cpu.display_model_string(), "AMD Ryzen 5 5500");
         });
     }
 
@@ -610,6 +2115,208 @@ mod amd_5900xt {
         });
     }
 
+    #[test]
+    fn test_amd_rdt_monitoring() {
+        with_mock_cpu(|| {
+            let rdt = rdt_monitoring().expect("Missing RDT monitoring");
+            assert_eq!(rdt.max_rmid, 255);
+            assert!(rdt.l3.is_some());
+        });
+    }
+
+    #[test]
+    fn test_amd_rdt_allocation() {
+        with_mock_cpu(|| {
+            let rdt = rdt_allocation().expect("Missing RDT allocation");
+            assert!(rdt.l3_cat.is_some());
+            assert_eq!(rdt.l2_cat, None);
+            assert_eq!(rdt.mba, None);
+        });
+    }
+
+    #[test]
+    fn test_amd_suggested_rustflags() {
+        with_mock_cpu(|| {
+            let cpu = Cpu::detect();
+            assert_eq!(cpu.suggested_target_cpu(), Some("znver3"));
+
+            let features = cpu.suggested_target_features();
+            assert!(features.starts_with("+sse,+sse2,"));
+            assert!(features.contains("+avx2"));
+            assert!(features.contains("+sahf"));
+            assert!(!features.contains("+avx512f"));
+
+            assert_eq!(
+                cpu.suggested_rustflags(),
+                format!("-C target-cpu=znver3 -C target-feature={features}")
+            );
+        });
+
+        // A Cpu from a dump keeps its features, whatever CPU is current
+        let text = std::fs::read_to_string(raw_path("dump/5900XT.txt")).expect("Missing dump");
+        let cpu = Cpu::from_dump(&text).expect("Failed to parse dump");
+        set_file_cpuid_provider("dump/p6x2.txt");
+        assert!(cpu.suggested_target_features().contains("+avx2"));
+    }
+
+    #[test]
+    fn test_amd_baseline_features() {
+        with_mock_cpu(|| {
+            let cpu = Cpu::detect();
+            let arch = cpu.arch.micro_arch;
+            assert!(arch.baseline_features().contains("AVX2"));
+            assert!(arch.missing_baseline_features().is_empty());
+            assert!(arch.feature_contradictions(cpu.feature_set()).is_empty());
+        });
+    }
+
+    #[test]
+    fn test_amd_lahf_lm() {
+        with_mock_cpu(|| {
+            assert!(has_lahf_lm());
+            assert!(get_feature_list()["Base"].contains("LAHF-SAHF"));
+        });
+    }
+
+    #[test]
+    fn test_amd_stepping_name() {
+        with_mock_cpu(|| {
+            assert_eq!(Cpu::detect().stepping_name(), Some("B2"));
+        });
+    }
+
+    #[test]
+    fn test_amd_raw_brand_bytes() {
+        with_mock_cpu(|| {
+            let bytes = raw_brand_bytes();
+            let brand = std::str::from_utf8(&bytes).expect("Brand string isn't ASCII");
+            // The padding survives, unlike in the model string
+            assert_eq!(brand, "AMD Ryzen 9 5900XT 16-Core Processor           \0");
+        });
+    }
+
+    #[test]
+    fn test_amd_no_serialize() {
+        with_mock_cpu(|| {
+            assert!(!has_serialize());
+        });
+    }
+
+    #[test]
+    fn test_amd_no_thread_director() {
+        with_mock_cpu(|| {
+            assert!(!has_hybrid());
+            assert_eq!(thread_director(), None);
+        });
+    }
+
+    #[test]
+    fn test_amd_no_hreset() {
+        with_mock_cpu(|| {
+            assert!(!has_hreset());
+            assert_eq!(history_reset(), None);
+        });
+    }
+
+    #[test]
+    fn test_amd_bytes_round_trip() {
+        let dump = CpuDump::parse_file(raw_path("dump/5900XT.txt"));
+        let cpu = with_cpuid_provider(dump, Cpu::detect);
+        let bytes = cpu.to_bytes();
+        assert!(bytes.len() <= 256);
+
+        // Decoding doesn't depend on the active CPUID provider, so the
+        // dump isn't needed any more
+        let decoded = Cpu::from_bytes(&bytes).expect("Failed to decode");
+        assert_eq!(decoded.arch, cpu.arch);
+        assert_eq!(decoded.signature, cpu.signature);
+        assert_eq!(decoded.brand_id, cpu.brand_id);
+        assert_eq!(decoded.has_cpuid, cpu.has_cpuid);
+        assert_eq!(decoded.hyp_vendor_str, cpu.hyp_vendor_str);
+        assert_eq!(decoded.easter_egg, cpu.easter_egg);
+        assert_eq!(decoded.core_type, cpu.core_type);
+        assert_eq!(decoded.features, cpu.features);
+        assert_eq!(decoded.topology.cores.count, cpu.topology.cores.count);
+        assert_eq!(decoded.topology.threads.count, cpu.topology.threads.count);
+        assert_eq!(decoded.topology.speed, cpu.topology.speed);
+        assert_eq!(decoded.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_amd_apic_id_layout() {
+        with_mock_cpu(|| {
+            let layout = amd_apic_id_layout().expect("Missing APIC ID layout");
+            assert_eq!(
+                layout,
+                AmdApicIdLayout {
+                    threads: 32,
+                    apic_id_size: 5
+                }
+            );
+            assert_eq!(layout.split_apic_id(0x25), (1, 5));
+            assert!(has_ext_apic_space());
+        });
+    }
+
+    #[test]
+    fn test_amd_cpuid_maxval_not_limited() {
+        with_mock_cpu(|| {
+            assert_eq!(cpuid_maxval_limited(), Some(false));
+        });
+    }
+
+    #[test]
+    fn test_amd_lzcnt() {
+        with_mock_cpu(|| {
+            assert!(has_lzcnt());
+            assert!(lzcnt_is_real());
+        });
+    }
+
+    #[test]
+    fn test_amd_protection_keys() {
+        with_mock_cpu(|| {
+            assert!(has_pku());
+            assert!(has_ospke());
+
+            let security = get_feature_list();
+            let security = security.get("Security").expect("Missing Security features");
+            assert!(security.contains("PKU"));
+            assert!(security.contains("OSPKE"));
+        });
+    }
+
+    #[test]
+    fn test_amd_feature_os_status() {
+        with_mock_cpu(|| {
+            let status = |name: &str| {
+                feature_status()
+                    .into_iter()
+                    .find(|(spec, _)| spec.name == name)
+                    .map(|(_, status)| status)
+            };
+
+            assert!(has_osxsave());
+            assert_eq!(xcr0(), None);
+
+            // XCR0 isn't in the dump
+            assert_eq!(status("AVX2"), Some(FeatureStatus::OsUnknown));
+            assert_eq!(status("PKU"), Some(FeatureStatus::Usable));
+            assert_eq!(status("SSE4.2"), Some(FeatureStatus::Usable));
+            assert_eq!(status("AMD-V"), Some(FeatureStatus::Usable));
+            assert_eq!(status("F"), None);
+        });
+    }
+
+    #[test]
+    fn test_amd_cet() {
+        // Zen 3 has shadow stacks, but not indirect branch tracking
+        with_mock_cpu(|| {
+            assert!(has_cet_ss());
+            assert!(!has_cet_ibt());
+        });
+    }
+
     #[test]
     fn test_amd_mmx_support() {
         with_mock_cpu(|| {
@@ -676,6 +2383,97 @@ mod amd_2700u {
         test();
     }
 
+    #[test]
+    fn test_effective_max_leaf() {
+        with_mock_cpu(|| {
+            assert_eq!(effective_max_leaf(), 0xD);
+            assert_eq!(effective_max_extended_leaf(), 0x8000_001F);
+        });
+
+        // The same CPU, reporting max leaves past the ones it fills in
+        let dump = patched_dump(
+            "dump/2700U.txt",
+            &[
+                (
+                    "0x00000000 0x00: eax=0x0000000D",
+                    "0x00000000 0x00: eax=0x00000014",
+                ),
+                (
+                    "0x80000000 0x00: eax=0x8000001F",
+                    "0x80000000 0x00: eax=0x80000028",
+                ),
+            ],
+        );
+        with_cpuid_provider(dump, || {
+            assert_eq!(max_leaf(), 0x14);
+            assert_eq!(effective_max_leaf(), 0xD);
+            assert_eq!(max_extended_leaf(), 0x8000_0028);
+            assert_eq!(effective_max_extended_leaf(), 0x8000_001F);
+        });
+    }
+
+    #[test]
+    fn test_amd_package_type() {
+        with_mock_cpu(|| {
+            assert_eq!(amd_package_type(), Some("FP5"));
+        });
+    }
+
+    #[test]
+    fn test_amd_security_features() {
+        // Raven Ridge only reports CLZERO and IBPB here
+        with_mock_cpu(|| {
+            let sec = amd_security_features();
+            assert!(sec.clzero);
+            assert!(sec.ibpb);
+            assert!(!sec.ibrs && !sec.stibp && !sec.ssbd);
+        });
+    }
+
+    #[test]
+    fn test_amd_apic_id_layout() {
+        with_mock_cpu(|| {
+            assert_eq!(
+                amd_apic_id_layout(),
+                Some(AmdApicIdLayout {
+                    threads: 8,
+                    apic_id_size: 4
+                })
+            );
+        });
+
+        // The same CPU, without ApicIdSize, as on CPUs from before it was defined
+        let dump = patched_dump("dump/2700U.txt", &[("ecx=0x00004007", "ecx=0x00000007")]);
+        with_cpuid_provider(dump, || {
+            let layout = amd_apic_id_layout().expect("Missing APIC ID layout");
+            assert_eq!(layout.apic_id_size, 3);
+            assert_eq!(amd_logical_cores(), 8);
+        });
+    }
+
+    #[test]
+    fn test_amd_sev() {
+        with_mock_cpu(|| {
+            assert_eq!(
+                amd_sev(),
+                SevInfo {
+                    sme: true,
+                    sev: true,
+                    sev_es: true,
+                    sev_snp: false,
+                    c_bit: 47,
+                    encrypted_guests: 15,
+                }
+            );
+        });
+
+        // Shown from the detected values, not the CPU running the test
+        let cpu = with_cpuid_provider(CpuDump::parse_file(raw_path("dump/2700U.txt")), Cpu::detect);
+        let mut out = String::new();
+        cpu.emit(CliFlags::default(), &mut out);
+        assert!(out.contains("SME SEV SEV-ES"));
+    }
+
     #[test]
     fn test_amd_vendor_detection() {
         with_mock_cpu(|| {
@@ -684,6 +2482,39 @@ mod amd_2700u {
         });
     }
 
+    #[test]
+    fn test_amd_core_counts() {
+        let leaves = record_leaves("dump/2700U.txt", || {
+            assert_eq!(
+                core_counts(),
+                CoreCounts {
+                    logical: 8,
+                    physical: 4
+                }
+            );
+        });
+
+        assert!(leaves.contains(&EXT_LEAF_8));
+        assert!(leaves.contains(&EXT_LEAF_1E));
+        assert!(!leaves.contains(&LEAF_0B));
+    }
+
+    #[test]
+    fn test_amd_no_fast_rep_movsb() {
+        with_mock_cpu(|| {
+            assert!(!has_erms());
+            assert!(!has_fsrm());
+        });
+    }
+
+    #[test]
+    fn test_amd_lfence_serializing() {
+        // Zen 1 predates 8000_0021h, so it's up to the MSR
+        with_mock_cpu(|| {
+            assert_eq!(lfence_serializing(), None);
+        });
+    }
+
     #[test]
     fn test_amd_brand_string() {
         with_mock_cpu(|| {
@@ -692,6 +2523,19 @@ mod amd_2700u {
         });
     }
 
+    #[test]
+    fn test_amd_apu() {
+        // Raven Ridge is an APU, and the brand string already names the GPU
+        with_mock_cpu(|| {
+            let cpu = Cpu::detect();
+            assert!(cpu.arch.is_apu());
+            assert_eq!(
+                cpu.display_model_string(),
+                "AMD Ryzen 7 PRO 2700U w/ Radeon Vega Mobile Gfx"
+            );
+        });
+    }
+
     #[test]
     fn test_amd_signature() {
         with_mock_cpu(|| {
@@ -724,6 +2568,25 @@ mod zhaoxin_kx5640 {
         test();
     }
 
+    #[test]
+    fn test_zhaoxin_threads_per_core() {
+        // HTT is set for the multiple cores, but there is no SMT
+        with_mock_cpu(|| {
+            assert!(has_ht());
+            assert_eq!(Cpu::threads_per_core(), 1);
+        });
+    }
+
+    #[test]
+    fn test_zhaoxin_spec_ctrl() {
+        with_mock_cpu(|| {
+            let spec = intel_spec_ctrl();
+            assert!(spec.ibrs_ibpb);
+            assert!(spec.arch_capabilities);
+            assert!(!spec.stibp && !spec.ssbd && !spec.l1d_flush);
+        });
+    }
+
     #[test]
     fn test_zhaoxin_vendor_detection() {
         with_mock_cpu(|| {
@@ -878,6 +2741,22 @@ mod via_c7d {
         });
     }
 
+    #[test]
+    fn test_via_vendor_string() {
+        let dump = patched_dump(
+            "dump/c7d.txt",
+            &[(
+                "ebx=0x746e6543 ecx=0x736c7561 edx=0x48727561",
+                "ebx=0x20414956 ecx=0x20414956 edx=0x20414956",
+            )],
+        );
+        with_cpuid_provider(dump, || {
+            assert_eq!(&*vendor_str(), VENDOR_VIA);
+            assert!(is_centaur());
+            assert_eq!(Cpu::detect().arch.micro_arch, MicroArch::Esther);
+        });
+    }
+
     #[test]
     fn test_via_brand_string() {
         with_mock_cpu(|| {